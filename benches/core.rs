@@ -0,0 +1,180 @@
+// benchmarks for the hot paths: the physics update with a crowded field, particles, collision queries, and
+// the software rasteriser that draws every frame. run them with `cargo bench`, or `cargo bench -- render` for
+// the ones whose names contain "render". each is warmed up, then timed in batches, and the mean and the fastest
+// batch's time per iteration are printed so runs before and after a change can be compared.
+// the update benchmarks time a fresh game in each batch: left to run, pickups and lost balls soon change
+// how many balls there are and the work done per frame with them
+
+use game_loop::collision::{self, SpatialHash};
+use game_loop::config::Config;
+use game_loop::crt::CrtFilter;
+use game_loop::particles::Particles;
+use game_loop::renderer::{PixelBuffer, Renderer};
+use game_loop::rng::Rng;
+use game_loop::{Game, GameMode, GameObject};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const WARM_UP: Duration = Duration::from_millis(300);
+const MEASURE: Duration = Duration::from_secs(1);
+const BATCHES: u32 = 20;
+const WIDTH: usize = 800;
+const HEIGHT: usize = 600;
+const GAME_FRAMES: u32 = 120; // The first two seconds of a game, before the first pickup falls
+
+fn main() {
+    // Cargo passes --bench, anything else is a filter on the names
+    let filters: Vec<String> = std::env::args().skip(1).filter(|arg| !arg.starts_with("--")).collect();
+    let run = |name: &str| filters.is_empty() || filters.iter().any(|filter| name.contains(filter.as_str()));
+
+    for balls in [1, 50, 200] {
+        let name = format!("update/{} balls", balls);
+        if run(&name) {
+            bench_game(&name, balls);
+        }
+    }
+
+    if run("particles/update") {
+        let mut particles = Particles::new();
+        bench("particles/update", || {
+            if particles.is_empty() {
+                particles.burst(400.0, 300.0, 2000, 300.0, 0xFFFFFFFF);
+            }
+            particles.update(1.0 / 120.0);
+        });
+    }
+
+    if run("collision/query") {
+        // 500 blocks scattered over the field, and a ball looking for what it might hit
+        let mut rng = Rng::new(1);
+        let blocks: Vec<GameObject> = (0..500)
+            .map(|_| GameObject {
+                x: rng.range(0.0, WIDTH as f32),
+                y: rng.range(0.0, HEIGHT as f32),
+                width: 30.0,
+                height: 15.0,
+                vel_x: 0.0,
+                vel_y: 0.0,
+            })
+            .collect();
+        let mut grid = SpatialHash::new(64.0);
+        grid.rebuild(blocks.iter().enumerate());
+        let ball = GameObject { x: 400.0, y: 300.0, width: 10.0, height: 10.0, vel_x: 0.0, vel_y: 0.0 };
+        bench("collision/query", || {
+            black_box(grid.query(black_box(&ball)).into_iter().filter(|&i| collision::overlaps(&ball, &blocks[i])).count());
+        });
+    }
+
+    let mut pixels = vec![0u32; WIDTH * HEIGHT];
+    if run("render/clear") {
+        bench("render/clear", || PixelBuffer::new(&mut pixels, WIDTH).clear(black_box(0xFF101020)));
+    }
+    if run("render/fill_rect") {
+        bench("render/fill_rect", || {
+            let mut buffer = PixelBuffer::new(&mut pixels, WIDTH);
+            for i in 0..100 {
+                buffer.fill_rect(black_box(i as f32 * 7.5), 100.0, 70.0, 20.0, 0xFFE04050);
+            }
+        });
+    }
+    if run("render/fill_circle") {
+        bench("render/fill_circle", || {
+            let mut buffer = PixelBuffer::new(&mut pixels, WIDTH);
+            for i in 0..100 {
+                buffer.fill_circle(black_box(i as f32 * 7.5 + 0.3), 300.0, 15.0, 0xFFFFFFFF);
+            }
+        });
+    }
+    if run("render/draw_text") {
+        bench("render/draw_text", || {
+            let mut buffer = PixelBuffer::new(&mut pixels, WIDTH);
+            buffer.draw_text(10, 10, black_box("SCORE 12345  COMBO 12 X3"), 3, 0xFFFFFFFF);
+        });
+    }
+    if run("render/frame") {
+        let mut game = crowded_game(50);
+        for _ in 0..60 {
+            game.step(); // Let the balls spread out and the trails fill in
+        }
+        bench("render/frame", || game.render_into(&mut pixels));
+    }
+    // Split into bands on several threads with --features parallel
+    if run("render/crt") {
+        let mut crt = CrtFilter::new(WIDTH, HEIGHT);
+        bench("render/crt", || crt.apply(black_box(&mut pixels)));
+    }
+}
+
+// A headless 1 player game with extra balls in play. The paddle is as wide as the field so they stay in play,
+// and there are lives enough that it never ends while being timed
+fn crowded_game(balls: usize) -> Game {
+    let config = Config {
+        window_width: WIDTH,
+        window_height: HEIGHT,
+        paddle_width: WIDTH as f32,
+        lives: 1_000_000,
+        ..Config::default()
+    };
+    let mut game = Game::new(config, true, 1).expect("a headless game needs no window");
+    game.start_game(GameMode::Solo);
+    game.step(); // Serves straight away
+    for i in 1..balls {
+        let angle = i as f32 * 2.4;
+        game.spawn_ball(WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0, 300.0 * angle.cos(), 300.0 * angle.sin());
+    }
+    game
+}
+
+fn bench_game(name: &str, balls: usize) {
+    let mut times = Vec::new();
+    // The first batch warms up
+    for batch in 0..=BATCHES {
+        let mut game = crowded_game(balls);
+        let start = Instant::now();
+        for _ in 0..GAME_FRAMES {
+            game.step();
+        }
+        if batch > 0 {
+            times.push(start.elapsed().as_nanos() as f64 / GAME_FRAMES as f64);
+        }
+    }
+    report(name, &times);
+}
+
+fn bench(name: &str, mut f: impl FnMut()) {
+    // Warm up, and find how many iterations fill a batch
+    let start = Instant::now();
+    let mut iterations: u64 = 0;
+    while start.elapsed() < WARM_UP {
+        f();
+        iterations += 1;
+    }
+    let per_batch = (iterations * MEASURE.as_nanos() as u64 / WARM_UP.as_nanos() as u64 / BATCHES as u64).max(1);
+
+    let mut times = Vec::new();
+    for _ in 0..BATCHES {
+        let start = Instant::now();
+        for _ in 0..per_batch {
+            f();
+        }
+        times.push(start.elapsed().as_nanos() as f64 / per_batch as f64);
+    }
+    report(name, &times);
+}
+
+// Each batch's nanoseconds per iteration
+fn report(name: &str, times: &[f64]) {
+    let mean = times.iter().sum::<f64>() / times.len() as f64;
+    let fastest = times.iter().copied().fold(f64::MAX, f64::min);
+    println!("{:<24} mean {:>12}   fastest {:>12}", name, format_time(mean), format_time(fastest));
+}
+
+fn format_time(nanos: f64) -> String {
+    if nanos >= 1_000_000.0 {
+        format!("{:.2} ms", nanos / 1_000_000.0)
+    } else if nanos >= 1_000.0 {
+        format!("{:.2} µs", nanos / 1_000.0)
+    } else {
+        format!("{:.1} ns", nanos)
+    }
+}
@@ -0,0 +1,138 @@
+// goals to reach in a game, unlocked once and kept for good in the user's data directory next to the high scores.
+// the game reports what happens as events and shows a toast for each achievement they unlock
+
+use crate::clock;
+use crate::config;
+use crate::highscores;
+use std::path::PathBuf;
+
+const FILE_NAME: &str = "achievements.toml";
+const HITS_GOAL: u32 = 100;
+const SURVIVAL_GOAL: f32 = 300.0; // Seconds
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Achievement {
+    Centurion,
+    Survivor,
+    Statue,
+}
+
+pub const ALL: [Achievement; 3] = [Achievement::Centurion, Achievement::Survivor, Achievement::Statue];
+
+// Things that happen in a game that could unlock an achievement
+#[derive(Clone, Copy, Debug)]
+pub enum Event {
+    PaddleHit { hits: u32 },    // Paddle hits so far this game
+    Survived { seconds: f32 },  // Time played this game without it ending
+    Won { paddle_moved: bool }, // Player 1 beat the CPU
+}
+
+impl Achievement {
+    // Name in the save file
+    fn key(self) -> &'static str {
+        match self {
+            Achievement::Centurion => "centurion",
+            Achievement::Survivor => "survivor",
+            Achievement::Statue => "statue",
+        }
+    }
+
+    // Keys of the name and what it takes to unlock, in the language files
+    pub fn name_key(self) -> &'static str {
+        match self {
+            Achievement::Centurion => "achievement.centurion",
+            Achievement::Survivor => "achievement.survivor",
+            Achievement::Statue => "achievement.statue",
+        }
+    }
+
+    pub fn goal_key(self) -> &'static str {
+        match self {
+            Achievement::Centurion => "achievement.centurion_goal",
+            Achievement::Survivor => "achievement.survivor_goal",
+            Achievement::Statue => "achievement.statue_goal",
+        }
+    }
+
+    fn unlocked_by(self, event: Event) -> bool {
+        match (self, event) {
+            (Achievement::Centurion, Event::PaddleHit { hits }) => hits >= HITS_GOAL,
+            (Achievement::Survivor, Event::Survived { seconds }) => seconds >= SURVIVAL_GOAL,
+            (Achievement::Statue, Event::Won { paddle_moved }) => !paddle_moved,
+            _ => false,
+        }
+    }
+}
+
+pub struct Achievements {
+    unlocked: Vec<(Achievement, u64)>, // With when each was unlocked, seconds since the unix epoch
+    path: Option<PathBuf>,
+}
+
+impl Achievements {
+    // Load the unlocked achievements, an unreadable or missing file just starts with none
+    pub fn load() -> Self {
+        let path = highscores::data_dir().map(|dir| dir.join(FILE_NAME));
+        let unlocked = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|text| parse_unlocked(&text))
+            .unwrap_or_default();
+        Achievements { unlocked, path }
+    }
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.iter().any(|&(unlocked, _)| unlocked == achievement)
+    }
+
+    // Unlock anything the event earns, saving straight away, and return what was newly unlocked
+    pub fn record(&mut self, event: Event) -> Vec<Achievement> {
+        let earned: Vec<Achievement> = ALL
+            .into_iter()
+            .filter(|&achievement| !self.is_unlocked(achievement) && achievement.unlocked_by(event))
+            .collect();
+        if earned.is_empty() {
+            return earned;
+        }
+
+        let timestamp = clock::unix_time().as_secs();
+        self.unlocked.extend(earned.iter().map(|&achievement| (achievement, timestamp)));
+        if let Err(e) = self.save() {
+            eprintln!("Error saving achievements: {}", e);
+        }
+        earned
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()), // Nowhere to save, e.g. no home directory
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let mut text = String::from("[unlocked]\n");
+        for (achievement, timestamp) in &self.unlocked {
+            text.push_str(&format!("{} = {}\n", achievement.key(), timestamp));
+        }
+        std::fs::write(path, text)
+    }
+}
+
+fn parse_unlocked(text: &str) -> Vec<(Achievement, u64)> {
+    let values = match config::parse(text) {
+        Ok(values) => values,
+        Err(e) => {
+            eprintln!("Could not read achievements: {}", e);
+            return Vec::new();
+        }
+    };
+
+    ALL.into_iter()
+        .filter_map(|achievement| {
+            let timestamp = values.get(&format!("unlocked.{}", achievement.key()))?.as_int()?;
+            Some((achievement, timestamp as u64))
+        })
+        .collect()
+}
@@ -0,0 +1,48 @@
+// simple computer opponent for the top paddle
+// the ai only "sees" the ball every reaction_delay seconds and
+// can't move faster than max_speed, so it can be beaten
+
+use crate::GameObject;
+
+pub struct AiController {
+    pub reaction_delay: f32, // seconds between looking at the ball
+    pub max_speed: f32,      // pixels per second
+    time_since_look: f32,
+    target_x: f32,
+}
+
+impl AiController {
+    pub fn new(reaction_delay: f32, max_speed: f32) -> Self {
+        AiController {
+            reaction_delay,
+            max_speed,
+            time_since_look: 0.0,
+            target_x: 0.0,
+        }
+    }
+
+    // Work out the paddle velocity for this frame
+    pub fn update(&mut self, paddle: &GameObject, ball: &GameObject, field_width: f32, delta_time: f32) -> f32 {
+        self.time_since_look += delta_time;
+        if self.time_since_look >= self.reaction_delay {
+            self.time_since_look = 0.0;
+            // Follow the ball when it is heading our way, otherwise drift back to the middle
+            self.target_x = if ball.vel_y < 0.0 {
+                ball.x + ball.width / 2.0
+            } else {
+                field_width / 2.0
+            };
+        }
+
+        let paddle_centre = paddle.x + paddle.width / 2.0;
+        let distance = self.target_x - paddle_centre;
+
+        // Small dead zone stops the paddle jittering around the target
+        if distance.abs() < paddle.width / 8.0 {
+            return 0.0;
+        }
+
+        // Slow down when close so we don't overshoot, capped at max speed
+        (distance * 8.0).clamp(-self.max_speed, self.max_speed)
+    }
+}
@@ -0,0 +1,362 @@
+// loads images for sprites. PNG is decoded here, inflate included, so there's nothing to depend on.
+// 8 and 16 bit greyscale, RGB and RGBA work, as do palette and greyscale images of 1, 2 or 4 bits,
+// which covers what image editors save. interlaced images aren't supported
+
+use std::path::Path;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+// An image as 0xAARRGGBB pixels, alpha 0 is see-through
+#[derive(Clone, Debug)]
+pub struct Texture {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+}
+
+impl Texture {
+    // The pixel that lands at (x, y) when the texture is stretched over width x height
+    pub fn sample(&self, x: usize, y: usize, width: usize, height: usize) -> u32 {
+        let tx = (x * self.width / width.max(1)).min(self.width - 1);
+        let ty = (y * self.height / height.max(1)).min(self.height - 1);
+        self.pixels[ty * self.width + tx]
+    }
+}
+
+pub fn load_png(path: &Path) -> Result<Texture, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    decode_png(&bytes)
+}
+
+pub fn decode_png(bytes: &[u8]) -> Result<Texture, String> {
+    if !bytes.starts_with(&PNG_SIGNATURE) {
+        return Err("not a PNG file".to_string());
+    }
+
+    let mut header = None;
+    let mut palette: Vec<[u8; 3]> = Vec::new();
+    let mut transparency: Vec<u8> = Vec::new(); // Alpha for each palette entry
+    let mut compressed = Vec::new();
+    let mut rest = &bytes[PNG_SIGNATURE.len()..];
+    while rest.len() >= 12 {
+        let len = u32::from_be_bytes(rest[0..4].try_into().unwrap()) as usize;
+        let kind = &rest[4..8];
+        let data = rest.get(8..8 + len).ok_or("truncated chunk")?;
+        match kind {
+            b"IHDR" => header = Some(Header::parse(data)?),
+            b"PLTE" => palette = data.chunks_exact(3).map(|rgb| [rgb[0], rgb[1], rgb[2]]).collect(),
+            b"tRNS" => transparency = data.to_vec(),
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {} // Text, gamma and the rest don't matter here
+        }
+        rest = rest.get(8 + len + 4..).ok_or("truncated chunk")?; // Skip the CRC too
+    }
+    let header = header.ok_or("missing IHDR chunk")?;
+
+    // zlib: a two byte header, the deflate stream and a checksum
+    let raw = inflate(compressed.get(2..).ok_or("missing image data")?)?;
+    let rows = unfilter(&raw, &header)?;
+
+    let mut pixels = Vec::with_capacity(header.width * header.height);
+    for row in rows.chunks_exact(header.row_bytes()) {
+        for x in 0..header.width {
+            pixels.push(header.pixel(row, x, &palette, &transparency));
+        }
+    }
+    Ok(Texture { width: header.width, height: header.height, pixels })
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: usize,
+    colour_type: u8,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Self, String> {
+        if data.len() < 13 {
+            return Err("bad IHDR chunk".to_string());
+        }
+        let header = Header {
+            width: u32::from_be_bytes(data[0..4].try_into().unwrap()) as usize,
+            height: u32::from_be_bytes(data[4..8].try_into().unwrap()) as usize,
+            bit_depth: data[8] as usize,
+            colour_type: data[9],
+        };
+        if data[12] != 0 {
+            return Err("interlaced PNGs aren't supported".to_string());
+        }
+        if header.width == 0 || header.height == 0 {
+            return Err("empty image".to_string());
+        }
+        let depth_ok = match header.colour_type {
+            0 => matches!(header.bit_depth, 1 | 2 | 4 | 8 | 16),
+            3 => matches!(header.bit_depth, 1 | 2 | 4 | 8),
+            2 | 4 | 6 => matches!(header.bit_depth, 8 | 16),
+            _ => false,
+        };
+        if !depth_ok {
+            return Err(format!("unsupported colour type {} at {} bits", header.colour_type, header.bit_depth));
+        }
+        Ok(header)
+    }
+
+    fn channels(&self) -> usize {
+        match self.colour_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    fn row_bytes(&self) -> usize {
+        (self.width * self.channels() * self.bit_depth).div_ceil(8)
+    }
+
+    // Distance back to the same byte of the previous pixel, for the filters
+    fn pixel_bytes(&self) -> usize {
+        (self.channels() * self.bit_depth).div_ceil(8)
+    }
+
+    fn pixel(&self, row: &[u8], x: usize, palette: &[[u8; 3]], transparency: &[u8]) -> u32 {
+        // A channel scaled to 8 bits, 16 bit channels keep their high byte
+        let channel = |n: usize| -> u8 {
+            match self.bit_depth {
+                8 => row[x * self.channels() + n],
+                16 => row[(x * self.channels() + n) * 2],
+                _ => {
+                    let bit = x * self.bit_depth;
+                    let value = (row[bit / 8] >> (8 - self.bit_depth - bit % 8)) & ((1 << self.bit_depth) - 1);
+                    if self.colour_type == 3 {
+                        value // A palette index, not a level
+                    } else {
+                        (value as usize * 255 / ((1 << self.bit_depth) - 1)) as u8
+                    }
+                }
+            }
+        };
+        let argb = |a: u8, r: u8, g: u8, b: u8| (a as u32) << 24 | (r as u32) << 16 | (g as u32) << 8 | b as u32;
+        match self.colour_type {
+            0 => argb(0xFF, channel(0), channel(0), channel(0)),
+            2 => argb(0xFF, channel(0), channel(1), channel(2)),
+            3 => {
+                let index = channel(0) as usize;
+                let [r, g, b] = palette.get(index).copied().unwrap_or_default();
+                argb(transparency.get(index).copied().unwrap_or(0xFF), r, g, b)
+            }
+            4 => argb(channel(1), channel(0), channel(0), channel(0)),
+            _ => argb(channel(3), channel(0), channel(1), channel(2)),
+        }
+    }
+}
+
+// Undo the per row filters, leaving the rows back to back without their filter bytes
+fn unfilter(raw: &[u8], header: &Header) -> Result<Vec<u8>, String> {
+    let row_bytes = header.row_bytes();
+    let bpp = header.pixel_bytes();
+    if raw.len() < header.height * (row_bytes + 1) {
+        return Err("not enough image data".to_string());
+    }
+
+    let mut out = vec![0u8; header.height * row_bytes];
+    for y in 0..header.height {
+        let filter = raw[y * (row_bytes + 1)];
+        let line = &raw[y * (row_bytes + 1) + 1..(y + 1) * (row_bytes + 1)];
+        let (done, current) = out.split_at_mut(y * row_bytes);
+        let previous = if y > 0 { &done[(y - 1) * row_bytes..] } else { &[][..] };
+        let current = &mut current[..row_bytes];
+        for i in 0..row_bytes {
+            let left = if i >= bpp { current[i - bpp] } else { 0 };
+            let up = previous.get(i).copied().unwrap_or(0);
+            let up_left = if i >= bpp { previous.get(i - bpp).copied().unwrap_or(0) } else { 0 };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return Err(format!("unknown filter type {}", filter)),
+            };
+            current[i] = line[i].wrapping_add(predicted);
+        }
+    }
+    Ok(out)
+}
+
+// Whichever of left, up and up left is closest to left + up - up left
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// Reads a deflate stream a bit at a time, least significant bit first
+struct Bits<'a> {
+    data: &'a [u8],
+    position: usize, // In bits
+}
+
+impl Bits<'_> {
+    fn bit(&mut self) -> Result<u32, String> {
+        let byte = self.data.get(self.position / 8).ok_or("image data ends early")?;
+        let bit = (byte >> (self.position % 8)) & 1;
+        self.position += 1;
+        Ok(bit as u32)
+    }
+
+    fn bits(&mut self, count: u32) -> Result<u32, String> {
+        let mut value = 0;
+        for n in 0..count {
+            value |= self.bit()? << n;
+        }
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+}
+
+// A canonical Huffman code, as counts of codes per length and the symbols in code order
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16).filter(|&s| lengths[s as usize] != 0).collect();
+        symbols.sort_by_key(|&s| lengths[s as usize]); // Stable, so equal lengths stay in symbol order
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..16 {
+            code |= bits.bit()? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("bad Huffman code in image data".to_string())
+    }
+}
+
+const LENGTH_BASE: [u16; 29] =
+    [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] =
+    [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+// The order code length code lengths come in, in a dynamic block header
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut bits = Bits { data, position: 0 };
+    let mut out = Vec::new();
+    loop {
+        let is_final = bits.bit()? == 1;
+        match bits.bits(2)? {
+            0 => {
+                bits.align_to_byte();
+                let start = bits.position / 8;
+                let header = data.get(start..start + 4).ok_or("image data ends early")?;
+                let len = u16::from_le_bytes([header[0], header[1]]) as usize;
+                out.extend_from_slice(data.get(start + 4..start + 4 + len).ok_or("image data ends early")?);
+                bits.position = (start + 4 + len) * 8;
+            }
+            1 => {
+                let mut lengths = [8u8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                inflate_block(&mut bits, &mut out, &Huffman::new(&lengths), &Huffman::new(&[5; 30]))?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_codes(&mut bits)?;
+                inflate_block(&mut bits, &mut out, &literals, &distances)?;
+            }
+            _ => return Err("bad block type in image data".to_string()),
+        }
+        if is_final {
+            return Ok(out);
+        }
+    }
+}
+
+fn read_dynamic_codes(bits: &mut Bits) -> Result<(Huffman, Huffman), String> {
+    let literal_count = bits.bits(5)? as usize + 257;
+    let distance_count = bits.bits(5)? as usize + 1;
+    let code_length_count = bits.bits(4)? as usize + 4;
+
+    let mut code_lengths = [0u8; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[symbol] = bits.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat) = match code_lengths.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last().ok_or("bad code lengths in image data")?, 3 + bits.bits(2)?),
+            17 => (0, 3 + bits.bits(3)?),
+            _ => (0, 11 + bits.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() > literal_count + distance_count {
+        return Err("bad code lengths in image data".to_string());
+    }
+    Ok((Huffman::new(&lengths[..literal_count]), Huffman::new(&lengths[literal_count..])))
+}
+
+fn inflate_block(bits: &mut Bits, out: &mut Vec<u8>, literals: &Huffman, distances: &Huffman) -> Result<(), String> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let n = symbol - 257;
+                if n >= LENGTH_BASE.len() {
+                    return Err("bad length in image data".to_string());
+                }
+                let length = LENGTH_BASE[n] as usize + bits.bits(LENGTH_EXTRA[n] as u32)? as usize;
+                let d = distances.decode(bits)? as usize;
+                if d >= DISTANCE_BASE.len() {
+                    return Err("bad distance in image data".to_string());
+                }
+                let distance = DISTANCE_BASE[d] as usize + bits.bits(DISTANCE_EXTRA[d] as u32)? as usize;
+                if distance > out.len() {
+                    return Err("bad distance in image data".to_string());
+                }
+                // The copy can overlap what it's writing, so it goes a byte at a time
+                let start = out.len() - distance;
+                for i in 0..length {
+                    out.push(out[start + i]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,394 @@
+// sound effects and music, synthesised at startup and mixed on a background thread
+// there is no audio crate, so the mixed samples are streamed as raw pcm into
+// whichever command line player is available (aplay, pacat or sox's play),
+// if none can be started the game simply stays silent
+
+use std::io::Write;
+use std::process::{self, Child, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+
+pub const SAMPLE_RATE: u32 = 44_100;
+const CHANNELS: usize = 2;
+const CHUNK_FRAMES: usize = 512; // ~12 ms per write keeps latency low
+const MUSIC_FADE_TIME: f32 = 2.0; // seconds
+pub const VOLUME_STEP: f32 = 0.1;
+
+// Raw pcm players to try, in order of preference
+const PLAYERS: [(&str, &[&str]); 3] = [
+    ("aplay", &["-q", "-t", "raw", "-f", "S16_LE", "-r", "44100", "-c", "2", "--buffer-time=50000"]),
+    ("pacat", &["--raw", "--format=s16le", "--rate=44100", "--channels=2", "--latency-msec=50"]),
+    ("play", &["-q", "-t", "raw", "-r", "44100", "-e", "signed", "-b", "16", "-c", "2", "-"]),
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Sound {
+    WallBounce,
+    PaddleHit,
+    LifeLost,
+    GameOver,
+    BrickBreak,
+    LevelCleared,
+    LaserShot,
+}
+
+const SOUNDS: [Sound; 7] = [
+    Sound::WallBounce,
+    Sound::PaddleHit,
+    Sound::LifeLost,
+    Sound::GameOver,
+    Sound::BrickBreak,
+    Sound::LevelCleared,
+    Sound::LaserShot,
+];
+
+// Where a sound seems to come from, to follow the ball by ear
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Cue {
+    pub pan: f32,   // -1.0 all the way left to 1.0 all the way right
+    pub pitch: f32, // Played this many times faster, and so higher
+}
+
+impl Cue {
+    pub const CENTRE: Cue = Cue { pan: 0.0, pitch: 1.0 };
+}
+
+// Messages from the game to the mixer thread
+enum Command {
+    Play(Sound, Cue),
+    StartMusic,
+    FadeOutMusic,
+    StopMusic,
+    SetMusicVolume(f32),
+}
+
+pub struct AudioEngine {
+    sender: Option<Sender<Command>>,
+    pub music_volume: f32, // 0.0 to 1.0
+}
+
+impl AudioEngine {
+    // Start the mixer thread, falling back to a silent engine if no player is available
+    // Music comes from a 16 bit wav file if one is given, otherwise a built in tune
+    pub fn new(music_file: Option<&str>, music_volume: f32) -> Self {
+        let child = match spawn_player() {
+            Some(child) => child,
+            None => {
+                eprintln!("No audio player found (tried aplay, pacat and play), sound is disabled");
+                return AudioEngine::silent();
+            }
+        };
+
+        let music = match music_file.map(load_wav) {
+            Some(Ok(samples)) => samples,
+            Some(Err(e)) => {
+                eprintln!("Could not load music: {}, using the built in tune", e);
+                builtin_music()
+            }
+            None => builtin_music(),
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || run_mixer(child, receiver, music, music_volume));
+        AudioEngine {
+            sender: Some(sender),
+            music_volume,
+        }
+    }
+
+    pub fn silent() -> Self {
+        AudioEngine {
+            sender: None,
+            music_volume: 0.0,
+        }
+    }
+
+    fn send(&self, command: Command) {
+        if let Some(sender) = &self.sender {
+            // The mixer thread only goes away if the player died, nothing useful to do then
+            let _ = sender.send(command);
+        }
+    }
+
+    pub fn play(&self, sound: Sound) {
+        self.play_cued(sound, Cue::CENTRE);
+    }
+
+    pub fn play_cued(&self, sound: Sound, cue: Cue) {
+        self.send(Command::Play(sound, cue));
+    }
+
+    // Start the music loop from the beginning
+    pub fn start_music(&self) {
+        self.send(Command::StartMusic);
+    }
+
+    pub fn fade_out_music(&self) {
+        self.send(Command::FadeOutMusic);
+    }
+
+    pub fn stop_music(&self) {
+        self.send(Command::StopMusic);
+    }
+
+    pub fn change_music_volume(&mut self, change: f32) {
+        self.music_volume = (self.music_volume + change).clamp(0.0, 1.0);
+        self.send(Command::SetMusicVolume(self.music_volume));
+    }
+}
+
+fn spawn_player() -> Option<Child> {
+    PLAYERS.iter().find_map(|(program, args)| {
+        process::Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()
+    })
+}
+
+// A sound that is currently playing
+struct Voice {
+    sound: usize,  // index into SOUNDS
+    position: f32, // Samples into the sound, moving on by the cue's pitch each frame
+    cue: Cue,
+}
+
+// The looping music track and where we are in it
+struct Music {
+    samples: Vec<f32>,
+    position: usize,
+    playing: bool,
+    volume: f32,
+    fade_remaining: Option<usize>, // samples left until silent while fading out
+}
+
+impl Music {
+    fn next_sample(&mut self) -> f32 {
+        if !self.playing || self.samples.is_empty() {
+            return 0.0;
+        }
+
+        let mut sample = self.samples[self.position] * self.volume;
+        self.position = (self.position + 1) % self.samples.len();
+
+        if let Some(remaining) = self.fade_remaining {
+            let fade_length = MUSIC_FADE_TIME * SAMPLE_RATE as f32;
+            sample *= remaining as f32 / fade_length;
+            if remaining == 0 {
+                self.playing = false;
+                self.fade_remaining = None;
+            } else {
+                self.fade_remaining = Some(remaining - 1);
+            }
+        }
+        sample
+    }
+}
+
+fn run_mixer(mut child: Child, receiver: Receiver<Command>, music: Vec<f32>, music_volume: f32) {
+    // Render every sound once up front so the mixer only has to copy samples
+    let sounds: Vec<Vec<f32>> = SOUNDS.iter().map(|&sound| synthesise(sound)).collect();
+
+    let mut stdin = match child.stdin.take() {
+        Some(stdin) => stdin,
+        None => return,
+    };
+    let mut voices: Vec<Voice> = Vec::new();
+    let mut music = Music {
+        samples: music,
+        position: 0,
+        playing: false,
+        volume: music_volume,
+        fade_remaining: None,
+    };
+    let mut mix = vec![[0.0f32; CHANNELS]; CHUNK_FRAMES];
+    let mut bytes = Vec::with_capacity(CHUNK_FRAMES * CHANNELS * 2);
+
+    loop {
+        // Pick up any newly triggered sounds and music changes
+        loop {
+            match receiver.try_recv() {
+                Ok(Command::Play(sound, cue)) => {
+                    if let Some(index) = SOUNDS.iter().position(|&s| s == sound) {
+                        voices.push(Voice {
+                            sound: index,
+                            position: 0.0,
+                            cue,
+                        });
+                    }
+                }
+                Ok(Command::StartMusic) => {
+                    music.playing = true;
+                    music.position = 0;
+                    music.fade_remaining = None;
+                }
+                Ok(Command::FadeOutMusic) => {
+                    if music.playing && music.fade_remaining.is_none() {
+                        music.fade_remaining = Some((MUSIC_FADE_TIME * SAMPLE_RATE as f32) as usize);
+                    }
+                }
+                Ok(Command::StopMusic) => music.playing = false,
+                Ok(Command::SetMusicVolume(volume)) => music.volume = volume,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    let _ = child.kill();
+                    return;
+                }
+            }
+        }
+
+        // Mix the music and active voices into one chunk
+        mix.iter_mut().for_each(|frame| *frame = [music.next_sample(); CHANNELS]);
+        for voice in voices.iter_mut() {
+            let samples = &sounds[voice.sound];
+            // Panning turns the far side down, leaving a centred sound as loud as ever
+            let gains = [(1.0 - voice.cue.pan).min(1.0), (1.0 + voice.cue.pan).min(1.0)];
+            for frame in mix.iter_mut() {
+                let Some(&sample) = samples.get(voice.position as usize) else {
+                    break;
+                };
+                for (out, gain) in frame.iter_mut().zip(gains) {
+                    *out += sample * gain;
+                }
+                voice.position += voice.cue.pitch;
+            }
+        }
+        voices.retain(|voice| (voice.position as usize) < sounds[voice.sound].len());
+
+        // Blocking on the pipe is what paces this thread to real time
+        bytes.clear();
+        for sample in mix.iter().flatten() {
+            let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        if stdin.write_all(&bytes).is_err() {
+            return; // Player went away
+        }
+    }
+}
+
+// Build the samples for a sound effect
+fn synthesise(sound: Sound) -> Vec<f32> {
+    match sound {
+        Sound::WallBounce => tone(440.0, 440.0, 0.05, 0.25),
+        Sound::PaddleHit => tone(880.0, 880.0, 0.07, 0.3),
+        Sound::LifeLost => tone(400.0, 100.0, 0.5, 0.3),
+        Sound::BrickBreak => tone(1200.0, 600.0, 0.06, 0.25),
+        Sound::LaserShot => tone(1800.0, 900.0, 0.04, 0.15),
+        Sound::LevelCleared => {
+            let mut samples = tone(523.0, 523.0, 0.12, 0.3);
+            samples.extend(tone(659.0, 659.0, 0.12, 0.3));
+            samples.extend(tone(784.0, 1047.0, 0.3, 0.3));
+            samples
+        }
+        Sound::GameOver => {
+            let mut samples = tone(392.0, 392.0, 0.25, 0.3);
+            samples.extend(tone(330.0, 330.0, 0.25, 0.3));
+            samples.extend(tone(262.0, 196.0, 0.6, 0.3));
+            samples
+        }
+    }
+}
+
+// A short looping chiptune: a triangle wave bass line under a square wave arpeggio
+fn builtin_music() -> Vec<f32> {
+    const BEAT: f32 = 0.25; // seconds per note, 120 bpm in eighth notes
+    // Root notes of the chord for each bar (Am, F, C, G), as frequencies in Hz
+    const BARS: [f32; 4] = [220.0, 174.61, 261.63, 196.0];
+    // Arpeggio pattern as semitones above the root
+    const PATTERN: [i32; 8] = [0, 7, 12, 7, 3, 7, 12, 15];
+
+    let note_length = (BEAT * SAMPLE_RATE as f32) as usize;
+    let mut samples = Vec::with_capacity(BARS.len() * PATTERN.len() * note_length);
+    for (bar, &root) in BARS.iter().enumerate() {
+        // Major chords for F, C and G, minor for Am
+        let third = if bar == 0 { 3 } else { 4 };
+        for &step in PATTERN.iter() {
+            let semitones = if step % 12 == 3 { step - 3 + third } else { step };
+            let lead_freq = root * 2.0f32.powf(semitones as f32 / 12.0);
+            let bass_freq = root / 2.0;
+            for i in 0..note_length {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let envelope = 1.0 - i as f32 / note_length as f32;
+                let lead = if (t * lead_freq).fract() < 0.5 { 1.0 } else { -1.0 };
+                let bass = 4.0 * ((t * bass_freq).fract() - 0.5).abs() - 1.0;
+                samples.push(lead * 0.08 * envelope + bass * 0.15);
+            }
+        }
+    }
+    samples
+}
+
+// Read a 16 bit pcm wav file as mono samples at our sample rate
+fn load_wav(path: &str) -> Result<Vec<f32>, String> {
+    let data = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err(format!("{}: not a wav file", path));
+    }
+
+    let mut channels = 0usize;
+    let mut rate = 0u32;
+    let mut pcm: Option<&[u8]> = None;
+    let mut offset = 12;
+    // Walk the chunks looking for the format description and the sample data
+    while offset + 8 <= data.len() {
+        let id = &data[offset..offset + 4];
+        let size = u32::from_le_bytes([data[offset + 4], data[offset + 5], data[offset + 6], data[offset + 7]]) as usize;
+        let body = &data[offset + 8..(offset + 8 + size).min(data.len())];
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let format = u16::from_le_bytes([body[0], body[1]]);
+                let bits = u16::from_le_bytes([body[14], body[15]]);
+                if format != 1 || bits != 16 {
+                    return Err(format!("{}: only 16 bit pcm wav files are supported", path));
+                }
+                channels = u16::from_le_bytes([body[2], body[3]]) as usize;
+                rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+            }
+            b"data" => pcm = Some(body),
+            _ => {}
+        }
+        offset += 8 + size + size % 2; // Chunks are padded to an even length
+    }
+
+    let pcm = pcm.ok_or_else(|| format!("{}: no sample data", path))?;
+    if channels == 0 || rate == 0 {
+        return Err(format!("{}: missing format chunk", path));
+    }
+
+    // Mix down to mono
+    let frames: Vec<f32> = pcm
+        .chunks_exact(2 * channels)
+        .map(|frame| {
+            let sum: f32 = frame
+                .chunks_exact(2)
+                .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / i16::MAX as f32)
+                .sum();
+            sum / channels as f32
+        })
+        .collect();
+
+    // Nearest neighbour resampling is crude but fine for background music
+    let length = (frames.len() as u64 * SAMPLE_RATE as u64 / rate as u64) as usize;
+    Ok((0..length)
+        .map(|i| frames[(i as u64 * rate as u64 / SAMPLE_RATE as u64) as usize])
+        .collect())
+}
+
+// Square wave sliding from start_freq to end_freq with a linear fade out
+fn tone(start_freq: f32, end_freq: f32, duration: f32, volume: f32) -> Vec<f32> {
+    let count = (duration * SAMPLE_RATE as f32) as usize;
+    let mut phase = 0.0f32;
+    (0..count)
+        .map(|i| {
+            let t = i as f32 / count as f32;
+            let freq = start_freq + (end_freq - start_freq) * t;
+            phase = (phase + freq / SAMPLE_RATE as f32) % 1.0;
+            let square = if phase < 0.5 { 1.0 } else { -1.0 };
+            square * volume * (1.0 - t)
+        })
+        .collect()
+}
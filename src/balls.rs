@@ -0,0 +1,57 @@
+// the kinds of ball, each moving and hitting things its own way. catching one of the ball pickups turns
+// every ball in play into that kind, and the ball served after a lost point is a normal one again
+
+use crate::collision::{Filter, Layers};
+use crate::palette;
+
+const HEAVY_SPEED: f32 = 0.7; // Of a normal ball's
+const GHOST_FADE: f32 = 0.4; // How much of the ball's colour shows over the background
+pub const HEAVY_COLOUR: u32 = 0xFF8C96A8;
+pub const GHOST_COLOUR: u32 = 0xFFC8C8F0;
+pub const SPLIT_COLOUR: u32 = 0xFFE06838;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum BallKind {
+    #[default]
+    Normal,
+    Heavy, // Slower, but knocks out any brick in one hit
+    Ghost, // Passes straight through the level's obstacles
+    Split, // Divides in two off a paddle, into normal balls
+}
+
+impl BallKind {
+    // How fast it moves compared with a normal ball
+    pub fn speed(self) -> f32 {
+        match self {
+            BallKind::Heavy => HEAVY_SPEED,
+            BallKind::Normal | BallKind::Ghost | BallKind::Split => 1.0,
+        }
+    }
+
+    pub fn breaks_bricks(self) -> bool {
+        self == BallKind::Heavy
+    }
+
+    // What it collides with, a ghost ball goes through obstacles
+    pub fn filter(self) -> Filter {
+        let filter = Filter::on(Layers::BALL);
+        match self {
+            BallKind::Ghost => filter.ignoring(Layers::OBSTACLE),
+            BallKind::Normal | BallKind::Heavy | BallKind::Split => filter,
+        }
+    }
+
+    pub fn splits(self) -> bool {
+        self == BallKind::Split
+    }
+
+    // The colour it's drawn in, given the theme's ball and background colours
+    pub fn colour(self, ball: u32, background: u32) -> u32 {
+        match self {
+            BallKind::Normal => ball,
+            BallKind::Heavy => HEAVY_COLOUR,
+            BallKind::Ghost => palette::mix(background, ball, GHOST_FADE),
+            BallKind::Split => SPLIT_COLOUR,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+// splitting a frame into horizontal bands of whole rows for heavy passes over every pixel, like the CRT filter.
+// with the parallel feature each band goes to a thread of its own, one per core up to MAX_BANDS, otherwise
+// the whole frame is a single band done on the calling thread. the bands don't overlap, so a pass that only
+// writes the rows it's given and reads from elsewhere gives the same picture either way
+
+#[cfg(feature = "parallel")]
+const MAX_BANDS: usize = 8;
+#[cfg(feature = "parallel")]
+const MIN_BAND_ROWS: usize = 32; // Fewer rows than this aren't worth a thread
+
+// Call pass with the index of each band's first row and its pixels
+#[cfg(feature = "parallel")]
+pub fn for_each_band(frame: &mut [u32], width: usize, pass: impl Fn(usize, &mut [u32]) + Sync) {
+    let rows = frame.len().checked_div(width).unwrap_or(0);
+    let bands = band_count(rows);
+    if bands < 2 {
+        pass(0, frame);
+    } else {
+        let band_rows = rows.div_ceil(bands);
+        std::thread::scope(|scope| {
+            for (i, band) in frame.chunks_mut(band_rows * width).enumerate() {
+                let pass = &pass;
+                scope.spawn(move || pass(i * band_rows, band));
+            }
+        });
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn for_each_band(frame: &mut [u32], _width: usize, pass: impl Fn(usize, &mut [u32]) + Sync) {
+    pass(0, frame);
+}
+
+#[cfg(feature = "parallel")]
+fn band_count(rows: usize) -> usize {
+    let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+    cores.min(MAX_BANDS).min(rows / MIN_BAND_ROWS).max(1)
+}
@@ -0,0 +1,69 @@
+// the boss in breakout mode: every BOSS_EVERY levels the bricks give way to one big block sweeping
+// across the top, which takes a number of hits to beat and speeds up the more it has taken
+
+use crate::bricks;
+use crate::GameObject;
+
+pub const BOSS_EVERY: u32 = 5; // Levels 5, 10, 15... are boss levels
+const WIDTH: f32 = 200.0;
+const HEIGHT: f32 = 30.0;
+const BASE_HEALTH: u32 = 10;
+const HEALTH_PER_BOSS: u32 = 5; // Each boss takes this many more hits than the last
+const BASE_SPEED: f32 = 120.0; // pixels/second at full health
+const SPEED_UP: f32 = 2.0; // ...up to (1 + SPEED_UP) times that on its last hit
+
+pub struct Boss {
+    pub body: GameObject, // vel_x is kept up to date so the ball comes off it like a moving obstacle
+    pub health: u32,
+    pub max_health: u32,
+}
+
+pub fn is_boss_level(level: u32) -> bool {
+    level.is_multiple_of(BOSS_EVERY)
+}
+
+impl Boss {
+    // The boss for a boss level, starting in the middle at the top of the field
+    pub fn new(level: u32, field_width: f32) -> Self {
+        let health = BASE_HEALTH + (level / BOSS_EVERY).saturating_sub(1) * HEALTH_PER_BOSS;
+        let width = WIDTH.min(field_width);
+        Boss {
+            body: GameObject {
+                x: (field_width - width) / 2.0,
+                y: bricks::TOP_MARGIN,
+                width,
+                height: HEIGHT,
+                vel_x: BASE_SPEED,
+                vel_y: 0.0,
+            },
+            health,
+            max_health: health,
+        }
+    }
+
+    // Sweep to and fro between the walls, faster as it gets weaker
+    pub fn update(&mut self, delta_time: f32, field_width: f32) {
+        let hurt = 1.0 - self.health as f32 / self.max_health as f32;
+        let speed = BASE_SPEED * (1.0 + hurt * SPEED_UP);
+        self.body.vel_x = speed.copysign(self.body.vel_x);
+        self.body.x += self.body.vel_x * delta_time;
+        if self.body.x <= 0.0 {
+            self.body.x = 0.0;
+            self.body.vel_x = speed;
+        } else if self.body.x + self.body.width >= field_width {
+            self.body.x = field_width - self.body.width;
+            self.body.vel_x = -speed;
+        }
+    }
+
+    // Take a hit, true once that has beaten it
+    pub fn hit(&mut self) -> bool {
+        self.health = self.health.saturating_sub(1);
+        self.health == 0
+    }
+
+    // Fraction of its health left, for the health bar
+    pub fn health_left(&self) -> f32 {
+        self.health as f32 / self.max_health as f32
+    }
+}
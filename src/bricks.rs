@@ -0,0 +1,155 @@
+// bricks for breakout mode, laid out in rows across the top of the playfield
+
+use crate::collision::{overlaps, SpatialHash};
+use crate::GameObject;
+
+const COLUMNS: usize = 10;
+const MAX_ROWS: usize = 8;
+const BRICK_HEIGHT: f32 = 20.0;
+const GAP: f32 = 4.0;
+const SIDE_MARGIN: f32 = 10.0;
+pub const TOP_MARGIN: f32 = 60.0; // Leave room for the HUD and a gap above the bricks
+const GRID_CELL: f32 = 64.0; // Side of a cell in the grid the bricks are found through, about a brick across
+
+// The top rows are worth the most
+const ROW_POINTS: [i32; MAX_ROWS] = [7, 7, 5, 5, 3, 3, 1, 1];
+
+pub struct Brick {
+    pub body: GameObject,
+    pub hits_left: u32,
+    pub points: i32,
+    pub row: usize, // Counted from the top, picks the brick's colour from the theme
+}
+
+// Build the wall of bricks for a level, later levels have more rows and tougher bricks
+pub fn layout(level: u32, field_width: f32) -> Vec<Brick> {
+    let rows = (level as usize + 2).min(MAX_ROWS);
+    // From level 2 the top row takes two hits, from level 4 the top two rows do
+    let tough_rows = (level as usize / 2).min(2);
+    let grid: Vec<Vec<u32>> = (0..rows)
+        .map(|row| vec![if row < tough_rows { 2 } else { 1 }; COLUMNS])
+        .collect();
+    from_grid(&grid, field_width)
+}
+
+// Build bricks from a grid of hit counts, 0 leaves a gap.
+// The columns are stretched to fill the width of the playfield
+pub fn from_grid(grid: &[Vec<u32>], field_width: f32) -> Vec<Brick> {
+    let columns = grid.iter().map(Vec::len).max().unwrap_or(0).max(1);
+    let brick_width = (field_width - 2.0 * SIDE_MARGIN - (columns - 1) as f32 * GAP) / columns as f32;
+
+    let mut bricks = Vec::new();
+    for (row, cells) in grid.iter().enumerate() {
+        for (column, &hits_left) in cells.iter().enumerate() {
+            if hits_left == 0 {
+                continue;
+            }
+            bricks.push(Brick {
+                body: GameObject {
+                    x: SIDE_MARGIN + column as f32 * (brick_width + GAP),
+                    y: TOP_MARGIN + row as f32 * (BRICK_HEIGHT + GAP),
+                    width: brick_width,
+                    height: BRICK_HEIGHT,
+                    vel_x: 0.0,
+                    vel_y: 0.0,
+                },
+                hits_left,
+                points: ROW_POINTS[row % MAX_ROWS],
+                row,
+            });
+        }
+    }
+    bricks
+}
+
+// Where the bricks are, by their index in the list, so a ball only tests the bricks near it.
+// Removing a brick moves the ones after it down the list, so collide and shoot rebuild it when they do
+pub type Grid = SpatialHash<usize>;
+
+pub fn grid(bricks: &[Brick]) -> Grid {
+    let mut grid = Grid::new(GRID_CELL);
+    index(&mut grid, bricks);
+    grid
+}
+
+// Put the bricks back in the grid after the list has changed
+pub fn index(grid: &mut Grid, bricks: &[Brick]) {
+    grid.rebuild(bricks.iter().map(|brick| &brick.body).enumerate());
+}
+
+// The first brick in the list the body overlaps
+fn first_overlapping(body: &GameObject, bricks: &[Brick], grid: &Grid) -> Option<usize> {
+    grid.query(body).into_iter().find(|&index| bricks.get(index).is_some_and(|brick| overlaps(body, &brick.body)))
+}
+
+// Bounce the ball off the first brick it overlaps, returning the points scored.
+// Some(0) means a tough brick was hit but not destroyed, a smashing ball destroys any brick in one hit
+pub fn collide(ball: &mut GameObject, bricks: &mut Vec<Brick>, grid: &mut Grid, smash: bool) -> Option<i32> {
+    let index = first_overlapping(ball, bricks, grid)?;
+    bounce(ball, &bricks[index].body);
+
+    let brick = &mut bricks[index];
+    brick.hits_left = if smash { 0 } else { brick.hits_left - 1 };
+    if brick.hits_left > 0 {
+        return Some(0);
+    }
+    let points = brick.points;
+    bricks.remove(index);
+    self::index(grid, bricks);
+    Some(points)
+}
+
+// Knock out the first brick the shot touches whatever hits it has left, returning where it was and its points
+pub fn shoot(shot: &GameObject, bricks: &mut Vec<Brick>, grid: &mut Grid) -> Option<((f32, f32), i32)> {
+    let index = first_overlapping(shot, bricks, grid)?;
+    let brick = bricks.remove(index);
+    self::index(grid, bricks);
+    Some((brick.body.centre(), brick.points))
+}
+
+// Bounce the ball off a solid block such as a level obstacle, returns true if the ball was turned around.
+// The block may be moving: the ball comes off at the speed it hit at relative to the block, and is put back
+// outside it so a block running into the ball pushes it along instead of swallowing it
+pub fn bounce_off(ball: &mut GameObject, block: &GameObject) -> bool {
+    if !overlaps(ball, block) {
+        return false;
+    }
+    let before = (ball.vel_x, ball.vel_y);
+    let overlap_left = ball.x + ball.width - block.x;
+    let overlap_right = block.x + block.width - ball.x;
+    let overlap_top = ball.y + ball.height - block.y;
+    let overlap_bottom = block.y + block.height - ball.y;
+    let (relative_x, relative_y) = (ball.vel_x - block.vel_x, ball.vel_y - block.vel_y);
+
+    if overlap_left.min(overlap_right) < overlap_top.min(overlap_bottom) {
+        let from_left = overlap_left < overlap_right;
+        ball.x = if from_left { block.x - ball.width } else { block.x + block.width };
+        // Only turn round when heading into the block, it may have caught up with a ball moving away
+        if (relative_x > 0.0) == from_left && relative_x != 0.0 {
+            ball.vel_x = block.vel_x - relative_x;
+        }
+    } else {
+        let from_top = overlap_top < overlap_bottom;
+        ball.y = if from_top { block.y - ball.height } else { block.y + block.height };
+        if (relative_y > 0.0) == from_top && relative_y != 0.0 {
+            ball.vel_y = block.vel_y - relative_y;
+        }
+    }
+    before != (ball.vel_x, ball.vel_y)
+}
+
+// Bounce off whichever side the ball is poking into the least
+fn bounce(ball: &mut GameObject, block: &GameObject) {
+    let overlap_left = ball.x + ball.width - block.x;
+    let overlap_right = block.x + block.width - ball.x;
+    let overlap_top = ball.y + ball.height - block.y;
+    let overlap_bottom = block.y + block.height - ball.y;
+    let overlap_x = overlap_left.min(overlap_right);
+    let overlap_y = overlap_top.min(overlap_bottom);
+
+    if overlap_x < overlap_y {
+        ball.vel_x = if overlap_left < overlap_right { -ball.vel_x.abs() } else { ball.vel_x.abs() };
+    } else {
+        ball.vel_y = if overlap_top < overlap_bottom { -ball.vel_y.abs() } else { ball.vel_y.abs() };
+    }
+}
@@ -0,0 +1,174 @@
+// command line options, these override anything set in pingpong.toml
+
+use game_loop::config::Config;
+use game_loop::difficulty;
+use game_loop::net;
+use game_loop::GameMode;
+
+pub const USAGE: &str = "Usage: pingpong [OPTIONS]
+
+Options:
+  --width <PIXELS>     Window width
+  --height <PIXELS>    Window height
+  --paddle-width <PX>  Paddle width
+  --paddle-height <PX> Paddle height
+  --ball-size <PX>     Ball width and height
+  --lives <N>          Starting lives in 1 player mode
+  --difficulty <NAME>  easy, normal or hard
+  --fps <N>            Target frames per second
+  --fullscreen         Open a borderless window
+  --seed <N>           Seed for the random number generator
+  --two-player         Skip the title screen and start a 2 player game
+  --ai                 Skip the title screen and start a game against the CPU
+  --breakout           Skip the title screen and start a breakout game
+  --survival           Skip the title screen and start a survival game
+  --arena <FILE>       Play the modes without bricks among the obstacles in FILE
+  --headless           Run the game without a window, the CPU plays every paddle
+  --terminal           Play in the terminal instead of a window (builds with the terminal feature)
+  --frames <N>         Frames to simulate in headless mode (default 36000)
+  --host               Host a 2 player game over the network and wait for the other player
+  --port <N>           Port to host on (default 7878)
+  --connect <ADDRESS>  Join a network game at host or host:port
+  --udp                Use UDP for the network game, better over the internet (both players need it)
+  --replay <FILE>      Watch a recorded game, the last game is saved as last_replay.rpl
+  --config <FILE>      Read settings from FILE instead of pingpong.toml
+  -h, --help           Show this message";
+
+const DEFAULT_HEADLESS_FRAMES: u64 = 36000; // 10 minutes at 60 frames a second
+
+pub struct Args {
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub paddle_width: Option<f32>,
+    pub paddle_height: Option<f32>,
+    pub ball_size: Option<f32>,
+    pub lives: Option<i32>,
+    pub difficulty: Option<usize>,
+    pub fps: Option<u64>,
+    pub fullscreen: bool,
+    pub seed: Option<u64>,
+    pub mode: Option<GameMode>,
+    pub arena: Option<String>,
+    pub config_path: Option<String>,
+    pub replay_path: Option<String>,
+    pub host_port: Option<u16>,
+    pub connect_address: Option<String>,
+    pub udp: bool,
+    pub headless: bool,
+    pub terminal: bool,
+    pub frames: u64,
+    pub help: bool,
+}
+
+impl Args {
+    // Parse the arguments after the program name
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut parsed = Args {
+            width: None,
+            height: None,
+            paddle_width: None,
+            paddle_height: None,
+            ball_size: None,
+            lives: None,
+            difficulty: None,
+            fps: None,
+            fullscreen: false,
+            seed: None,
+            mode: None,
+            arena: None,
+            config_path: None,
+            replay_path: None,
+            host_port: None,
+            connect_address: None,
+            udp: false,
+            headless: false,
+            terminal: false,
+            frames: DEFAULT_HEADLESS_FRAMES,
+            help: false,
+        };
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--width" => parsed.width = Some(positive(&arg, args.next())?),
+                "--height" => parsed.height = Some(positive(&arg, args.next())?),
+                "--paddle-width" => parsed.paddle_width = Some(positive(&arg, args.next())?),
+                "--paddle-height" => parsed.paddle_height = Some(positive(&arg, args.next())?),
+                "--ball-size" => parsed.ball_size = Some(positive(&arg, args.next())?),
+                "--lives" => parsed.lives = Some(positive(&arg, args.next())?),
+                "--difficulty" => {
+                    let name = args.next().ok_or("--difficulty needs a name")?;
+                    let preset = difficulty::find(&name).ok_or_else(|| format!("unknown difficulty {}", name))?;
+                    parsed.difficulty = Some(preset);
+                }
+                "--fps" => parsed.fps = Some(positive(&arg, args.next())?),
+                "--seed" => parsed.seed = Some(number(&arg, args.next())?),
+                "--fullscreen" => parsed.fullscreen = true,
+                "--two-player" => parsed.mode = Some(GameMode::TwoPlayer),
+                "--ai" => parsed.mode = Some(GameMode::VsAi),
+                "--breakout" => parsed.mode = Some(GameMode::Breakout),
+                "--survival" => parsed.mode = Some(GameMode::Survival),
+                "--arena" => parsed.arena = Some(args.next().ok_or("--arena needs a file name")?),
+                "--config" => parsed.config_path = Some(args.next().ok_or("--config needs a file name")?),
+                "--replay" => parsed.replay_path = Some(args.next().ok_or("--replay needs a file name")?),
+                "--host" => parsed.host_port = Some(parsed.host_port.unwrap_or(net::DEFAULT_PORT)),
+                "--port" => parsed.host_port = Some(positive(&arg, args.next())?),
+                "--connect" => parsed.connect_address = Some(args.next().ok_or("--connect needs an address")?),
+                "--udp" => parsed.udp = true,
+                "--headless" => parsed.headless = true,
+                "--terminal" => parsed.terminal = true,
+                "--frames" => parsed.frames = positive(&arg, args.next())?,
+                "-h" | "--help" => parsed.help = true,
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        Ok(parsed)
+    }
+
+    // Command line options win over the config file
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(width) = self.width {
+            config.window_width = width;
+        }
+        if let Some(height) = self.height {
+            config.window_height = height;
+        }
+        if let Some(width) = self.paddle_width {
+            config.paddle_width = width;
+        }
+        if let Some(height) = self.paddle_height {
+            config.paddle_height = height;
+        }
+        if let Some(size) = self.ball_size {
+            config.ball_size = size;
+        }
+        if let Some(lives) = self.lives {
+            config.lives = lives;
+        }
+        if let Some(difficulty) = self.difficulty {
+            config.difficulty = difficulty;
+        }
+        if let Some(fps) = self.fps {
+            config.frame_target_time = (1000 / fps).max(1);
+        }
+        if self.fullscreen {
+            config.fullscreen = true;
+        }
+        if let Some(arena) = &self.arena {
+            config.arena = Some(arena.clone());
+        }
+    }
+}
+
+fn number<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", flag))?;
+    value.parse().map_err(|_| format!("invalid value {} for {}", value, flag))
+}
+
+fn positive<T: std::str::FromStr + PartialOrd + Default>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let n: T = number(flag, value)?;
+    if n <= T::default() {
+        return Err(format!("{} must be greater than zero", flag));
+    }
+    Ok(n)
+}
@@ -0,0 +1,119 @@
+// the time, for frame timing and anything stamped with the date.
+// std's clock isn't there in the browser (using it panics), so the web build asks JavaScript instead,
+// through performance_now() and date_now() which web/index.html passes in when it loads the game.
+// the game reads the time through a Clock, which tests swap for a ManualClock to move time on themselves
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+pub use web::Instant;
+
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+// The real time
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+// Time that stands still until advance() moves it on, see Game::set_clock.
+// Clones share the same time, so a test can keep one to move on while the game reads the other
+#[derive(Clone)]
+pub struct ManualClock {
+    start: Instant,
+    elapsed: Rc<Cell<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        ManualClock {
+            start: Instant::now(),
+            elapsed: Rc::new(Cell::new(Duration::ZERO)),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed.set(self.elapsed.get() + duration);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed.get()
+    }
+}
+
+// Time since the start of 1970 (UTC)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unix_time() -> Duration {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn unix_time() -> Duration {
+    // SAFETY: a plain JavaScript function returning a number
+    Duration::from_secs_f64(unsafe { web::date_now() } / 1000.0)
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use std::ops::{Add, Sub};
+    use std::time::Duration;
+
+    extern "C" {
+        fn performance_now() -> f64; // Milliseconds since the page loaded
+        pub fn date_now() -> f64; // Milliseconds since 1970
+    }
+
+    // The same as std's Instant as far as the game uses it
+    #[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+    pub struct Instant(f64);
+
+    impl Instant {
+        pub fn now() -> Self {
+            // SAFETY: a plain JavaScript function returning a number
+            Instant(unsafe { performance_now() })
+        }
+
+        pub fn duration_since(&self, earlier: Instant) -> Duration {
+            Duration::from_secs_f64((self.0 - earlier.0).max(0.0) / 1000.0)
+        }
+
+        pub fn elapsed(&self) -> Duration {
+            Instant::now().duration_since(*self)
+        }
+    }
+
+    impl Add<Duration> for Instant {
+        type Output = Instant;
+
+        fn add(self, duration: Duration) -> Instant {
+            Instant(self.0 + duration.as_secs_f64() * 1000.0)
+        }
+    }
+
+    impl Sub for Instant {
+        type Output = Duration;
+
+        fn sub(self, earlier: Instant) -> Duration {
+            self.duration_since(earlier)
+        }
+    }
+}
@@ -0,0 +1,163 @@
+// which things collide with which. everything that can collide is on a layer, and has a mask of the layers
+// it collides with. LAYERS below is the one place the pairs are decided: a ball hits paddles, bricks,
+// obstacles and the shield, the paddle catches pickups, laser bolts knock out bricks and obstacles.
+// the overlap tests every collision goes through are here as well, and a grid that narrows down which
+// things are worth testing when there are a lot of them
+
+use crate::GameObject;
+use std::collections::HashMap;
+use std::ops::BitOr;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Layers(u32);
+
+impl Layers {
+    pub const NONE: Layers = Layers(0);
+    pub const BALL: Layers = Layers(1);
+    pub const PADDLE: Layers = Layers(1 << 1);
+    pub const BRICK: Layers = Layers(1 << 2); // Bricks and the boss
+    pub const OBSTACLE: Layers = Layers(1 << 3);
+    pub const PICKUP: Layers = Layers(1 << 4); // Power-ups and coins
+    pub const PROJECTILE: Layers = Layers(1 << 5);
+    pub const SHIELD: Layers = Layers(1 << 6);
+
+    // Any layer in common
+    pub fn intersects(self, other: Layers) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn without(self, other: Layers) -> Layers {
+        Layers(self.0 & !other.0)
+    }
+}
+
+impl BitOr for Layers {
+    type Output = Layers;
+
+    fn bitor(self, other: Layers) -> Layers {
+        Layers(self.0 | other.0)
+    }
+}
+
+// The layer something is on and the layers it collides with
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Filter {
+    pub layer: Layers,
+    pub mask: Layers,
+}
+
+impl Filter {
+    // What's on the layer usually collides with, see LAYERS
+    pub fn on(layer: Layers) -> Filter {
+        let mask = LAYERS
+            .iter()
+            .find(|filter| filter.layer == layer)
+            .map_or(Layers::NONE, |filter| filter.mask);
+        Filter { layer, mask }
+    }
+
+    // The same, colliding with nothing on the given layers
+    pub fn ignoring(self, layers: Layers) -> Filter {
+        Filter {
+            mask: self.mask.without(layers),
+            ..self
+        }
+    }
+
+    // They collide when each is on a layer the other's mask has, so either side can opt out
+    pub fn collides_with(self, other: Filter) -> bool {
+        self.mask.intersects(other.layer) && other.mask.intersects(self.layer)
+    }
+}
+
+const LAYERS: [Filter; 7] = [
+    Filter {
+        layer: Layers::BALL,
+        mask: Layers(Layers::PADDLE.0 | Layers::BRICK.0 | Layers::OBSTACLE.0 | Layers::SHIELD.0),
+    },
+    Filter {
+        layer: Layers::PADDLE,
+        mask: Layers(Layers::BALL.0 | Layers::PICKUP.0),
+    },
+    Filter {
+        layer: Layers::BRICK,
+        mask: Layers(Layers::BALL.0 | Layers::PROJECTILE.0),
+    },
+    Filter {
+        layer: Layers::OBSTACLE,
+        mask: Layers(Layers::BALL.0 | Layers::PROJECTILE.0),
+    },
+    Filter {
+        layer: Layers::PICKUP,
+        mask: Layers::PADDLE,
+    },
+    Filter {
+        layer: Layers::PROJECTILE,
+        mask: Layers(Layers::BRICK.0 | Layers::OBSTACLE.0),
+    },
+    Filter {
+        layer: Layers::SHIELD,
+        mask: Layers::BALL,
+    },
+];
+
+// The rectangles overlap, just touching edges doesn't count
+pub fn overlaps(a: &GameObject, b: &GameObject) -> bool {
+    a.x < b.x + b.width && a.x + a.width > b.x && a.y < b.y + b.height && a.y + a.height > b.y
+}
+
+// The rectangles overlap or touch along an edge, for catching things the moment they arrive
+pub fn touches(a: &GameObject, b: &GameObject) -> bool {
+    a.x <= b.x + b.width && a.x + a.width >= b.x && a.y <= b.y + b.height && a.y + a.height >= b.y
+}
+
+// A uniform grid over the field for finding what's near a rectangle without testing everything.
+// Each id goes in every cell its rectangle covers, a query gathers the ids in the cells the rectangle it's
+// given covers. Those are only candidates, the caller still tests them for overlap
+pub struct SpatialHash<T> {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<T>>,
+}
+
+impl<T: Copy + Ord> SpatialHash<T> {
+    pub fn new(cell_size: f32) -> Self {
+        SpatialHash { cell_size, cells: HashMap::new() }
+    }
+
+    // Empties the grid, keeping the cells' storage for the next lot
+    pub fn clear(&mut self) {
+        self.cells.values_mut().for_each(Vec::clear);
+    }
+
+    pub fn insert(&mut self, id: T, body: &GameObject) {
+        for cell in self.covered(body) {
+            self.cells.entry(cell).or_default().push(id);
+        }
+    }
+
+    // Start again with these
+    pub fn rebuild<'a>(&mut self, bodies: impl IntoIterator<Item = (T, &'a GameObject)>) {
+        self.clear();
+        for (id, body) in bodies {
+            self.insert(id, body);
+        }
+    }
+
+    // The ids in the cells the body covers, each once and in order so a query gives the same answer
+    // however the grid was filled
+    pub fn query(&self, body: &GameObject) -> Vec<T> {
+        let mut found: Vec<T> =
+            self.covered(body).filter_map(|cell| self.cells.get(&cell)).flatten().copied().collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    // The cells a rectangle covers, edges included so touching rectangles share a cell
+    fn covered(&self, body: &GameObject) -> impl Iterator<Item = (i32, i32)> {
+        let cell = |at: f32| (at / self.cell_size).floor() as i32;
+        let (left, right) = (cell(body.x), cell(body.x + body.width));
+        let (top, bottom) = (cell(body.y), cell(body.y + body.height));
+        (top..=bottom).flat_map(move |row| (left..=right).map(move |column| (column, row)))
+    }
+}
@@ -0,0 +1,426 @@
+// game settings loaded from pingpong.toml
+// only a small subset of toml is understood: [sections], key = value pairs,
+// numbers, booleans, quoted strings and # comments, which is all the config needs
+
+use crate::clock::Instant;
+use crate::difficulty;
+use crate::keybindings::{self, KeyBindings};
+use crate::levels;
+use crate::modes;
+use crate::palette;
+use crate::viewport::{self, Bars, Scaling};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+pub const CONFIG_FILE: &str = "pingpong.toml";
+
+// Defaults used when the config file is missing or doesn't set a value
+const DEFAULT_WINDOW_WIDTH: usize = 800;
+const DEFAULT_WINDOW_HEIGHT: usize = 600;
+const DEFAULT_BALL_SPEED: f32 = 300.0;
+const DEFAULT_BALL_SIZE: f32 = 15.0;
+const DEFAULT_BALL_MAX_SPEED: f32 = 900.0;
+const DEFAULT_BALL_TRAIL_LENGTH: usize = 12;
+const DEFAULT_SPIN_TRANSFER: f32 = 0.3; // Fraction of the paddle's speed passed to the ball
+const DEFAULT_PADDLE_SPEED: f32 = 400.0;
+const DEFAULT_PADDLE_WIDTH: f32 = 100.0;
+const DEFAULT_PADDLE_HEIGHT: f32 = 20.0;
+const DEFAULT_LIVES: i32 = 3;
+const DEFAULT_FRAME_TARGET_TIME: u64 = 16; // ~60 FPS
+const DEFAULT_MUSIC_VOLUME: f32 = 0.5;
+const DEFAULT_SPEED_UP_PERCENT: f32 = 5.0;
+const DEFAULT_HITS_PER_SPEED_UP: u32 = 5;
+const MAX_PADDLE_HEIGHT_FRACTION: usize = 8; // Paddles and the ball can be at most this fraction of the window height
+const MIN_TIME_SCALE: f64 = 0.25;
+const MAX_BALL_SIZE_FRACTION: usize = 8; // ...and the ball of the window's shorter side
+const WATCH_INTERVAL: Duration = Duration::from_millis(500); // How often the config file is checked for changes
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+}
+
+impl Value {
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(i) => Some(*i as f64),
+            Value::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub window_width: usize,
+    pub window_height: usize,
+    pub render_width: Option<usize>, // Lower resolution to draw at, scaled up to the window. None for the playfield's
+    pub render_height: Option<usize>,
+    pub scaling: Scaling, // How frames are scaled to the window
+    pub bars: Bars,       // Around the frame when the window is a different shape
+    pub pixel_scale: Option<usize>, // Screen pixels to a playfield pixel on high-DPI screens, None to detect it
+    pub fullscreen: bool,
+    pub vsync: bool, // Show frames as the screen refreshes where the window can, the game runs at frame_target_time
+    pub screen_shake: bool,
+    pub starfield: bool, // Drifting stars behind the playfield, on levels without tiles
+    pub day_night: bool, // Colours drift through dusk, night and dawn over a session's play time
+    pub crt: bool, // Scanlines, curved glass and darker corners over the finished frame
+    pub theme: usize, // Index into palette::THEMES
+    pub ball_speed: f32,
+    pub ball_size: f32,
+    pub ball_max_speed: f32,
+    pub ball_trail_length: usize, // Physics steps of trail drawn behind the ball, 0 for none
+    pub spin_transfer: f32,
+    pub paddle_speed: f32,
+    pub paddle_width: f32,
+    pub paddle_height: f32,
+    pub lives: i32,
+    pub difficulty: usize, // Index into difficulty::PRESETS
+    pub speed_up_percent: f32, // Ball speed increase every hits_per_speed_up paddle hits
+    pub hits_per_speed_up: u32,
+    pub frame_target_time: u64, // milliseconds
+    pub music_volume: f32,
+    pub music_file: Option<String>,
+    pub position_cues: bool, // Bounces sound from where the ball is, for following it by ear
+    pub ball_sprite: Option<String>, // PNG files drawn instead of plain rectangles
+    pub paddle_sprite: Option<String>,
+    pub mouse_control: bool,
+    pub levels_dir: String,
+    pub plugins_dir: String, // Libraries with more game modes, with the plugins feature
+    pub arena: Option<String>, // Level file with obstacles for the modes without bricks
+    pub language: Option<String>, // None follows the system's language
+    pub time_scale: f32, // The game runs at this fraction of its usual speed, for players who need more time
+    pub leaderboard_url: Option<String>, // Server for the online leaderboard, None to keep scores to this machine
+    pub leaderboard_key: Option<String>, // Shared with the server to sign the scores sent to it
+    pub discord_client_id: Option<String>, // Discord application to show the game as, with the discord feature
+    pub script: Option<String>, // Lua file with hooks into the game, with the lua feature
+    pub keys: KeyBindings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            window_width: DEFAULT_WINDOW_WIDTH,
+            window_height: DEFAULT_WINDOW_HEIGHT,
+            render_width: None,
+            render_height: None,
+            scaling: Scaling::Nearest,
+            bars: Bars::Black,
+            pixel_scale: None,
+            fullscreen: false,
+            vsync: false,
+            screen_shake: true,
+            starfield: true,
+            day_night: true,
+            crt: false,
+            theme: 0,
+            ball_speed: DEFAULT_BALL_SPEED,
+            ball_size: DEFAULT_BALL_SIZE,
+            ball_max_speed: DEFAULT_BALL_MAX_SPEED,
+            ball_trail_length: DEFAULT_BALL_TRAIL_LENGTH,
+            spin_transfer: DEFAULT_SPIN_TRANSFER,
+            paddle_speed: DEFAULT_PADDLE_SPEED,
+            paddle_width: DEFAULT_PADDLE_WIDTH,
+            paddle_height: DEFAULT_PADDLE_HEIGHT,
+            lives: DEFAULT_LIVES,
+            difficulty: difficulty::NORMAL,
+            speed_up_percent: DEFAULT_SPEED_UP_PERCENT,
+            hits_per_speed_up: DEFAULT_HITS_PER_SPEED_UP,
+            frame_target_time: DEFAULT_FRAME_TARGET_TIME,
+            music_volume: DEFAULT_MUSIC_VOLUME,
+            music_file: None,
+            position_cues: false,
+            ball_sprite: None,
+            paddle_sprite: None,
+            mouse_control: false,
+            levels_dir: levels::LEVELS_DIR.to_string(),
+            plugins_dir: modes::PLUGINS_DIR.to_string(),
+            arena: None,
+            language: None,
+            time_scale: 1.0,
+            leaderboard_url: None,
+            leaderboard_key: None,
+            discord_client_id: None,
+            script: None,
+            keys: KeyBindings::default(),
+        }
+    }
+}
+
+impl Config {
+    // Load the config file, falling back to defaults for anything missing or invalid
+    pub fn load(path: &str) -> Self {
+        let mut config = Config::default();
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(_) => return config, // No config file is fine, just use the defaults
+        };
+
+        match parse(&text) {
+            Ok(values) => {
+                for (key, value) in &values {
+                    if let Err(e) = config.apply(key, value) {
+                        eprintln!("{}: {}", path, e);
+                    }
+                }
+            }
+            Err(e) => eprintln!("{}: {}", path, e),
+        }
+        config
+    }
+
+    // The size frames are drawn at, the playfield's unless a render resolution is set. With only one side of it
+    // set the other keeps the playfield's shape
+    pub fn render_size(&self) -> (usize, usize) {
+        let (width, height) = (self.window_width, self.window_height);
+        match (self.render_width, self.render_height) {
+            (Some(render_width), Some(render_height)) => (render_width, render_height),
+            (Some(render_width), None) => (render_width, (render_width * height / width).max(1)),
+            (None, Some(render_height)) => ((render_height * width / height).max(1), render_height),
+            (None, None) => (width, height),
+        }
+    }
+
+    // Shrink paddles and the ball that won't fit the window, returning a warning for each one. They're never
+    // shrunk below a pixel, however small the window. Run once everything that can change the sizes has been
+    // applied, the window size included
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let window = format!("{}x{}", self.window_width, self.window_height);
+        let mut limit = |value: &mut f32, max: usize, name: &str| {
+            if *value > max as f32 {
+                warnings.push(format!("{} {} doesn't fit a {} window, using {}", name, value, window, max));
+                *value = max as f32;
+            }
+        };
+        limit(&mut self.paddle_width, self.window_width, "paddle width");
+        limit(&mut self.paddle_height, (self.window_height / MAX_PADDLE_HEIGHT_FRACTION).max(1), "paddle height");
+        let shorter_side = self.window_width.min(self.window_height);
+        limit(&mut self.ball_size, (shorter_side / MAX_BALL_SIZE_FRACTION).max(1), "ball size");
+        warnings
+    }
+
+    // Set a single setting, keys are "section.name" or just "name" at the top level
+    fn apply(&mut self, key: &str, value: &Value) -> Result<(), String> {
+        let bad_value = || format!("invalid value {:?} for {}", value, key);
+        match key {
+            "window.width" => self.window_width = positive_int(value).ok_or_else(bad_value)?,
+            "window.height" => self.window_height = positive_int(value).ok_or_else(bad_value)?,
+            "window.render_width" => self.render_width = Some(positive_int(value).ok_or_else(bad_value)?),
+            "window.render_height" => self.render_height = Some(positive_int(value).ok_or_else(bad_value)?),
+            "window.scaling" => {
+                let name = value.as_str().ok_or_else(bad_value)?;
+                self.scaling = viewport::find_scaling(name).ok_or_else(|| format!("unknown scaling {:?}", name))?;
+            }
+            "window.scale" => {
+                self.pixel_scale = match value.as_str() {
+                    Some(name) if name.eq_ignore_ascii_case("auto") => None,
+                    _ => {
+                        let scale = positive_int(value).filter(|&scale| scale <= viewport::MAX_PIXEL_SCALE);
+                        Some(scale.ok_or_else(bad_value)?)
+                    }
+                }
+            }
+            "window.bars" => {
+                let name = value.as_str().ok_or_else(bad_value)?;
+                self.bars = viewport::find_bars(name).ok_or_else(|| format!("unknown bars {:?}", name))?;
+            }
+            "window.fullscreen" => self.fullscreen = value.as_bool().ok_or_else(bad_value)?,
+            "window.vsync" => self.vsync = value.as_bool().ok_or_else(bad_value)?,
+            "window.screen_shake" => self.screen_shake = value.as_bool().ok_or_else(bad_value)?,
+            "window.starfield" => self.starfield = value.as_bool().ok_or_else(bad_value)?,
+            "window.day_night" => self.day_night = value.as_bool().ok_or_else(bad_value)?,
+            "window.crt" => self.crt = value.as_bool().ok_or_else(bad_value)?,
+            "window.theme" => {
+                let name = value.as_str().ok_or_else(bad_value)?;
+                self.theme = palette::find(name).ok_or_else(|| format!("unknown theme {:?}", name))?;
+            }
+            "window.frame_target_time" => self.frame_target_time = positive_int(value).ok_or_else(bad_value)? as u64,
+            "ball.speed" => self.ball_speed = positive_float(value).ok_or_else(bad_value)?,
+            "ball.size" => self.ball_size = positive_float(value).ok_or_else(bad_value)?,
+            "ball.trail_length" => {
+                self.ball_trail_length = value.as_int().filter(|&i| i >= 0).ok_or_else(bad_value)? as usize
+            }
+            "ball.sprite" => self.ball_sprite = Some(value.as_str().ok_or_else(bad_value)?.to_string()),
+            "ball.max_speed" => self.ball_max_speed = positive_float(value).ok_or_else(bad_value)?,
+            "paddle.spin_transfer" => {
+                self.spin_transfer = value.as_f64().filter(|&v| v >= 0.0).ok_or_else(bad_value)? as f32
+            }
+            "paddle.sprite" => self.paddle_sprite = Some(value.as_str().ok_or_else(bad_value)?.to_string()),
+            "paddle.speed" => self.paddle_speed = positive_float(value).ok_or_else(bad_value)?,
+            "paddle.width" => self.paddle_width = positive_float(value).ok_or_else(bad_value)?,
+            "paddle.height" => self.paddle_height = positive_float(value).ok_or_else(bad_value)?,
+            "audio.music_volume" => {
+                self.music_volume = value.as_f64().filter(|v| (0.0..=1.0).contains(v)).ok_or_else(bad_value)? as f32
+            }
+            "audio.music_file" => self.music_file = Some(value.as_str().ok_or_else(bad_value)?.to_string()),
+            "audio.position_cues" => self.position_cues = value.as_bool().ok_or_else(bad_value)?,
+            "input.mouse" => self.mouse_control = value.as_bool().ok_or_else(bad_value)?,
+            "game.lives" => self.lives = positive_int(value).ok_or_else(bad_value)? as i32,
+            "game.difficulty" => {
+                let name = value.as_str().ok_or_else(bad_value)?;
+                self.difficulty = difficulty::find(name).ok_or_else(|| format!("unknown difficulty {:?}", name))?;
+            }
+            "game.levels_dir" => self.levels_dir = value.as_str().ok_or_else(bad_value)?.to_string(),
+            "game.plugins_dir" => self.plugins_dir = value.as_str().ok_or_else(bad_value)?.to_string(),
+            "game.arena" => self.arena = Some(value.as_str().ok_or_else(bad_value)?.to_string()),
+            "game.time_scale" => {
+                self.time_scale = value.as_f64().filter(|v| (MIN_TIME_SCALE..=1.0).contains(v)).ok_or_else(bad_value)? as f32
+            }
+            "game.language" => self.language = Some(value.as_str().ok_or_else(bad_value)?.to_lowercase()),
+            "leaderboard.url" => self.leaderboard_url = Some(value.as_str().ok_or_else(bad_value)?.to_string()),
+            "leaderboard.key" => self.leaderboard_key = Some(value.as_str().ok_or_else(bad_value)?.to_string()),
+            "discord.client_id" => self.discord_client_id = Some(value.as_str().ok_or_else(bad_value)?.to_string()),
+            "scripting.file" => self.script = Some(value.as_str().ok_or_else(bad_value)?.to_string()),
+            "difficulty.speed_up_percent" => {
+                self.speed_up_percent = value.as_f64().filter(|&v| v >= 0.0).ok_or_else(bad_value)? as f32
+            }
+            "difficulty.hits_per_speed_up" => self.hits_per_speed_up = positive_int(value).ok_or_else(bad_value)? as u32,
+            _ => {
+                if let Some(action) = key.strip_prefix("keys.") {
+                    let name = value.as_str().ok_or_else(bad_value)?;
+                    let key_code = keybindings::parse_key(name).ok_or_else(|| format!("unknown key {:?} for {}", name, key))?;
+                    if self.keys.set(action, key_code) {
+                        return Ok(());
+                    }
+                }
+                return Err(format!("unknown setting {}", key));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Notices the config file being saved, so settings can be tried out without restarting the game
+pub struct ConfigWatcher {
+    path: String,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: &str) -> Self {
+        ConfigWatcher {
+            path: path.to_string(),
+            modified: modified_time(path),
+            last_check: Instant::now(),
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    // The config as it is now if the file has been saved since the last look, call once a frame
+    pub fn poll(&mut self) -> Option<Config> {
+        if self.last_check.elapsed() < WATCH_INTERVAL {
+            return None;
+        }
+        self.last_check = Instant::now();
+        // A file that has gone is left alone rather than put back to the defaults
+        let modified = modified_time(&self.path).filter(|&time| Some(time) != self.modified)?;
+        self.modified = Some(modified);
+        Some(Config::load(&self.path))
+    }
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn positive_int(value: &Value) -> Option<usize> {
+    value.as_int().filter(|&i| i > 0).map(|i| i as usize)
+}
+
+fn positive_float(value: &Value) -> Option<f32> {
+    value.as_f64().filter(|&f| f > 0.0).map(|f| f as f32)
+}
+
+// Parse the toml subset into a map of "section.key" to value
+pub fn parse(text: &str) -> Result<HashMap<String, Value>, String> {
+    let mut values = HashMap::new();
+    let mut section = String::new();
+
+    for (line_no, raw_line) in text.lines().enumerate() {
+        let line = strip_comment(raw_line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name
+                .strip_suffix(']')
+                .ok_or_else(|| format!("line {}: unclosed section header", line_no + 1))?;
+            section = name.trim().to_string();
+            continue;
+        }
+
+        let (key, raw_value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected key = value", line_no + 1))?;
+        let key = key.trim();
+        let value = parse_value(raw_value.trim()).ok_or_else(|| format!("line {}: invalid value for {}", line_no + 1, key))?;
+
+        let full_key = if section.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", section, key)
+        };
+        values.insert(full_key, value);
+    }
+    Ok(values)
+}
+
+// Drop a trailing # comment, ignoring any # inside a quoted string
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_value(raw: &str) -> Option<Value> {
+    if let Some(inner) = raw.strip_prefix('"') {
+        return inner.strip_suffix('"').map(|s| Value::Str(s.to_string()));
+    }
+    match raw {
+        "true" => return Some(Value::Bool(true)),
+        "false" => return Some(Value::Bool(false)),
+        _ => {}
+    }
+    // toml allows underscores as digit separators
+    let number = raw.replace('_', "");
+    if let Ok(i) = number.parse::<i64>() {
+        return Some(Value::Int(i));
+    }
+    number.parse::<f64>().ok().map(Value::Float)
+}
@@ -0,0 +1,71 @@
+// CRT-style post-processing of the finished frame, for a retro arcade look: darker scanlines,
+// a slight barrel distortion like the curved glass of an old monitor and a vignette darkening the corners.
+// everything that depends only on the position of a pixel is worked out once for the frame size
+
+use crate::bands;
+
+const CURVATURE: f32 = 0.06; // How much further out the corners are read from than the middle
+const SCANLINE_SHADE: u32 = 190; // Brightness of every other row, out of 256
+const VIGNETTE: f32 = 0.3; // How much darker the very corners are
+
+pub struct CrtFilter {
+    source: Vec<Option<u32>>, // The pixel each one is read from once bent, None beyond the edge of the picture
+    shade: Vec<u32>,          // Brightness of each pixel out of 256, scanlines and vignette together
+    copy: Vec<u32>,           // The frame before filtering, pixels are read from here as the frame is written
+    width: usize,
+}
+
+impl CrtFilter {
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut source = Vec::with_capacity(width * height);
+        let mut shade = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                // -1 to 1 across the frame, pixel centres
+                let nx = (x as f32 + 0.5) / width as f32 * 2.0 - 1.0;
+                let ny = (y as f32 + 0.5) / height as f32 * 2.0 - 1.0;
+                let r2 = nx * nx + ny * ny;
+                let bend = 1.0 + CURVATURE * r2;
+                let (sx, sy) = (nx * bend, ny * bend);
+                source.push(if sx.abs() < 1.0 && sy.abs() < 1.0 {
+                    let column = ((sx + 1.0) / 2.0 * width as f32) as usize;
+                    let row = ((sy + 1.0) / 2.0 * height as f32) as usize;
+                    Some((row.min(height - 1) * width + column.min(width - 1)) as u32)
+                } else {
+                    None
+                });
+
+                let vignette = 1.0 - VIGNETTE * r2 / 2.0;
+                let scanline = if y % 2 == 1 { SCANLINE_SHADE } else { 256 };
+                shade.push((scanline as f32 * vignette) as u32);
+            }
+        }
+        CrtFilter { source, shade, copy: Vec::new(), width }
+    }
+
+    // Filter a frame the size the filter was made for, in place. Rows are written in bands, see bands.rs
+    pub fn apply(&mut self, frame: &mut [u32]) {
+        if frame.len() != self.source.len() {
+            return;
+        }
+        self.copy.clear();
+        self.copy.extend_from_slice(frame);
+        let (copy, sources, shades) = (&self.copy, &self.source, &self.shade);
+        bands::for_each_band(frame, self.width, |first_row, band| {
+            let start = first_row * self.width;
+            let (sources, shades) = (&sources[start..start + band.len()], &shades[start..start + band.len()]);
+            for ((pixel, source), &shade) in band.iter_mut().zip(sources).zip(shades) {
+                *pixel = match source {
+                    Some(index) => darken(copy[*index as usize], shade),
+                    None => 0,
+                };
+            }
+        });
+    }
+}
+
+// Scale each colour channel by shade / 256
+fn darken(pixel: u32, shade: u32) -> u32 {
+    let channel = |shift: u32| ((((pixel >> shift) & 0xFF) * shade) >> 8) << shift;
+    channel(16) | channel(8) | channel(0)
+}
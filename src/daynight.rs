@@ -0,0 +1,75 @@
+// day and night: the longer a session is played, the further its colours drift through dusk, night and dawn
+// and round to day again. each moment of the cycle is a colour look up table, which everything drawn goes
+// through on its way to the renderer (see renderer::Tinted), so the themes themselves are left alone
+
+use crate::assets::Texture;
+
+pub const DAY_SECONDS: f32 = 600.0; // Play time for the whole cycle
+pub const STEPS: u32 = 200; // The colours move on in this many steps a day, each one redraws the whole frame
+
+// What the light does to each of red, green and blue: out = lift + gain * in
+#[derive(Clone, Copy)]
+struct Light {
+    gain: [f32; 3],
+    lift: [f32; 3],
+}
+
+const DAY: Light = Light { gain: [1.0, 1.0, 1.0], lift: [0.0, 0.0, 0.0] };
+const DUSK: Light = Light { gain: [1.0, 0.82, 0.7], lift: [18.0, 6.0, 0.0] };
+const NIGHT: Light = Light { gain: [0.6, 0.68, 0.9], lift: [0.0, 4.0, 22.0] };
+const DAWN: Light = Light { gain: [0.95, 0.85, 0.88], lift: [14.0, 6.0, 10.0] };
+
+// The light at points through the day, from 0 to 1, blended in between
+const CYCLE: [(f32, Light); 7] = [
+    (0.0, DAY),
+    (0.4, DAY),
+    (0.5, DUSK),
+    (0.6, NIGHT),
+    (0.8, NIGHT),
+    (0.9, DAWN),
+    (1.0, DAY),
+];
+
+// The step of the cycle after seconds of play
+pub fn step(seconds: f32) -> u32 {
+    ((seconds / DAY_SECONDS).fract() * STEPS as f32) as u32 % STEPS
+}
+
+// A new value for each possible value of each channel
+pub struct ColourLut {
+    channels: [[u8; 256]; 3], // Red, green, blue
+}
+
+impl ColourLut {
+    // The table for a step of the cycle
+    pub fn for_step(step: u32) -> Self {
+        let time = step as f32 / STEPS as f32;
+        let next = CYCLE.iter().position(|&(start, _)| start > time).unwrap_or(CYCLE.len() - 1);
+        let ((start, from), (end, to)) = (CYCLE[next - 1], CYCLE[next]);
+        let t = ((time - start) / (end - start)).clamp(0.0, 1.0);
+
+        let mut channels = [[0u8; 256]; 3];
+        for (channel, table) in channels.iter_mut().enumerate() {
+            let gain = from.gain[channel] + (to.gain[channel] - from.gain[channel]) * t;
+            let lift = from.lift[channel] + (to.lift[channel] - from.lift[channel]) * t;
+            for (value, entry) in table.iter_mut().enumerate() {
+                *entry = (lift + gain * value as f32).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+        ColourLut { channels }
+    }
+
+    // An 0xAARRGGBB colour under this light, alpha is kept
+    pub fn map(&self, colour: u32) -> u32 {
+        let channel = |index: usize, shift: u32| (self.channels[index][((colour >> shift) & 0xFF) as usize] as u32) << shift;
+        (colour & 0xFF000000) | channel(0, 16) | channel(1, 8) | channel(2, 0)
+    }
+
+    pub fn map_texture(&self, texture: &Texture) -> Texture {
+        Texture {
+            width: texture.width,
+            height: texture.height,
+            pixels: texture.pixels.iter().map(|&pixel| self.map(pixel)).collect(),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+// frame timing for tracking down stutter: the time between the frames actually shown,
+// drawn as the current and average FPS over a graph of the last couple of seconds (F3)
+
+use crate::clock::Instant;
+use crate::font;
+use crate::renderer::Renderer;
+use std::collections::VecDeque;
+
+const HISTORY: usize = 120; // Frames kept for the average and the graph, a bar each
+const GRAPH_HEIGHT: f32 = 50.0;
+const GRAPH_MS: f32 = 50.0; // Frame time at the top of the graph, longer frames are cut off
+const MARGIN: usize = 4;
+const TOP: usize = 30; // Below the HUD
+const TEXT_COLOUR: u32 = 0xFF00FF00;
+const BAR_COLOUR: u32 = 0xFF00A000;
+const SLOW_BAR_COLOUR: u32 = 0xFFE04040; // Frames half as long again as the target or more
+const TARGET_COLOUR: u32 = 0xFF606060;
+const GRAPH_BACKGROUND: u32 = 0xFF101010;
+
+pub struct FrameStats {
+    times: VecDeque<f32>, // Seconds between frames, oldest first
+    last_frame: Option<Instant>,
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        FrameStats::new()
+    }
+}
+
+impl FrameStats {
+    pub fn new() -> Self {
+        FrameStats {
+            times: VecDeque::with_capacity(HISTORY),
+            last_frame: None,
+        }
+    }
+
+    // Call once a frame, as it's shown
+    pub fn record(&mut self) {
+        let now = Instant::now();
+        if let Some(last) = self.last_frame {
+            if self.times.len() == HISTORY {
+                self.times.pop_front();
+            }
+            self.times.push_back((now - last).as_secs_f32());
+        }
+        self.last_frame = Some(now);
+    }
+
+    // Frames a second going by the last frame alone
+    pub fn fps(&self) -> f32 {
+        self.times.back().map_or(0.0, |&time| 1.0 / time.max(f32::EPSILON))
+    }
+
+    pub fn average_fps(&self) -> f32 {
+        let total: f32 = self.times.iter().sum();
+        if total > 0.0 {
+            self.times.len() as f32 / total
+        } else {
+            0.0
+        }
+    }
+
+    // The readout in the top right corner, with a line across the graph at the target frame time
+    pub fn draw(&self, renderer: &mut dyn Renderer, target_ms: f32) {
+        let field_width = renderer.size().0;
+        let left = field_width.saturating_sub(MARGIN + HISTORY);
+        let worst = self.times.iter().copied().fold(0.0, f32::max) * 1000.0;
+        let text = format!("FPS {:.0} AVG {:.0} MAX {:.1}MS", self.fps(), self.average_fps(), worst);
+        let text_x = field_width.saturating_sub(MARGIN + font::text_width(&text, 1));
+        renderer.draw_text(text_x, TOP, &text, 1, TEXT_COLOUR);
+
+        let bottom = (TOP + font::GLYPH_HEIGHT + MARGIN) as f32 + GRAPH_HEIGHT;
+        renderer.fill_rect(left as f32, bottom - GRAPH_HEIGHT, HISTORY as f32, GRAPH_HEIGHT, GRAPH_BACKGROUND);
+        for (i, &time) in self.times.iter().enumerate() {
+            let ms = time * 1000.0;
+            let height = (ms / GRAPH_MS).min(1.0) * GRAPH_HEIGHT;
+            let colour = if ms > target_ms * 1.5 { SLOW_BAR_COLOUR } else { BAR_COLOUR };
+            renderer.fill_rect((left + i) as f32, bottom - height, 1.0, height, colour);
+        }
+        let target_y = bottom - (target_ms / GRAPH_MS).min(1.0) * GRAPH_HEIGHT;
+        renderer.fill_rect(left as f32, target_y, HISTORY as f32, 1.0, TARGET_COLOUR);
+    }
+}
@@ -0,0 +1,49 @@
+// difficulty presets, each scales the settings from the config: how fast the ball is served and speeds up,
+// how wide the paddle is and how many lives a game starts with. normal plays the config as it is.
+// picked with difficulty = "name" in the [game] section of pingpong.toml, --difficulty or from the title screen
+
+#[derive(Clone, Copy, Debug)]
+pub struct Preset {
+    pub name: &'static str,
+    pub ball_speed: f32,   // Multiplies the serve speed
+    pub speed_up: f32,     // Multiplies the speed increase every few paddle hits
+    pub paddle_width: f32, // Multiplies the paddle width
+    pub extra_lives: i32,  // Added to the starting lives, a game always starts with at least one
+}
+
+pub const PRESETS: [Preset; 3] = [
+    Preset {
+        name: "easy",
+        ball_speed: 0.8,
+        speed_up: 0.5,
+        paddle_width: 1.3,
+        extra_lives: 2,
+    },
+    Preset {
+        name: "normal",
+        ball_speed: 1.0,
+        speed_up: 1.0,
+        paddle_width: 1.0,
+        extra_lives: 0,
+    },
+    Preset {
+        name: "hard",
+        ball_speed: 1.25,
+        speed_up: 1.5,
+        paddle_width: 0.75,
+        extra_lives: -1,
+    },
+];
+
+pub const NORMAL: usize = 1;
+
+// Index of the preset with the given name, ignoring case
+pub fn find(name: &str) -> Option<usize> {
+    PRESETS.iter().position(|preset| preset.name.eq_ignore_ascii_case(name))
+}
+
+impl Preset {
+    pub fn lives(&self, lives: i32) -> i32 {
+        (lives + self.extra_lives).max(1)
+    }
+}
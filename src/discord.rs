@@ -0,0 +1,115 @@
+// discord rich presence, built with the discord feature: the mode being played, the score and the time
+// in the game shown on the player's discord profile. it talks to the discord app over its local IPC socket,
+// each message a little endian opcode and length followed by json. without discord running the game
+// just carries on without it
+
+use std::io::{self, Read, Write};
+
+const HANDSHAKE: u32 = 0;
+const FRAME: u32 = 1;
+const MAX_SOCKETS: usize = 10; // Discord takes the first free one of discord-ipc-0 to discord-ipc-9
+#[cfg(unix)]
+const REPLY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+#[cfg(unix)]
+type Socket = std::os::unix::net::UnixStream;
+#[cfg(windows)]
+type Socket = std::fs::File;
+
+// What to show, e.g. details "1 player" and state "Score: 40  Lives: 2  Level: 1"
+pub struct Activity {
+    pub details: String,
+    pub state: String, // Left off when empty, discord won't take an empty one
+    pub start: Option<u64>, // When the game started, seconds since the unix epoch, for discord's elapsed time
+}
+
+pub struct Presence {
+    socket: Socket,
+    nonce: u64, // Counts up so each command is told apart
+}
+
+impl Presence {
+    // None if discord isn't running or won't talk to us
+    pub fn connect(client_id: &str) -> Option<Self> {
+        let socket = (0..MAX_SOCKETS).find_map(open_socket)?;
+        let mut presence = Presence { socket, nonce: 0 };
+        let handshake = format!("{{\"v\":1,\"client_id\":{}}}", json_string(client_id));
+        match presence.send(HANDSHAKE, &handshake) {
+            Ok(()) => Some(presence),
+            Err(e) => {
+                eprintln!("Could not connect to Discord: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn set_activity(&mut self, activity: &Activity) -> io::Result<()> {
+        self.nonce += 1;
+        let mut fields = format!("\"details\":{}", json_string(&activity.details));
+        if !activity.state.is_empty() {
+            fields.push_str(&format!(",\"state\":{}", json_string(&activity.state)));
+        }
+        if let Some(start) = activity.start {
+            fields.push_str(&format!(",\"timestamps\":{{\"start\":{}}}", start));
+        }
+        let command = format!(
+            "{{\"cmd\":\"SET_ACTIVITY\",\"args\":{{\"pid\":{},\"activity\":{{{}}}}},\"nonce\":\"{}\"}}",
+            std::process::id(),
+            fields,
+            self.nonce
+        );
+        self.send(FRAME, &command)
+    }
+
+    // Write a message and read discord's reply, which only says whether it worked
+    fn send(&mut self, opcode: u32, json: &str) -> io::Result<()> {
+        let mut message = Vec::with_capacity(8 + json.len());
+        message.extend_from_slice(&opcode.to_le_bytes());
+        message.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        message.extend_from_slice(json.as_bytes());
+        self.socket.write_all(&message)?;
+
+        let mut header = [0u8; 8];
+        self.socket.read_exact(&mut header)?;
+        let length = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        let mut reply = vec![0u8; length as usize];
+        self.socket.read_exact(&mut reply)?;
+        if reply.windows(7).any(|word| word == b"\"ERROR\"") {
+            return Err(io::Error::other(String::from_utf8_lossy(&reply).into_owned()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn open_socket(index: usize) -> Option<Socket> {
+    let dir = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .iter()
+        .find_map(|name| std::env::var_os(name).filter(|dir| !dir.is_empty()))
+        .unwrap_or_else(|| "/tmp".into());
+    let socket = Socket::connect(std::path::Path::new(&dir).join(format!("discord-ipc-{}", index))).ok()?;
+    socket.set_read_timeout(Some(REPLY_TIMEOUT)).ok()?;
+    Some(socket)
+}
+
+// A named pipe opens like a file, it has no read timeout but discord always replies
+#[cfg(windows)]
+fn open_socket(index: usize) -> Option<Socket> {
+    let path = format!(r"\\?\pipe\discord-ipc-{}", index);
+    std::fs::OpenOptions::new().read(true).write(true).open(path).ok()
+}
+
+// A string in quotes with anything json needs escaped
+fn json_string(text: &str) -> String {
+    let mut json = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -0,0 +1,363 @@
+// a small entity-component store for the things in the playfield.
+// an entity is an index and the generation of its slot, each component type is a column with an optional
+// value per slot and systems are plain functions that run over the columns they need. slots are reused,
+// the generation goes up each time so a handle kept to a despawned entity never finds the slot's new one.
+// pickups, laser bolts and level obstacles live here, the balls, paddles and bricks are still GameObjects for now
+
+use crate::collision::{self, Filter, Layers, SpatialHash};
+use crate::pool::PoolStats;
+use crate::powerups::PowerUpKind;
+use crate::GameObject;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct Entity {
+    index: usize,
+    generation: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Position {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Velocity {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Size {
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Sprite {
+    pub colour: u32,
+    pub symbol: Option<&'static str>, // Drawn in black in the middle, so pickups can be told apart
+}
+
+// A path for an obstacle to follow over and over, about where it was placed
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Path {
+    Sweep { distance: f32, period: f32 }, // Over to the right by distance (left if negative) and back
+    Orbit { radius: f32, period: f32 },   // Round a circle centred on where it was placed, clockwise
+}
+
+impl Path {
+    // How far from where it was placed the path has gone after the given time, and its velocity there
+    pub fn at(&self, time: f32) -> ((f32, f32), Velocity) {
+        let period = match *self {
+            Path::Sweep { period, .. } | Path::Orbit { period, .. } => period,
+        };
+        let angular_speed = std::f32::consts::TAU / period;
+        let angle = angular_speed * time;
+        match *self {
+            Path::Sweep { distance, .. } => {
+                // Easing in and out of each end instead of turning round at full speed
+                let x = distance * (1.0 - angle.cos()) / 2.0;
+                let speed = distance * angular_speed * angle.sin() / 2.0;
+                ((x, 0.0), Velocity { x: speed, y: 0.0 })
+            }
+            Path::Orbit { radius, .. } => {
+                let offset = (radius * angle.cos(), radius * angle.sin());
+                let velocity = Velocity {
+                    x: -radius * angular_speed * angle.sin(),
+                    y: radius * angular_speed * angle.cos(),
+                };
+                (offset, velocity)
+            }
+        }
+    }
+}
+
+// An entity following a path
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Route {
+    pub path: Path,
+    pub origin: Position,
+    pub time: f32, // How long it has been following the path
+}
+
+// What happens when something runs into the entity
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Collider {
+    Solid,                // The ball bounces off it
+    PowerUp(PowerUpKind), // The bottom paddle catches it
+    Coin,                 // ...and this too, banked for the shop
+    Bolt,                 // A laser bolt, knocks out the first brick or obstacle it reaches
+}
+
+impl Collider {
+    pub fn filter(self) -> Filter {
+        Filter::on(match self {
+            Collider::Solid => Layers::OBSTACLE,
+            Collider::PowerUp(_) | Collider::Coin => Layers::PICKUP,
+            Collider::Bolt => Layers::PROJECTILE,
+        })
+    }
+}
+
+// Something to put in the world, see Game::spawn
+#[derive(Clone)]
+pub enum Spawn {
+    Block { body: GameObject, colour: u32, path: Option<Path> }, // Solid, following the path if it has one
+    PowerUp { kind: PowerUpKind, x: f32 },                        // Falling from the top of the field
+    Coin { x: f32, y: f32 },                                      // Falling from there, its centre
+    Bolt { x: f32, y: f32 },                                      // Flying up from there, its top left
+}
+
+// One component type, indexed by entity. Each value keeps the generation of the entity it belongs to
+pub struct Column<T> {
+    values: Vec<Option<(u32, T)>>,
+}
+
+impl<T> Default for Column<T> {
+    fn default() -> Self {
+        Column { values: Vec::new() }
+    }
+}
+
+impl<T> Column<T> {
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        match self.values.get(entity.index)? {
+            Some((generation, value)) if *generation == entity.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        match self.values.get_mut(entity.index)? {
+            Some((generation, value)) if *generation == entity.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn insert(&mut self, entity: Entity, value: T) {
+        if self.values.len() <= entity.index {
+            self.values.resize_with(entity.index + 1, || None);
+        }
+        self.values[entity.index] = Some((entity.generation, value));
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        if self.get(entity).is_some() {
+            self.values[entity.index] = None;
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.values.iter_mut().enumerate().filter_map(|(index, value)| {
+            value.as_mut().map(|(generation, value)| (Entity { index, generation: *generation }, value))
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.values.iter().enumerate().filter_map(|(index, value)| {
+            value.as_ref().map(|(generation, value)| (Entity { index, generation: *generation }, value))
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct World {
+    alive: Vec<bool>,
+    generations: Vec<u32>, // Each slot's, raised as its entity is despawned
+    free: Vec<usize>,      // Slots of despawned entities, reused before the columns grow
+    doomed: Vec<Entity>,   // To be despawned at the next cleanup()
+    changes: u64,          // Spawns and despawns so far, for noticing something appeared or went
+    pub positions: Column<Position>,
+    pub velocities: Column<Velocity>,
+    pub sizes: Column<Size>,
+    pub sprites: Column<Sprite>,
+    pub colliders: Column<Collider>,
+    pub routes: Column<Route>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        World::default()
+    }
+
+    // A new entity with no components
+    pub fn spawn(&mut self) -> Entity {
+        let index = match self.free.pop() {
+            Some(slot) => {
+                self.alive[slot] = true;
+                slot
+            }
+            None => {
+                self.alive.push(true);
+                self.generations.push(0);
+                self.alive.len() - 1
+            }
+        };
+        self.changes += 1;
+        Entity { index, generation: self.generations[index] }
+    }
+
+    // Gone straight away. Despawning an entity that is already gone does nothing
+    pub fn despawn(&mut self, entity: Entity) {
+        if !self.is_alive(entity) {
+            return;
+        }
+        self.alive[entity.index] = false;
+        self.changes += 1;
+        self.generations[entity.index] = self.generations[entity.index].wrapping_add(1);
+        self.free.push(entity.index);
+        self.positions.remove(entity);
+        self.velocities.remove(entity);
+        self.sizes.remove(entity);
+        self.sprites.remove(entity);
+        self.colliders.remove(entity);
+        self.routes.remove(entity);
+    }
+
+    // Gone at the next cleanup(), for code that is going through the world and can't change it yet
+    pub fn despawn_later(&mut self, entity: Entity) {
+        self.doomed.push(entity);
+    }
+
+    // Despawn everything despawn_later() was given, once a physics step has finished with it
+    pub fn cleanup(&mut self) {
+        for entity in std::mem::take(&mut self.doomed) {
+            self.despawn(entity);
+        }
+    }
+
+    // Goes up with every spawn and despawn, so a frame drawn while it stays the same shows the same entities
+    pub fn changes(&self) -> u64 {
+        self.changes
+    }
+
+    // Entities alive and the slots there are for them, despawned ones' slots being reused by the next spawns
+    pub fn stats(&self) -> PoolStats {
+        PoolStats { live: self.alive.len() - self.free.len(), slots: self.alive.len() }
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.alive.get(entity.index).copied().unwrap_or(false) && self.generations[entity.index] == entity.generation
+    }
+
+    // Remove every entity that has the given collider, e.g. all the pickups
+    pub fn despawn_all(&mut self, collider: impl Fn(&Collider) -> bool) {
+        let doomed: Vec<Entity> = self
+            .colliders
+            .iter()
+            .filter(|(_, entity_collider)| collider(entity_collider))
+            .map(|(entity, _)| entity)
+            .collect();
+        for entity in doomed {
+            self.despawn(entity);
+        }
+    }
+
+    // Despawn everything, the slots keep their generations so no old handle finds what is spawned next
+    pub fn clear(&mut self) {
+        for index in 0..self.alive.len() {
+            self.despawn(Entity { index, generation: self.generations[index] });
+        }
+        self.doomed.clear();
+    }
+
+    // The entity's rectangle as a GameObject, for the collision code shared with the balls and paddles
+    pub fn body(&self, entity: Entity) -> Option<GameObject> {
+        let position = self.positions.get(entity)?;
+        let size = self.sizes.get(entity)?;
+        let velocity = self.velocities.get(entity).copied().unwrap_or(Velocity { x: 0.0, y: 0.0 });
+        Some(GameObject {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+            vel_x: velocity.x,
+            vel_y: velocity.y,
+        })
+    }
+}
+
+// Spawn a still, solid block such as a level obstacle
+pub fn spawn_block(world: &mut World, block: &GameObject, colour: u32) -> Entity {
+    let entity = world.spawn();
+    world.positions.insert(entity, Position { x: block.x, y: block.y });
+    world.sizes.insert(entity, Size { width: block.width, height: block.height });
+    world.sprites.insert(entity, Sprite { colour, symbol: None });
+    world.colliders.insert(entity, Collider::Solid);
+    entity
+}
+
+// The same as spawn_block for a block that follows a path from there
+pub fn spawn_moving_block(world: &mut World, block: &GameObject, colour: u32, path: Path) -> Entity {
+    let entity = spawn_block(world, block, colour);
+    let origin = Position { x: block.x, y: block.y };
+    world.routes.insert(entity, Route { path, origin, time: 0.0 });
+    world.velocities.insert(entity, path.at(0.0).1);
+    entity
+}
+
+// Movement system: everything with a velocity moves on by it, apart from anything on a route
+pub fn movement(world: &mut World, delta_time: f32) {
+    let World { positions, velocities, routes, .. } = world;
+    for (entity, velocity) in velocities.iter() {
+        if routes.get(entity).is_some() {
+            continue;
+        }
+        if let Some(position) = positions.get_mut(entity) {
+            position.x += velocity.x * delta_time;
+            position.y += velocity.y * delta_time;
+        }
+    }
+}
+
+// Route system: entities on a path go wherever it has got to, with the velocity kept to match
+// so whatever bounces off them can tell how they are moving
+pub fn follow_routes(world: &mut World, delta_time: f32) {
+    let World { positions, velocities, routes, .. } = world;
+    for (entity, route) in routes.iter_mut() {
+        route.time += delta_time;
+        let ((x, y), velocity) = route.path.at(route.time);
+        positions.insert(entity, Position { x: route.origin.x + x, y: route.origin.y + y });
+        velocities.insert(entity, velocity);
+    }
+}
+
+// Cleanup system: moving entities that have left the top or bottom of the field are gone for good
+pub fn despawn_outside(world: &mut World, field_height: f32) {
+    let outside: Vec<Entity> = world
+        .velocities
+        .iter()
+        .filter(|&(entity, _)| {
+            world.body(entity).is_some_and(|body| body.y >= field_height || body.y + body.height <= 0.0)
+        })
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in outside {
+        world.despawn(entity);
+    }
+}
+
+// Where the entities with a collider are, for finding the ones near something. Rebuild it once they've moved
+pub fn index(world: &World, grid: &mut SpatialHash<Entity>) {
+    grid.clear();
+    for (entity, _) in world.colliders.iter() {
+        if let Some(body) = world.body(entity) {
+            grid.insert(entity, &body);
+        }
+    }
+}
+
+// Collision query: the entities whose collider the filter collides with and whose rectangle touches the
+// body, edges included
+pub fn touching(world: &World, body: &GameObject, filter: Filter) -> Vec<(Entity, Collider)> {
+    world
+        .colliders
+        .iter()
+        .filter(|&(entity, collider)| {
+            filter.collides_with(collider.filter())
+                && world.body(entity).is_some_and(|other| collision::touches(body, &other))
+        })
+        .map(|(entity, &collider)| (entity, collider))
+        .collect()
+}
@@ -0,0 +1,24 @@
+// what can stop the game from running, reported to the player as a message rather than a panic
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum GameError {
+    Window(String),   // Opening the window, or showing a frame in it
+    Terminal(String), // Setting up the terminal for --terminal
+    Network(String),  // Hosting or joining a network game
+    Replay { path: String, error: String },
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::Window(e) => write!(f, "Window error: {}", e),
+            GameError::Terminal(e) => write!(f, "Error setting up the terminal: {}", e),
+            GameError::Network(e) => write!(f, "Network error: {}", e),
+            GameError::Replay { path, error } => write!(f, "{}: {}", path, error),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
@@ -0,0 +1,38 @@
+// what happened during a physics step, queued as the step runs into it and handed out once it's over to
+// whatever reacts to it: the sounds, the HUD, particles and achievements. the step itself only says what
+// happened, so it doesn't need to know who is listening
+
+use crate::powerups::PowerUpKind;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameEvent {
+    PaddleHit { at: (f32, f32) },  // Where the ball was as it came off either paddle
+    WallBounce { at: (f32, f32) }, // Off a wall, an obstacle or the shield
+    LifeLost { lives: i32 },       // Or a point lost in the modes without lives, lives is what's left
+    LevelCleared { level: u32 },   // The level just finished
+    PowerUpCollected { kind: PowerUpKind, at: (f32, f32) },
+}
+
+#[derive(Default)]
+pub struct EventQueue {
+    events: Vec<GameEvent>,
+}
+
+impl EventQueue {
+    pub fn push(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    // Everything queued since the last drain, oldest first. Hand the list back to recycle once it's been
+    // gone through, so the next step queues into the same buffer instead of allocating a new one
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn recycle(&mut self, mut events: Vec<GameEvent>) {
+        events.clear();
+        // Anything queued while the drained ones were handled stays queued
+        events.append(&mut self.events);
+        self.events = events;
+    }
+}
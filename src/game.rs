@@ -0,0 +1,2492 @@
+// the game itself: the title screen, the rules of each mode, input and the simulation.
+// drawing the playfield is in game/render.rs, what needs drawing again in game/redraw.rs, each screen is a scene in game/scenes.rs
+
+mod redraw;
+mod render;
+mod scenes;
+
+pub use scenes::Scene;
+
+use crate::achievements::{self, Achievement, Achievements};
+use crate::ai::AiController;
+use crate::assets::{self, Texture};
+use crate::audio::{self, AudioEngine, Cue, Sound};
+use crate::balls::BallKind;
+use crate::boss::{self, Boss};
+use crate::bricks::{self, Brick};
+use crate::clock::{Clock, Instant, SystemClock};
+use crate::collision::{self, Filter, Layers, SpatialHash};
+use crate::config::{Config, ConfigWatcher};
+use crate::crt::CrtFilter;
+use crate::daynight::{self, ColourLut};
+use crate::diagnostics::FrameStats;
+#[cfg(feature = "discord")]
+use crate::discord::{Activity, Presence};
+use crate::difficulty::{self, Preset};
+use crate::ecs::{self, Collider, Entity, Spawn, World};
+use crate::error::GameError;
+use crate::events::{EventQueue, GameEvent};
+use crate::gamepad::Gamepads;
+use crate::highscores::{self, HighScores};
+use crate::image::{self, Clip};
+use crate::lasers::{self, Lasers};
+use crate::levels::{Campaign, Level, Obstacle};
+use crate::leaderboard::Leaderboard;
+use crate::locale::Locale;
+use crate::menu::{Menu, MenuItem};
+use crate::modes::{End, Field, Modes, Outcome, Rules, Scoreboard};
+use crate::net::{Connection, Message, Role, Snapshot};
+use crate::palette::{self, Palette};
+use crate::particles::Particles;
+use crate::popups::Popups;
+use crate::physics;
+use crate::powerups::{self, ActiveEffect, PowerUpKind, Spawner};
+use crate::profile::Profile;
+use crate::input::{InputSource, InputState, Key};
+use crate::renderer::{DirtyRects, Display, Scaled};
+#[cfg(feature = "sdl2")]
+use crate::renderer::SdlDisplay;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::renderer::WindowRenderer;
+use crate::replay::{self, FrameInput, Replay};
+use crate::rng::Rng;
+#[cfg(feature = "lua")]
+use crate::scripting::{self, Command, Script};
+use crate::starfield::Starfield;
+use crate::shop::{self, Shop};
+use crate::stats::Stats;
+use crate::tiles::Tilemap;
+use crate::tutorial::{self, Tutorial};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::viewport::{Bars, Screen};
+use crate::GameObject;
+use std::collections::VecDeque;
+use scenes::{ControlsScene, GameOverScene, MenuScene, PauseScene, PlayScene, ShopScene, StatsScene, TutorialScene};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+
+// Constants for game timing and rules, sizes and speeds live in the config
+const SERVE_COUNTDOWN: Duration = Duration::from_secs(3); // Counted down on screen before the next serve
+const DEMO_IDLE_TIME: Duration = Duration::from_secs(15); // Left this long the title screen starts a demo game
+const TIME_SCALES: [f32; 3] = [1.0, 0.75, 0.5]; // The speeds the title screen steps through
+const CUE_PITCH_RISE: f32 = 1.0; // Bounces right by the bottom paddle play this much faster than at the top
+const TOAST_TIME: Duration = Duration::from_secs(4); // How long each newly unlocked achievement is shown for
+const AI_REACTION_DELAY: f32 = 0.1; // Seconds between the AI looking at the ball
+const AI_MAX_SPEED: f32 = 300.0; // Slower than a human paddle so the AI can be beaten
+const INITIALS_LENGTH: usize = 3;
+const WIDE_PADDLE_FACTOR: f32 = 1.5;
+const SLOW_BALL_FACTOR: f32 = 0.6;
+const FAST_BALL_FACTOR: f32 = 1.4;
+const SHRINK_PADDLE_FACTOR: f32 = 0.6;
+const SHIELD_GAP: f32 = 3.0; // Between the bottom paddle and the shield under it
+const WORLD_GRID_CELL: f32 = 64.0; // Side of a cell in the grid the balls find obstacles through
+const MIN_SUBSTEP_TRAVEL: f32 = 0.5; // Pixels, so a ball or paddle sized down to nothing can't need endless substeps
+const SHIELD_HEIGHT: f32 = 4.0;
+const SHIELD_PARTICLES: usize = 24;
+const PICKUP_PARTICLES: usize = 16; // Thrown out of the paddle in the power-up's colour as it's caught
+const MULTI_BALL_ANGLE: f32 = 0.35; // radians either side of the original ball
+const SPLIT_ANGLE: f32 = 0.25; // radians either side of where a split ball was going
+const COIN_CHANCE: f32 = 0.15; // Of a broken brick dropping a coin
+const RALLY_COIN_HITS: u32 = 10; // A coin drops from the top every this many hits in a rally
+const MIN_SERVE_ANGLE: f32 = 0.45; // radians away from straight up/down, serves are somewhere in between
+const MAX_SERVE_ANGLE: f32 = 0.95;
+pub const HEADLESS_FRAME_TIME: f32 = 1.0 / 60.0; // Simulated time per frame when there is no window
+const PHYSICS_STEP: f32 = 1.0 / 120.0; // The simulation always advances in steps of this many seconds
+const MAX_FRAME_TIME: f32 = 0.25; // Longer stalls are dropped rather than simulated in one go
+const OBSTACLE_COLOUR: u32 = 0xFF808080;
+const CLIP_SECONDS: f32 = 5.0; // Length of the GIF saved with F10
+const CLIP_FRAME_RATE: f32 = 20.0;
+const MAX_PENDING_INPUTS: usize = 120; // Unacknowledged inputs kept by a network client, 2s worth
+const REMOTE_SNAP_DISTANCE: f32 = 40.0; // Smaller corrections to the other player's paddle are eased in
+const REMOTE_BLEND: f32 = 0.5;
+const LIFE_LOST_SHAKE: f32 = 10.0; // Pixels the playfield shakes by at first, dying away over a fraction of a second
+const HARD_HIT_SHAKE: f32 = 4.0;
+const HARD_HIT_SPEED: f32 = 0.7; // Paddle hits leaving at this fraction of the speed limit or faster shake the screen
+const SHAKE_DECAY: f32 = 8.0; // Per second
+const MAX_COLLISION_POINTS: usize = 16; // Recent bounces kept for the debug overlay
+const COMBO_STEP: u32 = 5; // Paddle hits in a row for each step up in the score multiplier
+const MAX_MULTIPLIER: u32 = 5;
+const COMBO_PARTICLES: usize = 40; // Thrown out of the paddle each time the multiplier goes up
+const COMBO_PARTICLE_SPEED: f32 = 300.0;
+const BOSS_HIT_POINTS: i32 = 5;
+const BOSS_PARTICLES: usize = 80; // Thrown out of the boss as it is beaten
+const BOSS_BEATEN_SHAKE: f32 = 12.0;
+const MAGNET_LAUNCH_ANGLE: f32 = 0.8; // radians off straight up for a ball launched with the paddle at full speed
+
+// The first ball, stuck to the bottom paddle by the magnet until the serve key launches it
+#[derive(Clone, Copy)]
+struct HeldBall {
+    offset: f32, // From the paddle's left end to the ball's centre
+    armed: bool, // The serve key has been let go since the catch, so pressing it launches
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameState {
+    Menu,
+    Playing,
+    Paused,
+    GameOver,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameMode {
+    Solo,
+    TwoPlayer,
+    VsAi,
+    Breakout,
+    Survival, // One ball that never stops speeding up, scored by the seconds it is kept in play
+    Plugin(usize), // Registered at startup, see modes::Modes
+}
+
+pub struct Game {
+    display: Option<Box<dyn Display>>, // None in headless mode, where nothing is drawn and the CPU plays
+    input_source: Option<Box<dyn InputSource>>, // Read instead of the display's keys, see set_input()
+    keys: InputState,                           // What the keys did this frame
+    clock: Box<dyn Clock>, // Where the game gets the time from, see set_clock()
+    config: Config,
+    config_watcher: Option<ConfigWatcher>, // Set to pick up changes to the config file as it is saved
+    locale: Locale, // The words on screen, in the player's language
+    scenes: Vec<Rc<dyn Scene>>, // The title screen at the bottom, whatever is showing on top
+    menu: Menu,
+    mode: GameMode,
+    balls: Vec<GameObject>,
+    ball_kinds: Vec<BallKind>, // Each ball's kind, any ball added without one is normal
+    lasers: Lasers,
+    firing: bool, // The serve key is held, which fires the laser while it's running
+    held_ball: Option<HeldBall>,
+    paddle: GameObject,
+    top_paddle: GameObject,
+    ai: AiController,
+    autopilot: AiController, // Steers the bottom paddle in headless mode
+    bricks: Vec<Brick>,
+    brick_grid: bricks::Grid,
+    boss: Option<Boss>, // On the boss levels in breakout mode, instead of bricks
+    campaign: Option<Campaign>, // Level files to play before the generated levels
+    arena: Option<Level>,       // Obstacles for the modes without bricks
+    background: Option<u32>, // Set by levels with their own background colour
+    tiles: Option<Rc<Tilemap>>, // Set by levels with a tileset, drawn over the background colour
+    forces: (f32, f32),      // The level's gravity and wind, pulling the balls down and sideways
+    theme: usize,
+    difficulty: usize, // Index into difficulty::PRESETS
+    time_scale: f32,   // Fraction of real time the game moves at, less than 1 to give slower players a chance
+    world: World, // Pickups and level obstacles
+    world_grid: SpatialHash<Entity>, // Where the world's colliders were after they last moved
+    effects: Vec<ActiveEffect>,
+    spawner: Spawner,
+    rng: Rng,   // Reseeded for every game so it can be replayed
+    seeds: Rng, // Where each game's seed comes from
+    shake_rng: Rng, // Separate from rng so shaking doesn't change how a game plays out
+    shake: f32,     // How far the playfield can move off centre, in pixels
+    shake_offset: (f32, f32),
+    crt: Option<CrtFilter>, // Set while the CRT effect is on
+    redraw: redraw::Redraw,
+    recording: Replay,
+    playback: Option<Replay>, // The replay being watched instead of reading the player's input
+    playback_frame: usize,
+    net: Option<Connection>, // Set for a game against another machine
+    remote_paddle_vel: f32,  // The client's paddle speed as last heard by the host
+    remote_input_seq: u32,
+    pending_inputs: VecDeque<(u32, f32, f32)>, // Client inputs the host hasn't seen yet: seq, paddle speed, frame time
+    client_frame_time: f32,
+    audio: AudioEngine,
+    hud_message: Option<(String, Instant)>, // Short notice shown in the middle of the HUD
+    mouse_control: bool,
+    mouse_x: Option<f32>,
+    gamepads: Gamepads,
+    last_frame_time: Instant,
+    accumulator: f32, // Real time not yet simulated, always less than PHYSICS_STEP after update()
+    interpolation: f32, // How far between the previous and current step to draw, 0.0 to 1.0
+    prev_ball_positions: Vec<(f32, f32)>, // Positions before the last step, empty after a reset
+    trails: Vec<VecDeque<(f32, f32)>>,    // Each ball's recent positions, oldest first
+    prev_paddle_x: f32,
+    prev_top_paddle_x: f32,
+    game_is_running: bool,
+    last_key_press: Instant,
+    demo: bool, // The CPU is playing by itself until someone presses a key
+    tutorial: Option<Tutorial>, // Set while the tutorial is running
+    modes: Modes,
+    scoreboard: Scoreboard,
+    high_scores: HighScores,
+    survival_scores: HighScores, // Kept apart, they are seconds survived rather than points
+    leaderboard: Option<Leaderboard>, // Set when the config names a leaderboard server
+    #[cfg(feature = "discord")]
+    discord: Option<(Presence, Option<(GameState, GameMode)>)>, // With what it last showed, it changes with those
+    #[cfg(feature = "lua")]
+    script: Option<Script>, // Set when the config names a Lua script
+    achievements: Achievements,
+    stats: Stats,
+    profile: Profile,
+    shop: Shop,
+    toasts: VecDeque<Achievement>, // Newly unlocked achievements to show, the first one is on screen
+    toast_since: Instant,          // When the first toast went up
+    initials: Option<String>, // Some while a new high scorer is typing their initials
+    serve_dir: f32,
+    ball_speed: f32, // Serve speed, raised as the game goes on
+    paddle_hits: u32,
+    rally: u32,         // Paddle hits since the last serve
+    play_time: f32,     // Seconds simulated this game
+    session_time: f32,  // Seconds simulated since the game was started, which moves the day and night cycle on
+    daylight: Option<(u32, ColourLut)>, // The day and night cycle's step and its colours, None with it off
+    paddle_moved: bool, // Has player 1's paddle moved at all this game
+    combo: u32, // Paddle hits since the last life was lost, in the modes with lives
+    particles: Particles,
+    starfield: Starfield,
+    popups: Popups, // The points scored floating up from where they were won
+    show_debug: bool,
+    collision_points: VecDeque<(f32, f32)>, // Where the ball's centre was at the last few bounces, oldest first
+    events: EventQueue, // What happened in the physics step, see handle_events()
+    debug_frame_time: f32,                  // The last frame's time, for the debug overlay
+    show_frame_stats: bool,
+    frame_stats: FrameStats,
+    screenshot_requested: bool,
+    clip: Clip,
+    is_paused: bool,
+    pause_start: Option<Instant>,
+    serve_requested: bool,
+    paused_at: Option<Instant>,
+    ball_reset_pending: bool,
+    ball_texture: Option<Texture>,
+    paddle_texture: Option<Texture>,
+    skin_texture: Option<Texture>, // Player 1's paddle in the skin picked, over paddle_texture
+}
+
+// The config's arena, None to play in an empty one if it won't load
+fn load_arena(path: &str) -> Option<Level> {
+    Level::load_arena(path).map_err(|e| eprintln!("Could not load arena {}: {}", path, e)).ok()
+}
+
+fn spawn_obstacles(world: &mut World, obstacles: &[Obstacle]) {
+    for obstacle in obstacles {
+        let colour = obstacle.colour.unwrap_or(OBSTACLE_COLOUR);
+        match obstacle.path {
+            Some(path) => ecs::spawn_moving_block(world, &obstacle.body, colour, path),
+            None => ecs::spawn_block(world, &obstacle.body, colour),
+        };
+    }
+}
+
+// The config's Lua script, None to go on without it if it won't load
+#[cfg(feature = "lua")]
+fn load_script(path: &str) -> Option<Script> {
+    Script::load(path).map_err(|e| eprintln!("Could not load script {}: {}", path, e)).ok()
+}
+
+// The built in game modes and any the plugins folder adds
+#[cfg(feature = "plugins")]
+fn load_modes(config: &Config) -> Modes {
+    let mut modes = Modes::new();
+    modes.load_plugins(&config.plugins_dir);
+    modes
+}
+
+#[cfg(not(feature = "plugins"))]
+fn load_modes(_config: &Config) -> Modes {
+    Modes::new()
+}
+
+// A sprite from the config file, None to go on with plain rectangles if it won't load
+fn load_sprite(path: &str) -> Option<Texture> {
+    assets::load_png(Path::new(path)).map_err(|e| eprintln!("Could not load sprite {}: {}", path, e)).ok()
+}
+
+// Built with the sdl2 feature the window is SDL2's, falling back to minifb when SDL2 isn't installed
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open_window(config: &Config) -> Result<Box<dyn Display>, GameError> {
+    #[cfg(feature = "sdl2")]
+    match SdlDisplay::new(Screen::new(config), config.fullscreen, config.vsync) {
+        Ok(display) => return Ok(Box::new(display)),
+        Err(e) => eprintln!("Couldn't open an SDL2 window, using minifb instead: {}", e),
+    }
+
+    let display = WindowRenderer::new(Screen::new(config), config.fullscreen);
+    Ok(Box::new(display.map_err(|e| GameError::Window(e.to_string()))?))
+}
+
+// The browser has no windows to open, web.rs passes its canvas to Game::with_display instead
+#[cfg(target_arch = "wasm32")]
+pub fn open_window(_config: &Config) -> Result<Box<dyn Display>, GameError> {
+    Err(GameError::Window("there are no windows on the web, use Game::with_display".to_string()))
+}
+
+impl Game {
+    // A game in a minifb window, or with no window at all when headless
+    pub fn new(config: Config, headless: bool, seed: u64) -> Result<Self, GameError> {
+        let display = if headless { None } else { Some(open_window(&config)?) };
+        Ok(Game::with_display(config, display, seed))
+    }
+
+    // A game shown on any display backend, None to run headless
+    pub fn with_display(config: Config, display: Option<Box<dyn Display>>, seed: u64) -> Self {
+        let headless = display.is_none();
+        let audio = if headless {
+            AudioEngine::silent()
+        } else {
+            AudioEngine::new(config.music_file.as_deref(), config.music_volume)
+        };
+
+        let profile = Profile::load();
+        let (render_width, render_height) = config.render_size();
+        let skin_texture = profile.selected().texture(config.paddle_width as usize, config.paddle_height as usize);
+        let ball = GameObject {
+            x: 20.0,
+            y: 20.0,
+            width: config.ball_size,
+            height: config.ball_size,
+            vel_x: config.ball_speed,
+            vel_y: config.ball_speed,
+        };
+
+        let paddle = GameObject {
+            width: config.paddle_width,
+            height: config.paddle_height,
+            x: (config.window_width as f32 - config.paddle_width) / 2.0,
+            y: config.window_height as f32 - 2.0 * config.paddle_height,
+            vel_x: 0.0,
+            vel_y: 0.0,
+        };
+
+        let top_paddle = GameObject {
+            width: config.paddle_width,
+            height: config.paddle_height,
+            x: (config.window_width as f32 - config.paddle_width) / 2.0,
+            y: config.paddle_height,
+            vel_x: 0.0,
+            vel_y: 0.0,
+        };
+
+        let modes = load_modes(&config);
+        let mut menu = Menu::new();
+        for mode in modes.plugins() {
+            menu.add_mode(mode);
+        }
+
+        Game {
+            display,
+            input_source: None,
+            keys: InputState::default(),
+            clock: Box::new(SystemClock),
+            config_watcher: None,
+            locale: Locale::new(config.language.as_deref()),
+            scenes: vec![Rc::new(MenuScene)],
+            menu,
+            mode: GameMode::Solo,
+            balls: vec![ball],
+            ball_kinds: Vec::new(),
+            lasers: Lasers::default(),
+            firing: false,
+            held_ball: None,
+            paddle,
+            top_paddle,
+            ai: AiController::new(AI_REACTION_DELAY, AI_MAX_SPEED),
+            autopilot: AiController::new(AI_REACTION_DELAY, config.paddle_speed),
+            bricks: Vec::new(),
+            brick_grid: bricks::grid(&[]),
+            boss: None,
+            campaign: Campaign::load(&config.levels_dir),
+            arena: config.arena.as_deref().and_then(load_arena),
+            background: None,
+            tiles: None,
+            forces: (0.0, 0.0),
+            theme: config.theme,
+            difficulty: config.difficulty,
+            time_scale: config.time_scale,
+            world: World::new(),
+            world_grid: SpatialHash::new(WORLD_GRID_CELL),
+            effects: Vec::new(),
+            spawner: Spawner::new(),
+            rng: Rng::new(seed),
+            seeds: Rng::new(seed),
+            shake_rng: Rng::new(seed),
+            shake: 0.0,
+            shake_offset: (0.0, 0.0),
+            crt: config.crt.then(|| CrtFilter::new(render_width, render_height)),
+            redraw: redraw::Redraw::default(),
+            recording: Replay::new(seed, GameMode::Solo),
+            playback: None,
+            playback_frame: 0,
+            net: None,
+            remote_paddle_vel: 0.0,
+            remote_input_seq: 0,
+            pending_inputs: VecDeque::new(),
+            client_frame_time: 0.0,
+            audio,
+            hud_message: None,
+            mouse_control: config.mouse_control,
+            mouse_x: None,
+            gamepads: Gamepads::new(),
+            last_frame_time: Instant::now(),
+            accumulator: 0.0,
+            interpolation: 0.0,
+            prev_ball_positions: Vec::new(),
+            trails: Vec::new(),
+            prev_paddle_x: 0.0,
+            prev_top_paddle_x: 0.0,
+            game_is_running: true,
+            last_key_press: Instant::now(),
+            demo: false,
+            tutorial: None,
+            modes,
+            scoreboard: Scoreboard { lives: config.lives, level: 1, ..Scoreboard::default() },
+            high_scores: HighScores::load(),
+            survival_scores: HighScores::load_survival(),
+            leaderboard: Leaderboard::new(config.leaderboard_url.as_deref(), config.leaderboard_key.as_deref()),
+            #[cfg(feature = "discord")]
+            discord: config.discord_client_id.as_deref().filter(|_| !headless).and_then(Presence::connect).map(|presence| (presence, None)),
+            #[cfg(feature = "lua")]
+            script: config.script.as_deref().and_then(load_script),
+            achievements: Achievements::load(),
+            stats: Stats::load(),
+            profile,
+            shop: Shop::default(),
+            toasts: VecDeque::new(),
+            toast_since: Instant::now(),
+            initials: None,
+            serve_dir: 1.0,
+            ball_speed: config.ball_speed,
+            paddle_hits: 0,
+            rally: 0,
+            play_time: 0.0,
+            session_time: 0.0,
+            daylight: None,
+            paddle_moved: false,
+            combo: 0,
+            particles: Particles::new(),
+            starfield: Starfield::new(config.window_width, config.window_height),
+            popups: Popups::new(),
+            show_debug: false,
+            collision_points: VecDeque::new(),
+            events: EventQueue::default(),
+            debug_frame_time: 0.0,
+            show_frame_stats: false,
+            frame_stats: FrameStats::new(),
+            screenshot_requested: false,
+            clip: Clip::new(
+                CLIP_SECONDS,
+                1000.0 / config.frame_target_time as f32,
+                CLIP_FRAME_RATE,
+                render_width,
+                render_height,
+            ),
+            is_paused: false,
+            pause_start: None,
+            serve_requested: false,
+            paused_at: None,
+            ball_reset_pending: false,
+            ball_texture: config.ball_sprite.as_deref().and_then(load_sprite),
+            paddle_texture: config.paddle_sprite.as_deref().and_then(load_sprite),
+            skin_texture,
+            config,
+        }
+    }
+
+    // Watch a recorded game instead of taking the player's input
+    pub fn start_replay(&mut self, replay: Replay) {
+        let mode = replay.mode;
+        if !self.modes.contains(mode) {
+            eprintln!("The replay is of a game mode from a plugin that isn't loaded");
+            return;
+        }
+        self.playback = Some(replay);
+        self.start_game(mode);
+    }
+
+    // Apply changes to the config file as it is saved, see reload_config() for which settings change
+    pub fn watch_config(&mut self, path: &str) {
+        self.config_watcher = Some(ConfigWatcher::new(path));
+    }
+
+    // Two player game against another machine, see sync_network()
+    pub fn start_network_game(&mut self, net: Connection) {
+        self.net = Some(net);
+        self.start_game(GameMode::TwoPlayer);
+    }
+
+    pub fn state(&self) -> GameState {
+        self.scenes.last().map_or(GameState::Menu, |scene| scene.state())
+    }
+
+    // Show a scene on top of the current one, which carries on underneath it
+    pub fn push_scene(&mut self, scene: Rc<dyn Scene>) {
+        self.scenes.push(scene);
+    }
+
+    // Go back to the scene underneath, the bottom scene always stays
+    pub fn pop_scene(&mut self) {
+        if self.scenes.len() > 1 {
+            self.scenes.pop();
+        }
+    }
+
+    // Leave every scene for a new one
+    pub fn switch_scene(&mut self, scene: Rc<dyn Scene>) {
+        self.scenes.clear();
+        self.scenes.push(scene);
+    }
+
+    // False once the player has quit or closed the window
+    pub fn is_running(&self) -> bool {
+        self.game_is_running && self.input().is_none_or(InputSource::is_open)
+    }
+
+    // Take the keys and mouse from somewhere other than the display, such as a ScriptedInput in tests.
+    // A headless game with one is played with it instead of by the CPU, still HEADLESS_FRAME_TIME a frame
+    pub fn set_input(&mut self, input: Box<dyn InputSource>) {
+        self.input_source = Some(input);
+    }
+
+    // Read the time from another clock, such as a ManualClock in tests so the serve countdown and the rest
+    // wait for them to move time on
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        let now = clock.now();
+        self.clock = clock;
+        self.last_frame_time = now;
+        self.last_key_press = now;
+        self.toast_since = now;
+    }
+
+    // How long ago it was by the game's clock
+    fn since(&self, time: Instant) -> Duration {
+        self.clock.now().duration_since(time)
+    }
+
+    // Run one frame: read the input, talk to the other machine and move everything on.
+    // Without a window or input the CPU plays, and without a window each frame is HEADLESS_FRAME_TIME long
+    pub fn step(&mut self) {
+        let input = match self.input_source.as_deref() {
+            Some(input) => Some(input),
+            None => self.display.as_deref().map(|display| display as &dyn InputSource),
+        };
+        if let Some(input) = input {
+            self.keys.update(input);
+        }
+        self.handle_fullscreen_key();
+        self.handle_crt_key();
+        if self.keys.just_pressed(Key::F3) {
+            self.show_frame_stats = !self.show_frame_stats;
+        }
+        self.handle_capture_keys();
+        self.update_toasts();
+        self.reload_config();
+        self.update_daylight();
+        if let Some(leaderboard) = self.leaderboard.as_mut() {
+            leaderboard.poll();
+        }
+        if !self.keys.just_pressed_keys().is_empty() {
+            self.last_key_press = self.clock.now();
+        }
+        self.sync_network();
+        if let Some(scene) = self.scenes.last().cloned() {
+            scene.handle_input(self);
+            scene.update(self);
+        }
+        #[cfg(feature = "discord")]
+        self.update_presence();
+    }
+
+    // Move the day and night cycle on to the step for the session's play time
+    fn update_daylight(&mut self) {
+        let step = self.config.day_night.then(|| daynight::step(self.session_time));
+        if step != self.daylight.as_ref().map(|(step, _)| *step) {
+            self.daylight = step.map(|step| (step, ColourLut::for_step(step)));
+        }
+    }
+
+    // Take the settings that can change mid-game from a newly saved config file, the ones that changed anyway,
+    // so a theme or volume picked in the game stays unless the file changes it too. Sizes, lives and the
+    // rest of the files the game loads wait for a restart
+    fn reload_config(&mut self) {
+        let Some(watcher) = self.config_watcher.as_mut() else {
+            return;
+        };
+        let Some(new) = watcher.poll() else {
+            return;
+        };
+        println!("Reloaded {}", watcher.path());
+
+        // Settings the game keeps a copy of, which can also be changed while playing
+        if new.theme != self.config.theme {
+            self.theme = new.theme;
+        }
+        if new.time_scale != self.config.time_scale {
+            self.time_scale = new.time_scale;
+        }
+        if new.mouse_control != self.config.mouse_control {
+            self.mouse_control = new.mouse_control;
+        }
+        if new.music_volume != self.config.music_volume {
+            self.audio.change_music_volume(new.music_volume - self.audio.music_volume);
+        }
+        // The next serve is as much faster or slower as the base speed is
+        if new.ball_speed != self.config.ball_speed {
+            self.ball_speed *= new.ball_speed / self.config.ball_speed;
+        }
+        self.autopilot = AiController::new(AI_REACTION_DELAY, new.paddle_speed);
+
+        self.config = Config {
+            theme: new.theme,
+            bars: new.bars,
+            screen_shake: new.screen_shake,
+            starfield: new.starfield,
+            day_night: new.day_night,
+            ball_speed: new.ball_speed,
+            ball_max_speed: new.ball_max_speed,
+            ball_trail_length: new.ball_trail_length,
+            spin_transfer: new.spin_transfer,
+            paddle_speed: new.paddle_speed,
+            speed_up_percent: new.speed_up_percent,
+            hits_per_speed_up: new.hits_per_speed_up,
+            music_volume: new.music_volume,
+            position_cues: new.position_cues,
+            mouse_control: new.mouse_control,
+            time_scale: new.time_scale,
+            keys: new.keys,
+            ..std::mem::take(&mut self.config)
+        };
+        self.show_hud_message(self.locale.get("hud.config_reloaded").to_string());
+    }
+
+    // Tell discord what's being played as the game moves between screens and modes,
+    // the score is as it was then so discord isn't asked to update too often
+    #[cfg(feature = "discord")]
+    fn update_presence(&mut self) {
+        let showing = (self.state(), self.mode);
+        if self.discord.as_ref().is_none_or(|(_, shown)| *shown == Some(showing)) {
+            return;
+        }
+        let mode = self.rules().name();
+        let started = crate::clock::unix_time().as_secs().saturating_sub(self.play_time as u64);
+        let (details, start) = match self.state() {
+            GameState::Menu => ("On the title screen".to_string(), None),
+            GameState::Playing => (mode.to_string(), Some(started)),
+            GameState::Paused => (format!("{} (paused)", mode), Some(started)),
+            GameState::GameOver => (format!("{} - game over", mode), None),
+        };
+        let state = if self.state() == GameState::Menu { String::new() } else { self.summary() };
+        let activity = Activity { details, state, start };
+        if let Some((presence, shown)) = self.discord.as_mut() {
+            *shown = Some(showing);
+            if let Err(e) = presence.set_activity(&activity) {
+                eprintln!("Lost the connection to Discord: {}", e);
+                self.discord = None;
+            }
+        }
+    }
+
+    // One line on how the game is going, for headless runs
+    pub fn summary(&self) -> String {
+        self.rules().summary(&self.scoreboard)
+    }
+
+    // The score, lives and level so far, for frontends and tests that drive a headless game
+    pub fn scoreboard(&self) -> &Scoreboard {
+        &self.scoreboard
+    }
+
+    pub fn balls(&self) -> &[GameObject] {
+        &self.balls
+    }
+
+    // Player 1's paddle, at the bottom
+    pub fn paddle(&self) -> &GameObject {
+        &self.paddle
+    }
+
+    // Player 2's or the CPU's paddle, only in play in the modes with one
+    pub fn top_paddle(&self) -> &GameObject {
+        &self.top_paddle
+    }
+
+    // Put another ball in play, for scripts and benchmarks that need a crowded field. It's held to the speed limit
+    pub fn spawn_ball(&mut self, x: f32, y: f32, vel_x: f32, vel_y: f32) {
+        let size = self.config.ball_size;
+        let mut ball = GameObject { x, y, width: size, height: size, vel_x, vel_y };
+        physics::limit_speed(&mut ball, self.config.ball_max_speed);
+        self.balls.push(ball);
+    }
+
+    // Put something in the playfield, for the game's own systems, scripts and frontends alike.
+    // The entity it returns stands for it until it's despawned, and never for anything spawned after
+    pub fn spawn(&mut self, spawn: Spawn) -> Entity {
+        match spawn {
+            Spawn::Block { body, colour, path: Some(path) } => ecs::spawn_moving_block(&mut self.world, &body, colour, path),
+            Spawn::Block { body, colour, path: None } => ecs::spawn_block(&mut self.world, &body, colour),
+            Spawn::PowerUp { kind, x } => powerups::spawn_pickup(&mut self.world, kind, x),
+            Spawn::Coin { x, y } => powerups::spawn_coin(&mut self.world, x, y),
+            Spawn::Bolt { x, y } => lasers::spawn_bolt(&mut self.world, x, y),
+        }
+    }
+
+    // Take something out of the playfield once the physics step is over, so nothing going through
+    // the world partway sees it vanish. Despawning what's already gone does nothing
+    pub fn despawn(&mut self, entity: Entity) {
+        self.world.despawn_later(entity);
+    }
+
+    // Add a game mode with its own rules to the title screen, for frontends with modes of their own
+    pub fn register_mode(&mut self, rules: Box<dyn Rules>) -> GameMode {
+        let mode = self.modes.register(rules);
+        self.menu.add_mode(mode);
+        mode
+    }
+
+    fn rules(&self) -> &dyn Rules {
+        self.modes.get(self.mode)
+    }
+
+    pub fn start_game(&mut self, mode: GameMode) {
+        // Every game gets its own seed and recording, a replay brings its seed with it
+        let seed = match &self.playback {
+            Some(replay) => replay.seed,
+            None => self.seeds.next_u64(),
+        };
+        self.rng = Rng::new(seed);
+        self.recording = Replay::new(seed, mode);
+        self.playback_frame = 0;
+
+        // Reset everything a previous game may have changed
+        self.mode = mode;
+        self.demo = false;
+        self.tutorial = None;
+        self.scoreboard = Scoreboard {
+            lives: self.preset().lives(self.config.lives),
+            level: 1,
+            ..Scoreboard::default()
+        };
+        self.serve_dir = 1.0;
+        self.ball_speed = self.base_ball_speed();
+        self.paddle_hits = 0;
+        self.rally = 0;
+        self.play_time = 0.0;
+        self.paddle_moved = false;
+        self.combo = 0;
+        self.particles.clear();
+        self.popups.clear();
+        self.is_paused = false;
+        self.pause_start = None;
+        self.paddle.width = self.base_paddle_width();
+        // The CPU's paddle stays the same whatever the difficulty, two players share it
+        self.top_paddle.width = if mode == GameMode::TwoPlayer { self.base_paddle_width() } else { self.config.paddle_width };
+        self.paddle.x = (self.config.window_width as f32 / 2.0) - self.paddle.width / 2.0;
+        self.top_paddle.x = (self.config.window_width as f32 / 2.0) - self.top_paddle.width / 2.0;
+        self.paddle.vel_x = 0.0;
+        self.top_paddle.vel_x = 0.0;
+        self.balls = vec![GameObject {
+            x: 20.0,
+            y: 60.0,
+            width: self.config.ball_size,
+            height: self.config.ball_size,
+            vel_x: self.ball_speed,
+            vel_y: self.ball_speed,
+        }];
+        self.ball_kinds.clear();
+        self.lasers.clear();
+        self.held_ball = None;
+        self.world.clear();
+        self.effects.clear();
+        self.collision_points.clear();
+        self.spawner = Spawner::new();
+        self.bricks.clear();
+        self.boss = None;
+        self.background = None;
+        self.tiles = None;
+        self.forces = (0.0, 0.0);
+        let has_bricks = self.rules().has_bricks();
+        if let Some(arena) = self.arena.as_ref().filter(|_| !has_bricks) {
+            spawn_obstacles(&mut self.world, &arena.obstacles);
+            self.background = arena.background;
+            self.tiles = arena.tiles.clone();
+            self.forces = (arena.gravity, arena.wind);
+        }
+        if has_bricks {
+            let from_campaign = match self.campaign.as_mut() {
+                Some(campaign) => {
+                    campaign.restart();
+                    true
+                }
+                None => false,
+            };
+            self.load_level(from_campaign);
+            // Serve from the middle, the usual start position is inside the bricks
+            self.reset_ball();
+        }
+        self.ball_reset_pending = true;
+        self.save_positions();
+        // A replay starts with the power-ups its game did
+        for kind in self.playback.as_ref().map(|replay| replay.power_ups.clone()).unwrap_or_default() {
+            self.show_hud_message(self.locale.get(kind.name_key()).to_string());
+            self.apply_powerup(kind);
+        }
+        #[cfg(feature = "lua")]
+        self.run_script(scripting::Hook::LevelStart { level: self.scoreboard.level });
+        self.switch_scene(Rc::new(PlayScene));
+        self.audio.start_music();
+    }
+
+    // Left alone on the title screen the CPU plays a 1 player game, like an arcade cabinet waiting for players
+    fn start_demo(&mut self) {
+        self.playback = None;
+        self.start_game(GameMode::Solo);
+        self.demo = true;
+        self.audio.stop_music();
+    }
+
+    // A 1 player game on an empty field, with the ball waiting in the middle until the player has learnt to move
+    fn start_tutorial(&mut self) {
+        self.playback = None;
+        self.start_game(GameMode::Solo);
+        self.tutorial = Some(Tutorial::new());
+        self.world.clear();
+        self.background = None;
+        self.tiles = None;
+        self.forces = (0.0, 0.0);
+        self.reset_ball();
+        self.balls[0].vel_x = 0.0;
+        self.balls[0].vel_y = 0.0;
+        self.switch_scene(Rc::new(TutorialScene));
+    }
+
+    // Enter leaves the tutorial once it has been done, until then it plays like any other game
+    fn update_tutorial(&mut self) {
+        let done = self.tutorial.as_ref().is_some_and(|tutorial| tutorial.step() == tutorial::Step::Done);
+        if done && self.keys.just_pressed(Key::Enter) {
+            self.tutorial = None;
+            self.switch_scene(Rc::new(MenuScene));
+            self.audio.stop_music();
+            return;
+        }
+        self.process_input();
+    }
+
+    // Move the tutorial on if the player did what it was waiting for
+    fn advance_tutorial(&mut self, action: tutorial::Action) {
+        let Some(tutorial) = self.tutorial.as_mut() else {
+            return;
+        };
+        if !tutorial.perform(action) {
+            return;
+        }
+        match tutorial.step() {
+            tutorial::Step::Serve => self.start_serve_pause(),
+            tutorial::Step::Done => self.audio.play(Sound::LevelCleared),
+            _ => {}
+        }
+    }
+
+    fn end_demo(&mut self) {
+        self.demo = false;
+        self.switch_scene(Rc::new(MenuScene));
+        self.last_key_press = self.clock.now();
+    }
+
+    fn finish_game(&mut self) {
+        // A demo goes back to the title screen, ready to start another one later
+        if self.demo {
+            self.end_demo();
+            return;
+        }
+        self.switch_scene(Rc::new(GameOverScene));
+        self.audio.play(Sound::GameOver);
+        self.audio.fade_out_music();
+        if self.playback.is_none() && !self.is_net_client() {
+            self.save_recording();
+        }
+
+        match self.mode {
+            GameMode::Solo => {
+                println!("Game Over! Lives remaining: {}", self.scoreboard.lives);
+                println!("Final Score: {}", self.scoreboard.score);
+            }
+            GameMode::Survival => println!("Game Over! Survived {} seconds", self.scoreboard.score),
+            GameMode::Breakout => {
+                println!("Game Over! Reached level {}", self.scoreboard.level);
+                println!("Final Score: {}", self.scoreboard.score);
+            }
+            GameMode::TwoPlayer | GameMode::VsAi => {
+                println!("Game Over! Player 1: {} {}: {}", self.scoreboard.p1_score, self.p2_name(), self.scoreboard.p2_score);
+                println!("{}", self.winner_text());
+            }
+            GameMode::Plugin(_) => println!("Game Over! {}", self.summary()),
+        }
+        // Only players at the keyboard can enter their initials
+        let qualifies = self.score_table().is_some_and(|table| table.qualifies(self.scoreboard.score));
+        if self.display.is_some() && self.playback.is_none() && qualifies {
+            println!("New high score!");
+            self.initials = Some(String::new());
+        }
+        // The global top 10 for the game over screen
+        if let (Some(board), true) = (self.board_name(), self.counts_for_player()) {
+            if let Some(leaderboard) = self.leaderboard.as_mut() {
+                leaderboard.fetch(board);
+            }
+        }
+        if self.mode == GameMode::VsAi && self.scoreboard.p1_score > self.scoreboard.p2_score {
+            self.record_event(achievements::Event::Won { paddle_moved: self.paddle_moved });
+        }
+    }
+
+    // The high score table for this mode, the modes without one are played for the win or the level reached
+    fn score_table(&self) -> Option<&HighScores> {
+        match self.mode {
+            GameMode::Solo => Some(&self.high_scores),
+            GameMode::Survival => Some(&self.survival_scores),
+            GameMode::TwoPlayer | GameMode::VsAi | GameMode::Breakout | GameMode::Plugin(_) => None,
+        }
+    }
+
+    // What the online leaderboard calls this mode's table
+    fn board_name(&self) -> Option<&'static str> {
+        match self.mode {
+            GameMode::Solo => Some("solo"),
+            GameMode::Survival => Some("survival"),
+            GameMode::TwoPlayer | GameMode::VsAi | GameMode::Breakout | GameMode::Plugin(_) => None,
+        }
+    }
+
+    fn score_table_mut(&mut self) -> Option<&mut HighScores> {
+        match self.mode {
+            GameMode::Solo => Some(&mut self.high_scores),
+            GameMode::Survival => Some(&mut self.survival_scores),
+            GameMode::TwoPlayer | GameMode::VsAi | GameMode::Breakout | GameMode::Plugin(_) => None,
+        }
+    }
+
+    // Only games played by someone at the keyboard count towards achievements and stats, the tutorial doesn't
+    fn counts_for_player(&self) -> bool {
+        self.display.is_some() && self.playback.is_none() && !self.demo && self.tutorial.is_none()
+    }
+
+    fn record_event(&mut self, event: achievements::Event) {
+        if !self.counts_for_player() {
+            return;
+        }
+        for achievement in self.achievements.record(event) {
+            println!("Achievement unlocked: {}", self.locale.get(achievement.name_key()));
+            if self.toasts.is_empty() {
+                self.toast_since = self.clock.now();
+            }
+            self.toasts.push_back(achievement);
+        }
+    }
+
+    // Scoring enough points in a game with lives unlocks paddle skins
+    fn record_skin_score(&mut self) {
+        if !self.counts_for_player() || !self.rules().has_lives() {
+            return;
+        }
+        for skin in self.profile.record_score(self.scoreboard.score) {
+            let name = self.locale.name("skin", skin.name);
+            println!("Skin unlocked: {}", name);
+            self.show_hud_message(self.locale.format("hud.skin_unlocked", &[&name]));
+        }
+    }
+
+    // Take down the toast on screen once it has been up long enough, putting up the next
+    fn update_toasts(&mut self) {
+        if !self.toasts.is_empty() && self.since(self.toast_since) >= TOAST_TIME {
+            self.toasts.pop_front();
+            self.toast_since = self.clock.now();
+        }
+    }
+
+    // Keep the last game's replay next to the high scores, or in the working directory without a data directory
+    fn save_recording(&self) {
+        let path = highscores::data_dir().map_or(PathBuf::from(replay::REPLAY_FILE), |dir| dir.join(replay::REPLAY_FILE));
+        match self.recording.save(&path) {
+            Ok(()) => println!("Replay saved to {}", path.display()),
+            Err(e) => eprintln!("Error saving replay {}: {}", path.display(), e),
+        }
+    }
+
+    fn p2_name(&self) -> &'static str {
+        if self.mode == GameMode::VsAi {
+            "CPU"
+        } else {
+            "PLAYER 2"
+        }
+    }
+
+    fn winner_text(&self) -> &str {
+        let key = if self.scoreboard.p1_score > self.scoreboard.p2_score {
+            "game_over.player_1_wins"
+        } else if self.mode == GameMode::VsAi {
+            "game_over.cpu_wins"
+        } else {
+            "game_over.player_2_wins"
+        };
+        self.locale.get(key)
+    }
+
+    fn update_menu(&mut self) {
+        if self.keys.just_pressed(self.config.keys.quit) {
+            self.game_is_running = false;
+            return;
+        }
+
+        self.handle_theme_key();
+
+        if self.keys.pressed_or_repeated(Key::Up) {
+            self.menu.move_up();
+        }
+        if self.keys.pressed_or_repeated(Key::Down) {
+            self.menu.move_down();
+        }
+        if self.keys.just_pressed(Key::Enter) {
+            match self.menu.selected_item() {
+                MenuItem::Play(mode) => {
+                    self.playback = None;
+                    self.start_game(mode);
+                    self.use_stocked_power_ups();
+                }
+                MenuItem::Tutorial => self.start_tutorial(),
+                MenuItem::Difficulty => self.difficulty = (self.difficulty + 1) % difficulty::PRESETS.len(),
+                MenuItem::Theme => self.next_theme(),
+                MenuItem::Skin => {
+                    self.profile.next_skin();
+                    let size = (self.config.paddle_width as usize, self.config.paddle_height as usize);
+                    self.skin_texture = self.profile.selected().texture(size.0, size.1);
+                }
+                MenuItem::Speed => {
+                    // On to the next slower speed, back to full speed after the slowest
+                    let slower = TIME_SCALES.into_iter().find(|&scale| scale < self.time_scale);
+                    self.time_scale = slower.unwrap_or(TIME_SCALES[0]);
+                }
+                MenuItem::Controls => self.push_scene(Rc::new(ControlsScene)),
+                MenuItem::Stats => self.push_scene(Rc::new(StatsScene)),
+                MenuItem::Shop => self.push_scene(Rc::new(ShopScene)),
+                MenuItem::Quit => self.game_is_running = false,
+            }
+        }
+
+        if self.display.is_some() && self.since(self.last_key_press) >= DEMO_IDLE_TIME {
+            self.start_demo();
+        }
+    }
+
+    fn update_shop(&mut self) {
+        if self.keys.just_pressed(self.config.keys.quit) {
+            self.pop_scene();
+            return;
+        }
+        self.handle_theme_key();
+        if self.keys.pressed_or_repeated(Key::Up) {
+            self.shop.move_up();
+        }
+        if self.keys.pressed_or_repeated(Key::Down) {
+            self.shop.move_down();
+        }
+        if self.keys.just_pressed(Key::Enter) {
+            let item = self.shop.selected_item();
+            let bought = match item {
+                shop::Item::Skin(skin) => self.profile.buy_skin(skin),
+                shop::Item::PowerUp(kind) => self.profile.buy_power_up(kind, item.price()),
+            };
+            if bought {
+                self.audio.play(Sound::LevelCleared);
+            }
+        }
+    }
+
+    // The controls and stats screens, which only go back to the title screen
+    fn update_info_screen(&mut self) {
+        if self.keys.just_pressed(self.config.keys.quit) || self.keys.just_pressed(Key::Enter) {
+            self.pop_scene();
+            return;
+        }
+        self.handle_theme_key();
+    }
+
+    fn pause(&mut self) {
+        self.push_scene(Rc::new(PauseScene));
+        self.paused_at = Some(self.clock.now());
+    }
+
+    fn resume(&mut self) {
+        self.pop_scene();
+        self.last_frame_time = self.clock.now(); // Don't count the time spent paused
+
+        // Push back the serve countdown so the time spent paused doesn't eat into it
+        if let (Some(start), Some(paused_at)) = (self.pause_start, self.paused_at.take()) {
+            self.pause_start = Some(start + self.since(paused_at));
+        }
+    }
+
+    fn handle_volume_keys(&mut self) {
+        let mut change = 0.0;
+        if self.keys.pressed_or_repeated(Key::Equal) || self.keys.pressed_or_repeated(Key::NumPadPlus) {
+            change += audio::VOLUME_STEP;
+        }
+        if self.keys.pressed_or_repeated(Key::Minus) || self.keys.pressed_or_repeated(Key::NumPadMinus) {
+            change -= audio::VOLUME_STEP;
+        }
+        if change != 0.0 {
+            self.audio.change_music_volume(change);
+            let volume = (self.audio.music_volume * 100.0).round();
+            self.show_hud_message(self.locale.format("hud.music", &[&volume]));
+        }
+    }
+
+    // T cycles through the colour themes
+    fn handle_theme_key(&mut self) {
+        if self.keys.just_pressed(Key::T) {
+            self.next_theme();
+            let theme = self.locale.name("theme", self.palette().name);
+            self.show_hud_message(self.locale.format("hud.theme", &[&theme]));
+        }
+    }
+
+    fn next_theme(&mut self) {
+        self.theme = (self.theme + 1) % palette::THEMES.len();
+    }
+
+    fn palette(&self) -> &'static Palette {
+        &palette::THEMES[self.theme]
+    }
+
+    fn preset(&self) -> &'static Preset {
+        &difficulty::PRESETS[self.difficulty]
+    }
+
+    // The config's serve speed and paddle width as the difficulty has them
+    fn base_ball_speed(&self) -> f32 {
+        self.config.ball_speed * self.preset().ball_speed
+    }
+
+    fn base_paddle_width(&self) -> f32 {
+        (self.config.paddle_width * self.preset().paddle_width).min(self.config.window_width as f32)
+    }
+
+    fn show_hud_message(&mut self, message: String) {
+        self.hud_message = Some((message, self.clock.now()));
+    }
+
+    fn update_paused(&mut self) {
+        // Pause or enter resumes, quit abandons the game and goes back to the title screen
+        if self.keys.just_pressed(self.config.keys.pause) || self.keys.just_pressed(Key::Enter) {
+            self.resume();
+        } else if self.keys.just_pressed(self.config.keys.quit) {
+            self.switch_scene(Rc::new(MenuScene));
+            self.audio.stop_music();
+        }
+        self.handle_volume_keys();
+        self.handle_theme_key();
+    }
+
+    fn update_game_over(&mut self) {
+        if self.initials.is_some() {
+            self.update_initials();
+            return;
+        }
+
+        if self.keys.just_pressed(Key::Enter) {
+            self.switch_scene(Rc::new(MenuScene));
+        } else if self.keys.just_pressed(self.config.keys.quit) {
+            self.game_is_running = false;
+        }
+    }
+
+    fn update_initials(&mut self) {
+        let mut initials = self.initials.take().unwrap_or_default();
+
+        for key in self.keys.just_pressed_keys() {
+            match key {
+                Key::Backspace => {
+                    initials.pop();
+                }
+                Key::Enter if !initials.is_empty() => {
+                    let score = self.scoreboard.score;
+                    if let Some(table) = self.score_table_mut() {
+                        table.insert(&initials, score);
+                        if let Err(e) = table.save() {
+                            eprintln!("Could not save high scores: {}", e);
+                        }
+                    }
+                    if let (Some(board), Some(leaderboard)) = (self.board_name(), self.leaderboard.as_mut()) {
+                        leaderboard.submit(board, &initials, score);
+                    }
+                    return;
+                }
+                _ => {
+                    if let Some(letter) = key_letter(key) {
+                        if initials.len() < INITIALS_LENGTH {
+                            initials.push(letter);
+                        }
+                    }
+                }
+            }
+        }
+        self.initials = Some(initials);
+    }
+
+    // Where the keys and mouse are read from, None when nobody is playing
+    fn input(&self) -> Option<&dyn InputSource> {
+        match self.input_source.as_deref() {
+            Some(input) => Some(input),
+            None => self.display.as_deref().map(|display| display as &dyn InputSource),
+        }
+    }
+
+    // The mouse on the playfield. A display gives it in pixels of its frame, which a lower render resolution
+    // makes smaller than the playfield
+    fn mouse_position(&self) -> Option<(f32, f32)> {
+        if let Some(input) = &self.input_source {
+            return input.mouse_position();
+        }
+        let (x, y) = self.display.as_ref()?.mouse_position()?;
+        let (frame_width, frame_height) = self.config.render_size();
+        let scale_x = self.config.window_width as f32 / frame_width as f32;
+        let scale_y = self.config.window_height as f32 / frame_height as f32;
+        Some((x * scale_x, y * scale_y))
+    }
+
+    fn process_input(&mut self) {
+        // Any key ends a demo game
+        if self.demo && !self.keys.just_pressed_keys().is_empty() {
+            self.end_demo();
+            return;
+        }
+        if self.input().is_none() || self.demo {
+            // A replay supplies the paddle movement itself in update()
+            if self.playback.is_none() {
+                self.autopilot_input();
+            }
+            return;
+        }
+
+        // Quitting goes via the pause screen so a stray key press can't throw a game away
+        let keys = &self.config.keys;
+        if self.keys.just_pressed(keys.pause) || self.keys.just_pressed(keys.quit) {
+            self.pause();
+            return;
+        }
+
+        // While watching a replay only pausing and quitting are up to the viewer
+        if self.playback.is_some() {
+            return;
+        }
+
+        // Serve straight away instead of waiting out the pause after a lost point
+        let can_serve = self.tutorial.as_ref().is_none_or(Tutorial::can_serve);
+        if self.is_paused && can_serve && self.keys.just_pressed(keys.serve) {
+            self.serve_requested = true;
+        }
+        self.firing = self.keys.is_down(keys.serve);
+        self.handle_volume_keys();
+        self.handle_theme_key();
+
+        self.gamepads.poll();
+
+        // F1 toggles the debug readout
+        if self.keys.just_pressed(Key::F1) {
+            self.show_debug = !self.show_debug;
+        }
+
+        // M switches the bottom paddle between mouse and keyboard/gamepad control
+        if self.keys.just_pressed(Key::M) {
+            self.mouse_control = !self.mouse_control;
+            let message = if self.mouse_control { "hud.mouse_on" } else { "hud.mouse_off" };
+            self.show_hud_message(self.locale.get(message).to_string());
+        }
+
+        // Handle paddle movement input, the first gamepad drives the bottom paddle
+        if !self.is_paused {
+            // A network client leaves the bottom paddle to the host
+            let owns_bottom_paddle = !self.is_net_client();
+            if owns_bottom_paddle && self.mouse_control {
+                // The paddle is moved to the cursor in update() once the frame time is known
+                let field_width = self.config.window_width as f32;
+                self.mouse_x = self.mouse_position().map(|(x, _)| x.clamp(0.0, field_width));
+            } else if owns_bottom_paddle {
+                self.paddle.vel_x = self.paddle_velocity(self.config.keys.move_left, self.config.keys.move_right, 0);
+            }
+
+            // Player 2 moves the top paddle with their own keys or the second gamepad,
+            // in a network game it's the player on the other machine
+            if self.mode == GameMode::TwoPlayer {
+                self.top_paddle.vel_x = match self.net.as_ref().map(|net| net.role) {
+                    Some(Role::Host) => self.remote_paddle_vel,
+                    Some(Role::Client) => self.paddle_velocity(self.config.keys.move_left, self.config.keys.move_right, 0),
+                    None => self.paddle_velocity(self.config.keys.p2_move_left, self.config.keys.p2_move_right, 1),
+                };
+            }
+        }
+    }
+
+    // Headless and demo games have nobody at the keyboard, so the CPU plays the human paddles too
+    fn autopilot_input(&mut self) {
+        let field_width = self.config.window_width as f32;
+        // The AI watches for balls coming up the screen, so show it the nearest ball flipped over
+        let target = self
+            .balls
+            .iter()
+            .filter(|ball| ball.vel_y > 0.0)
+            .max_by(|a, b| a.y.total_cmp(&b.y))
+            .or(self.balls.first());
+        if let Some(ball) = target {
+            let mut mirrored = ball.clone();
+            mirrored.vel_y = -ball.vel_y;
+            self.paddle.vel_x = self.autopilot.update(&self.paddle, &mirrored, field_width, HEADLESS_FRAME_TIME);
+            // The CPU isn't fooled by inverted controls, it pushes the other way to begin with
+            if powerups::is_active(&self.effects, PowerUpKind::InvertControls) {
+                self.paddle.vel_x = -self.paddle.vel_x;
+            }
+        }
+        // and keeps tapping the serve key, firing any laser and launching a ball off the magnet
+        self.firing = !self.firing;
+        if self.mode == GameMode::TwoPlayer {
+            // Player 2 gets the same CPU as the VS CPU mode
+            if let Some(ball) = self.balls.first() {
+                self.top_paddle.vel_x = self.ai.update(&self.top_paddle, ball, field_width, HEADLESS_FRAME_TIME);
+            }
+        }
+    }
+
+    // Keyboard movement takes priority, otherwise the gamepad's stick or d-pad sets the speed
+    fn paddle_velocity(&self, left: Key, right: Key, pad: usize) -> f32 {
+        if self.keys.is_down(left) {
+            -self.config.paddle_speed
+        } else if self.keys.is_down(right) {
+            self.config.paddle_speed
+        } else {
+            self.gamepads.horizontal(pad).unwrap_or(0.0) * self.config.paddle_speed
+        }
+    }
+
+    // This frame's input, from the replay being watched or read live and recorded
+    fn next_input(&mut self) -> Option<FrameInput> {
+        if let Some(replay) = &self.playback {
+            let input = replay.frames.get(self.playback_frame).copied();
+            self.playback_frame += 1;
+            return input;
+        }
+
+        // Delta time since the last frame, the simulation catches up with it in fixed steps
+        let current_time = self.clock.now();
+        let frame_time = match self.display {
+            Some(_) => (current_time - self.last_frame_time).as_secs_f32().min(MAX_FRAME_TIME),
+            None => HEADLESS_FRAME_TIME,
+        };
+        // Slowed down by shortening each frame, which the recording keeps so replays play out the same.
+        // A network game runs at full speed for both players
+        let frame_time = if self.net.is_some() { frame_time } else { frame_time * self.time_scale };
+        self.last_frame_time = current_time;
+
+        // Games with nobody playing serve straight away, there is nobody to watch the pause.
+        // The tutorial waits for the player to serve
+        let pause_over = self.tutorial.is_none() && self.pause_start.is_some_and(|start| self.since(start) >= SERVE_COUNTDOWN);
+        let serve = self.is_paused && (pause_over || self.serve_requested || self.input().is_none());
+
+        let input = FrameInput {
+            frame_time,
+            paddle_vel: self.paddle.vel_x,
+            top_paddle_vel: self.top_paddle.vel_x,
+            mouse_x: self.mouse_x.take(),
+            serve,
+            fire: self.firing,
+        };
+        self.recording.frames.push(input);
+        Some(input)
+    }
+
+    fn update(&mut self) {
+        // The host runs the game for both machines, the client just shows what it is sent
+        // and guesses where things have got to in between
+        if self.is_net_client() {
+            self.predict_client();
+            return;
+        }
+
+        let input = match self.next_input() {
+            Some(input) => input,
+            None => {
+                // A replay that runs out early was recorded with different settings
+                eprintln!("Replay ended before the game did");
+                self.finish_game();
+                return;
+            }
+        };
+        self.paddle.vel_x = input.paddle_vel;
+        if self.mode == GameMode::TwoPlayer {
+            self.top_paddle.vel_x = input.top_paddle_vel;
+        }
+
+        self.update_shake(input.frame_time);
+        self.particles.update(input.frame_time);
+        self.popups.update(input.frame_time);
+
+        // Handle pause state
+        if self.is_paused && self.pause_start.is_some() {
+            if !input.serve {
+                return;
+            }
+            self.is_paused = false;
+            self.pause_start = None;
+            self.serve_requested = false;
+            self.ball_reset_pending = true;
+            // Balls a script added while waiting go with the serve
+            let extra = self.balls.split_off(self.balls.len().min(1));
+            self.reset_ball();
+            self.balls.extend(extra);
+            self.advance_tutorial(tutorial::Action::Served);
+        }
+
+        // Ensure ball reset is handled before updating positions
+        if self.ball_reset_pending {
+            self.ball_reset_pending = false;
+            self.accumulator = 0.0;
+            return;
+        }
+        let frame_time = input.frame_time;
+        self.debug_frame_time = frame_time;
+
+        self.firing = input.fire;
+
+        // Inverted controls send the paddle the other way, and follow the mouse mirrored across the field
+        let inverted = powerups::is_active(&self.effects, PowerUpKind::InvertControls);
+        if inverted {
+            self.paddle.vel_x = -self.paddle.vel_x;
+        }
+
+        // Centre the paddle on the mouse over the course of this frame, as a velocity so the paddle's speed is still known
+        if let Some(mouse_x) = input.mouse_x {
+            let mouse_x = if inverted { self.config.window_width as f32 - mouse_x } else { mouse_x };
+            let target_x = mouse_x - self.paddle.width / 2.0;
+            self.paddle.vel_x = if frame_time > 0.0 { (target_x - self.paddle.x) / frame_time } else { 0.0 };
+        }
+
+        self.accumulator += frame_time;
+        while self.accumulator >= PHYSICS_STEP {
+            self.save_positions();
+            self.accumulator -= PHYSICS_STEP;
+            self.step_physics(PHYSICS_STEP);
+            self.handle_events();
+            self.cleanup();
+
+            // A lost point, cleared level or finished game stops the simulation until the next serve
+            if self.state() != GameState::Playing || self.is_paused {
+                self.accumulator = 0.0;
+                break;
+            }
+        }
+        self.interpolation = self.accumulator / PHYSICS_STEP;
+    }
+
+    fn is_net_client(&self) -> bool {
+        self.net.as_ref().is_some_and(|net| net.role == Role::Client)
+    }
+
+    // Swap paddle input and game state with the other machine, once a frame
+    fn sync_network(&mut self) {
+        // Leaving for the title screen ends the network game
+        if self.state() == GameState::Menu {
+            self.net = None;
+            return;
+        }
+        let role = match &self.net {
+            Some(net) => net.role,
+            None => return,
+        };
+
+        let outgoing = match role {
+            Role::Host => Message::State(self.snapshot()),
+            Role::Client => Message::Input {
+                paddle_vel: self.top_paddle.vel_x,
+            },
+        };
+        let sent = self.net.as_mut().and_then(|net| net.send(&outgoing));
+        if let (Some(seq), Role::Client) = (sent, role) {
+            // Remember the input until the host confirms it, see apply_snapshot()
+            self.pending_inputs.push_back((seq, self.top_paddle.vel_x, self.client_frame_time));
+            if self.pending_inputs.len() > MAX_PENDING_INPUTS {
+                self.pending_inputs.pop_front();
+            }
+        }
+        let received = sent.and(self.net.as_mut().and_then(Connection::receive));
+        let messages = match received {
+            Some(messages) => messages,
+            None => {
+                println!("The other player has disconnected");
+                self.net = None;
+                if matches!(self.state(), GameState::Playing | GameState::Paused) {
+                    self.switch_scene(Rc::new(MenuScene));
+                    self.audio.stop_music();
+                }
+                return;
+            }
+        };
+
+        for (seq, message) in messages {
+            match message {
+                Message::Input { paddle_vel } => {
+                    self.remote_paddle_vel = paddle_vel;
+                    self.remote_input_seq = seq;
+                }
+                Message::State(state) => self.apply_snapshot(state),
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            input_seq: self.remote_input_seq,
+            paddle_x: self.paddle.x,
+            paddle_vel: self.paddle.vel_x,
+            top_paddle_x: self.top_paddle.x,
+            p1_score: self.scoreboard.p1_score,
+            p2_score: self.scoreboard.p2_score,
+            game_over: self.state() == GameState::GameOver,
+            balls: self.balls.iter().map(|ball| [ball.x, ball.y, ball.vel_x, ball.vel_y]).collect(),
+        }
+    }
+
+    fn apply_snapshot(&mut self, state: Snapshot) {
+        let field_width = self.config.window_width as f32;
+
+        // Our own paddle: start from where the host had it and replay the inputs it hadn't seen yet
+        self.top_paddle.x = state.top_paddle_x;
+        self.pending_inputs.retain(|&(seq, _, _)| seq > state.input_seq);
+        for &(_, vel, frame_time) in &self.pending_inputs {
+            self.top_paddle.x += vel * frame_time;
+            physics::clamp_paddle(&mut self.top_paddle, field_width);
+        }
+
+        // The other player's paddle: ease small corrections in so it doesn't jitter, jump for big ones
+        let error = state.paddle_x - self.paddle.x;
+        if error.abs() < REMOTE_SNAP_DISTANCE {
+            self.paddle.x += error * REMOTE_BLEND;
+        } else {
+            self.paddle.x = state.paddle_x;
+        }
+        self.paddle.vel_x = state.paddle_vel;
+
+        self.scoreboard.p1_score = state.p1_score;
+        self.scoreboard.p2_score = state.p2_score;
+        let size = self.config.ball_size;
+        self.balls = state
+            .balls
+            .iter()
+            .map(|&[x, y, vel_x, vel_y]| GameObject {
+                x,
+                y,
+                width: size,
+                height: size,
+                vel_x,
+                vel_y,
+            })
+            .collect();
+        if state.game_over && matches!(self.state(), GameState::Playing | GameState::Paused) {
+            self.finish_game();
+        }
+    }
+
+    // Between snapshots a network client moves its own paddle straight away and carries
+    // the ball and the other paddle on at their last known speed
+    fn predict_client(&mut self) {
+        let current_time = self.clock.now();
+        let frame_time = (current_time - self.last_frame_time).as_secs_f32().min(MAX_FRAME_TIME);
+        self.last_frame_time = current_time;
+        self.client_frame_time = frame_time;
+
+        let field_width = self.config.window_width as f32;
+        self.top_paddle.x += self.top_paddle.vel_x * frame_time;
+        self.paddle.x += self.paddle.vel_x * frame_time;
+        physics::clamp_paddle(&mut self.top_paddle, field_width);
+        physics::clamp_paddle(&mut self.paddle, field_width);
+        for ball in self.balls.iter_mut() {
+            ball.x += ball.vel_x * frame_time;
+            ball.y += ball.vel_y * frame_time;
+        }
+
+        // Draw the predicted positions as they are
+        self.save_positions();
+        self.interpolation = 0.0;
+    }
+
+    // Remember where things are before a step so render() can draw between the two
+    fn save_positions(&mut self) {
+        self.prev_ball_positions = self.balls.iter().map(|ball| (ball.x, ball.y)).collect();
+        self.prev_paddle_x = self.paddle.x;
+        self.prev_top_paddle_x = self.top_paddle.x;
+
+        // Balls come and go with multiball, a ball that is new to the list starts without a trail
+        self.trails.resize_with(self.balls.len(), VecDeque::new);
+        for (trail, ball) in self.trails.iter_mut().zip(&self.balls) {
+            trail.push_back((ball.x, ball.y));
+            while trail.len() > self.config.ball_trail_length {
+                trail.pop_front();
+            }
+        }
+    }
+
+    // Advance the simulation by one fixed step
+    fn step_physics(&mut self, delta_time: f32) {
+        let rules = self.modes.get(self.mode);
+        let (has_top_paddle, has_bricks, return_points) = (rules.has_top_paddle(), rules.has_bricks(), rules.return_points());
+
+        // Let the computer steer the top paddle, watching the ball that is closest to it
+        if has_top_paddle && self.mode != GameMode::TwoPlayer {
+            let target = self
+                .balls
+                .iter()
+                .filter(|ball| ball.vel_y < 0.0)
+                .min_by(|a, b| a.y.total_cmp(&b.y))
+                .or(self.balls.first());
+            if let Some(ball) = target {
+                self.top_paddle.vel_x = self.ai.update(&self.top_paddle, ball, self.config.window_width as f32, delta_time);
+            }
+        }
+
+        self.update_effects(delta_time);
+
+        // Slow and fast ball stretch or squeeze time for the balls only, catching both cancels out some of each
+        let mut ball_time = delta_time;
+        if powerups::is_active(&self.effects, PowerUpKind::SlowBall) {
+            ball_time *= SLOW_BALL_FACTOR;
+        }
+        if powerups::is_active(&self.effects, PowerUpKind::FastBall) {
+            ball_time *= FAST_BALL_FACTOR;
+        }
+
+        // Update paddle positions, the balls move below in sub-steps
+        self.paddle.x += self.paddle.vel_x * delta_time;
+        self.top_paddle.x += self.top_paddle.vel_x * delta_time;
+
+        // Prevent paddles from moving out of window boundaries
+        physics::clamp_paddle(&mut self.paddle, self.config.window_width as f32);
+        physics::clamp_paddle(&mut self.top_paddle, self.config.window_width as f32);
+
+        self.paddle_moved |= self.paddle.vel_x != 0.0;
+        if self.paddle.vel_x != 0.0 {
+            self.advance_tutorial(tutorial::Action::Moved);
+        }
+        self.play_time += delta_time;
+        self.session_time += delta_time;
+        self.record_event(achievements::Event::Survived { seconds: self.play_time });
+        self.record_skin_score();
+        if self.counts_for_player() {
+            self.stats.add_play_time(delta_time);
+        }
+
+        // Moving obstacles go first, so the balls bounce off them where they are now, then falling pickups and bolts
+        ecs::follow_routes(&mut self.world, delta_time);
+        ecs::movement(&mut self.world, delta_time);
+        ecs::index(&self.world, &mut self.world_grid);
+        bricks::index(&mut self.brick_grid, &self.bricks);
+
+        let field = Field {
+            scoreboard: &mut self.scoreboard,
+            balls: &mut self.balls,
+            play_time: self.play_time,
+            max_speed: self.config.ball_max_speed,
+        };
+        self.modes.get(self.mode).update(field, delta_time);
+
+        let field_width = self.config.window_width as f32;
+        if let Some(boss) = self.boss.as_mut() {
+            boss.update(delta_time, field_width);
+        }
+        let mut paddle_hits = 0;
+        let mut bottom_hits = 0;
+        let mut hardest_hit: f32 = 0.0; // Speed of the fastest ball off a paddle this step
+        let mut sharpest_return: f32 = 0.0; // Furthest off straight up a ball left the bottom paddle this step, radians
+        let mut brick_points = 0;
+        let mut brick_hit_at = None; // Where the ball was as it hit the last brick this step
+        let mut boss_beaten = false;
+        let mut bounces = Vec::new(); // Off bricks and the boss, the other bounces are events
+        let mut sounds = Vec::new(); // Each with where the ball was, played once the balls have moved
+        let mut scored = Vec::new(); // Where points were won this step and how many, before the multiplier
+        let mut broken = Vec::new(); // Where bricks broke this step
+        let mut splits = Vec::new(); // Split balls that came off a paddle this step
+        let shield = self.shield_wall();
+        let mut shielded = Vec::new(); // Where balls bounced off the shield this step
+        let shield_filter = Filter::on(Layers::SHIELD);
+
+        self.update_held_ball();
+        let magnet = powerups::is_active(&self.effects, PowerUpKind::Magnet) && self.held_ball.is_none();
+        let mut caught = None; // A ball the magnet caught this step
+
+        // The laser fires from where the paddle is now, and its bolts hit things before the balls do
+        self.lasers.update(delta_time);
+        let laser = powerups::is_active(&self.effects, PowerUpKind::Laser);
+        if self.firing && laser && self.lasers.fire(&self.paddle, &mut self.world) {
+            sounds.push((Sound::LaserShot, self.paddle.centre()));
+        }
+        for (at, points) in self.lasers.hit_bricks(&mut self.world, &mut self.bricks, &mut self.brick_grid) {
+            brick_points += points;
+            brick_hit_at = Some(at);
+            scored.push((at, points));
+            broken.push(at);
+        }
+        // An obstacle knocked out is despawned, which the dirty rectangles see as the world changing
+        for at in self.lasers.hit_obstacles(&mut self.world) {
+            sounds.push((Sound::BrickBreak, at));
+        }
+        let max_travel = (self.config.ball_size.min(self.config.paddle_height) / 2.0).max(MIN_SUBSTEP_TRAVEL);
+        let (gravity, wind) = self.forces;
+        self.ball_kinds.resize(self.balls.len(), BallKind::Normal);
+        for (i, (ball, &kind)) in self.balls.iter_mut().zip(&self.ball_kinds).enumerate() {
+            if i == 0 && self.held_ball.is_some() {
+                continue;
+            }
+            let ball_time = ball_time * kind.speed();
+            // What this kind of ball runs into, see collision::LAYERS
+            let filter = kind.filter();
+            let hit_paddles = filter.collides_with(Filter::on(Layers::PADDLE));
+            let hit_bricks = filter.collides_with(Filter::on(Layers::BRICK));
+            if (gravity, wind) != (0.0, 0.0) {
+                ball.vel_x += wind * ball_time;
+                ball.vel_y += gravity * ball_time;
+                physics::limit_speed(ball, self.config.ball_max_speed);
+            }
+
+            // Move in steps no longer than half the ball or paddle, see physics::substeps
+            let substeps = physics::substeps(ball, ball_time, max_travel);
+            for _ in 0..substeps {
+                if physics::move_ball(ball, ball_time / substeps as f32, field_width) {
+                    self.events.push(GameEvent::WallBounce { at: ball.centre() });
+                }
+
+                // Knock out any brick the ball runs into
+                let smash = kind.breaks_bricks();
+                let hit = hit_bricks.then(|| bricks::collide(ball, &mut self.bricks, &mut self.brick_grid, smash));
+                if let Some(points) = hit.flatten() {
+                    if points > 0 {
+                        broken.push(ball.centre());
+                    }
+                    brick_points += points;
+                    brick_hit_at = Some(ball.centre());
+                    scored.push((ball.centre(), points));
+                    bounces.push(ball.centre());
+                }
+                for obstacle in self.world_grid.query(ball) {
+                    let collider = self.world.colliders.get(obstacle);
+                    if !collider.is_some_and(|collider| filter.collides_with(collider.filter())) {
+                        continue;
+                    }
+                    let Some(block) = self.world.body(obstacle) else {
+                        continue;
+                    };
+                    if bricks::bounce_off(ball, &block) {
+                        // A moving block can throw the ball faster than it's allowed to go
+                        physics::limit_speed(ball, self.config.ball_max_speed);
+                        self.events.push(GameEvent::WallBounce { at: ball.centre() });
+                    }
+                }
+
+                // The boss takes a hit every time a ball bounces off it, scored like a brick
+                if let Some(boss) = self.boss.as_mut().filter(|_| hit_bricks) {
+                    if bricks::bounce_off(ball, &boss.body) {
+                        physics::limit_speed(ball, self.config.ball_max_speed);
+                        boss_beaten |= boss.hit();
+                        brick_points += BOSS_HIT_POINTS;
+                        brick_hit_at = Some(ball.centre());
+                        scored.push((ball.centre(), BOSS_HIT_POINTS));
+                        bounces.push(ball.centre());
+                    }
+                }
+
+                // A shield sends back a ball that got past the bottom paddle
+                if let Some(wall) = shield.as_ref().filter(|_| ball.vel_y > 0.0 && filter.collides_with(shield_filter)) {
+                    if collision::touches(ball, wall) {
+                        ball.y = wall.y - ball.height;
+                        ball.vel_y = -ball.vel_y;
+                        self.events.push(GameEvent::WallBounce { at: ball.centre() });
+                        shielded.push(ball.centre());
+                    }
+                }
+
+                // In solo mode the top of the window is a wall, otherwise it is the top player's goal
+                if !has_top_paddle && ball.y <= 0.0 && ball.vel_y < 0.0 {
+                    ball.vel_y = -ball.vel_y;
+                    self.events.push(GameEvent::WallBounce { at: ball.centre() });
+                }
+
+                // Handle ball collision with top paddle
+                if has_top_paddle && hit_paddles && physics::hits_paddle(ball, &self.top_paddle, 1.0) {
+                    physics::paddle_bounce(ball, &self.top_paddle, 1.0);
+                    physics::apply_spin(ball, &self.top_paddle, self.config.spin_transfer, self.config.ball_max_speed);
+                    paddle_hits += 1;
+                    if kind.splits() {
+                        splits.push(i);
+                    }
+                    hardest_hit = hardest_hit.max(ball.vel_x.hypot(ball.vel_y));
+                    self.events.push(GameEvent::PaddleHit { at: ball.centre() });
+                }
+
+                // Handle ball collision with paddle, the angle it leaves at depends on where it hit
+                if hit_paddles && physics::hits_paddle(ball, &self.paddle, -1.0) {
+                    physics::paddle_bounce(ball, &self.paddle, -1.0);
+                    physics::apply_spin(ball, &self.paddle, self.config.spin_transfer, self.config.ball_max_speed);
+                    paddle_hits += 1;
+                    bottom_hits += 1;
+                    if kind.splits() {
+                        splits.push(i);
+                    }
+                    if magnet && caught.is_none() {
+                        caught = Some(i);
+                    }
+                    hardest_hit = hardest_hit.max(ball.vel_x.hypot(ball.vel_y));
+                    sharpest_return = sharpest_return.max(ball.vel_x.abs().atan2(-ball.vel_y));
+                    self.events.push(GameEvent::PaddleHit { at: ball.centre() });
+                    scored.push((ball.centre(), return_points));
+                }
+            }
+        }
+
+        if let Some(i) = caught {
+            self.hold_ball(i);
+        }
+
+        // The shield goes once it has saved a ball
+        if !shielded.is_empty() {
+            powerups::deactivate(&mut self.effects, PowerUpKind::Shield);
+        }
+        for (x, y) in shielded {
+            self.particles.burst(x, y, SHIELD_PARTICLES, COMBO_PARTICLE_SPEED, powerups::SHIELD_COLOUR);
+        }
+
+        // Each split ball divides in two, fanned out either side of where it was going, as normal balls
+        for i in splits {
+            let ball = self.balls[i].clone();
+            self.balls[i] = physics::turned(&ball, -SPLIT_ANGLE);
+            self.ball_kinds[i] = BallKind::Normal;
+            self.balls.push(physics::turned(&ball, SPLIT_ANGLE));
+            self.ball_kinds.push(BallKind::Normal);
+        }
+
+        if self.counts_for_player() {
+            let fastest = self.balls.iter().map(|ball| ball.vel_x.hypot(ball.vel_y)).fold(0.0, f32::max);
+            self.stats.record_speed(fastest);
+        }
+
+        for (sound, at) in sounds {
+            self.audio.play_cued(sound, self.sound_cue(at));
+        }
+        for at in bounces {
+            self.add_collision_point(at);
+        }
+
+        for _ in 0..paddle_hits {
+            self.register_paddle_hit();
+        }
+        if bottom_hits > 0 {
+            self.advance_tutorial(tutorial::Action::Returned { angle: sharpest_return });
+        }
+        if hardest_hit >= HARD_HIT_SPEED * self.config.ball_max_speed {
+            self.shake_screen(HARD_HIT_SHAKE);
+        }
+        self.scoreboard.score += bottom_hits * return_points * self.multiplier();
+        if let Some(at) = brick_hit_at {
+            self.scoreboard.score += brick_points * self.multiplier();
+            self.audio.play_cued(Sound::BrickBreak, self.sound_cue(at));
+        }
+        for ((x, y), points) in scored.into_iter().filter(|&(_, points)| points > 0) {
+            self.popups.spawn(x, y, format!("+{}", points * self.multiplier()));
+        }
+        for (x, y) in broken {
+            if self.rng.next_f32() < COIN_CHANCE {
+                self.spawn(Spawn::Coin { x, y });
+            }
+        }
+
+        // Clearing the wall or beating the boss moves on to the next level
+        if boss_beaten {
+            if let Some(boss) = self.boss.take() {
+                let (x, y) = boss.body.centre();
+                self.particles.burst(x, y, BOSS_PARTICLES, COMBO_PARTICLE_SPEED, self.palette().bricks[0]);
+                self.shake_screen(BOSS_BEATEN_SHAKE);
+            }
+        }
+        if has_bricks && self.bricks.is_empty() && self.boss.is_none() {
+            self.next_level();
+            return;
+        }
+
+        self.update_pickups(delta_time);
+
+        // Balls going out at either end are gone, the mode's rules say what that costs
+        let field_height = self.config.window_height as f32;
+        let out_at = |ball: &GameObject| {
+            if ball.y + ball.height > field_height {
+                Some(End::Bottom)
+            } else if has_top_paddle && ball.y < 0.0 {
+                Some(End::Top)
+            } else {
+                None
+            }
+        };
+        let lost: Vec<End> = self.balls.iter().filter_map(out_at).collect();
+        if lost.is_empty() {
+            return;
+        }
+        let (balls, kinds) = std::mem::take(&mut self.balls)
+            .into_iter()
+            .zip(std::mem::take(&mut self.ball_kinds))
+            .filter(|(ball, _)| out_at(ball).is_none())
+            .unzip();
+        (self.balls, self.ball_kinds) = (balls, kinds);
+        if self.counts_for_player() {
+            self.stats.add_balls_lost(lost.len());
+        }
+        let mut outcome = Outcome::Play;
+        for &end in &lost {
+            outcome = if self.tutorial.is_some() {
+                // The tutorial just serves again
+                if self.balls.is_empty() { Outcome::Serve } else { Outcome::Play }
+            } else {
+                self.modes.get(self.mode).ball_lost(&mut self.scoreboard, end, self.balls.len())
+            };
+            if outcome != Outcome::Play {
+                // Serve towards whoever lost the ball
+                self.serve_dir = if end == End::Bottom { 1.0 } else { -1.0 };
+                break;
+            }
+        }
+        match outcome {
+            Outcome::Play => {}
+            Outcome::Serve => {
+                self.combo = 0;
+                self.events.push(GameEvent::LifeLost { lives: self.scoreboard.lives });
+                self.start_serve_pause();
+                #[cfg(feature = "lua")]
+                if self.tutorial.is_none() && self.rules().has_lives() {
+                    self.run_script(scripting::Hook::LifeLost { lives: self.scoreboard.lives });
+                }
+            }
+            Outcome::GameOver => self.finish_game(),
+        }
+    }
+
+    // Pass on what happened in the last physics step to the sounds, HUD, particles and achievements
+    fn handle_events(&mut self) {
+        let mut events = self.events.drain();
+        for event in events.drain(..) {
+            match event {
+                GameEvent::WallBounce { at } => {
+                    self.audio.play_cued(Sound::WallBounce, self.sound_cue(at));
+                    self.add_collision_point(at);
+                }
+                GameEvent::PaddleHit { at } => {
+                    self.audio.play_cued(Sound::PaddleHit, self.sound_cue(at));
+                    self.add_collision_point(at);
+                    self.record_event(achievements::Event::PaddleHit { hits: self.paddle_hits });
+                }
+                GameEvent::LifeLost { .. } => {
+                    self.audio.play(Sound::LifeLost);
+                    self.shake_screen(LIFE_LOST_SHAKE);
+                }
+                GameEvent::LevelCleared { .. } => self.audio.play(Sound::LevelCleared),
+                GameEvent::PowerUpCollected { kind, at: (x, y) } => {
+                    self.show_hud_message(self.locale.get(kind.name_key()).to_string());
+                    self.particles.burst(x, y, PICKUP_PARTICLES, COMBO_PARTICLE_SPEED, kind.colour());
+                }
+            }
+        }
+        self.events.recycle(events);
+    }
+
+    // Clear away what's gone after a physics step: whatever was despawned along the way,
+    // and anything that has flown out of the top or bottom of the field
+    fn cleanup(&mut self) {
+        ecs::despawn_outside(&mut self.world, self.config.window_height as f32);
+        self.world.cleanup();
+    }
+
+    // For the debug overlay, which shows the last few
+    fn add_collision_point(&mut self, at: (f32, f32)) {
+        self.collision_points.push_back(at);
+        while self.collision_points.len() > MAX_COLLISION_POINTS {
+            self.collision_points.pop_front();
+        }
+    }
+
+    // Count down timed power-ups and size the paddle for any that are running
+    fn update_effects(&mut self, delta_time: f32) {
+        powerups::tick(&mut self.effects, delta_time);
+
+        let mut width = self.base_paddle_width();
+        if powerups::is_active(&self.effects, PowerUpKind::WidePaddle) {
+            width *= WIDE_PADDLE_FACTOR;
+        }
+        if powerups::is_active(&self.effects, PowerUpKind::ShrinkPaddle) {
+            width *= SHRINK_PADDLE_FACTOR;
+        }
+        let width = width.min(self.config.window_width as f32);
+        // Grow or shrink around the centre so the paddle doesn't jump sideways
+        self.paddle.x += (self.paddle.width - width) / 2.0;
+        self.paddle.width = width;
+    }
+
+    // The magnet's ball rides along on the paddle until the serve key is pressed again, or the magnet runs out
+    fn update_held_ball(&mut self) {
+        let Some(mut held) = self.held_ball.take() else {
+            return;
+        };
+        let Some(ball) = self.balls.first_mut() else {
+            return;
+        };
+        held.armed |= !self.firing;
+        ball.x = self.paddle.x + held.offset - ball.width / 2.0;
+        ball.y = self.paddle.y - ball.height;
+        if (held.armed && self.firing) || !powerups::is_active(&self.effects, PowerUpKind::Magnet) {
+            // Launched up at its speed when caught, angled by which way and how fast the paddle is moving
+            let speed = ball.vel_x.hypot(ball.vel_y);
+            let angle = (self.paddle.vel_x / self.config.paddle_speed).clamp(-1.0, 1.0) * MAGNET_LAUNCH_ANGLE;
+            ball.vel_x = speed * angle.sin();
+            ball.vel_y = -speed * angle.cos();
+        } else {
+            self.held_ball = Some(held);
+        }
+    }
+
+    // Stick a ball to the bottom paddle where it landed, moved to the front of the balls
+    fn hold_ball(&mut self, i: usize) {
+        self.balls.swap(0, i);
+        self.ball_kinds.swap(0, i);
+        if i < self.trails.len() {
+            self.trails.swap(0, i);
+        }
+        if i < self.prev_ball_positions.len() {
+            self.prev_ball_positions.swap(0, i);
+        }
+        let offset = self.balls[0].centre().0 - self.paddle.x;
+        self.held_ball = Some(HeldBall {
+            offset: offset.clamp(0.0, self.paddle.width),
+            armed: false,
+        });
+    }
+
+    // The wall a running shield puts across the field just under the bottom paddle
+    fn shield_wall(&self) -> Option<GameObject> {
+        powerups::is_active(&self.effects, PowerUpKind::Shield).then_some(GameObject {
+            x: 0.0,
+            y: self.paddle.y + self.paddle.height + SHIELD_GAP,
+            width: self.config.window_width as f32,
+            height: SHIELD_HEIGHT,
+            vel_x: 0.0,
+            vel_y: 0.0,
+        })
+    }
+
+    // Drop new pickups, move the falling ones and apply any the paddle catches
+    fn update_pickups(&mut self, delta_time: f32) {
+        if !self.rules().has_lives() || self.tutorial.is_some() {
+            return;
+        }
+
+        let field_width = self.config.window_width as f32;
+        self.spawner.update(delta_time, field_width, &mut self.rng, &mut self.world);
+
+        let mut caught = Vec::new();
+        let mut coins = 0;
+        for (pickup, collider) in ecs::touching(&self.world, &self.paddle, Filter::on(Layers::PADDLE)) {
+            match collider {
+                Collider::PowerUp(kind) => {
+                    let at = self.world.body(pickup).map_or(self.paddle.centre(), |body| body.centre());
+                    caught.push(kind);
+                    self.events.push(GameEvent::PowerUpCollected { kind, at });
+                }
+                Collider::Coin => coins += 1,
+                Collider::Solid | Collider::Bolt => continue,
+            }
+            self.world.despawn(pickup);
+        }
+
+        for kind in caught {
+            self.apply_powerup(kind);
+        }
+        if coins > 0 {
+            let (x, y) = (self.paddle.x + self.paddle.width / 2.0, self.paddle.y);
+            self.popups.spawn(x, y, self.locale.format("play.coins", &[&coins]));
+            if self.counts_for_player() {
+                self.profile.add_coins(coins);
+            }
+        }
+    }
+
+    // Start a game from the title screen with one of each power-up bought in the shop, in a mode with them
+    fn use_stocked_power_ups(&mut self) {
+        if !self.rules().has_lives() {
+            return;
+        }
+        for kind in self.profile.take_stock() {
+            self.show_hud_message(self.locale.get(kind.name_key()).to_string());
+            self.apply_powerup(kind);
+            self.recording.power_ups.push(kind);
+        }
+    }
+
+    fn apply_powerup(&mut self, kind: PowerUpKind) {
+        match kind {
+            PowerUpKind::ExtraLife => self.scoreboard.lives += 1,
+            PowerUpKind::MultiBall => {
+                // Split every ball into three, fanned out either side of its current direction, the same kind as it
+                self.ball_kinds.resize(self.balls.len(), BallKind::Normal);
+                let mut extra = Vec::new();
+                let mut extra_kinds = Vec::new();
+                for (ball, &ball_kind) in self.balls.iter().zip(&self.ball_kinds) {
+                    for angle in [-MULTI_BALL_ANGLE, MULTI_BALL_ANGLE] {
+                        extra.push(physics::turned(ball, angle));
+                        extra_kinds.push(ball_kind);
+                    }
+                }
+                self.balls.extend(extra);
+                self.ball_kinds.extend(extra_kinds);
+            }
+            PowerUpKind::HeavyBall => self.ball_kinds = vec![BallKind::Heavy; self.balls.len()],
+            PowerUpKind::GhostBall => self.ball_kinds = vec![BallKind::Ghost; self.balls.len()],
+            PowerUpKind::SplitBall => self.ball_kinds = vec![BallKind::Split; self.balls.len()],
+            PowerUpKind::WidePaddle
+            | PowerUpKind::SlowBall
+            | PowerUpKind::ShrinkPaddle
+            | PowerUpKind::InvertControls
+            | PowerUpKind::FastBall
+            | PowerUpKind::Shield
+            | PowerUpKind::Laser
+            | PowerUpKind::Magnet => powerups::activate(&mut self.effects, kind),
+        }
+    }
+
+    fn register_paddle_hit(&mut self) {
+        self.paddle_hits += 1;
+        self.rally += 1;
+        if self.counts_for_player() {
+            self.stats.add_paddle_hit(self.rally);
+        }
+
+        // Long rallies are worth a coin every so often, dropped from the top
+        if self.rules().has_lives() && self.rally.is_multiple_of(RALLY_COIN_HITS) {
+            let x = self.rng.range(0.0, self.config.window_width as f32);
+            self.spawn(Spawn::Coin { x, y: 0.0 });
+        }
+
+        // Every step up in the multiplier bursts out of the paddle
+        if self.rules().has_lives() {
+            self.combo += 1;
+            if self.combo.is_multiple_of(COMBO_STEP) && self.combo / COMBO_STEP < MAX_MULTIPLIER {
+                let (x, y) = (self.paddle.x + self.paddle.width / 2.0, self.paddle.y);
+                self.particles.burst(x, y, COMBO_PARTICLES, COMBO_PARTICLE_SPEED, self.palette().ball);
+                self.show_hud_message(self.locale.format("hud.combo", &[&self.multiplier()]));
+            }
+        }
+
+        // Speed the balls up every few hits so long games stay challenging
+        if self.paddle_hits.is_multiple_of(self.config.hits_per_speed_up) {
+            let factor = 1.0 + self.config.speed_up_percent * self.preset().speed_up / 100.0;
+            self.ball_speed *= factor;
+            for ball in self.balls.iter_mut() {
+                ball.vel_x *= factor;
+                ball.vel_y *= factor;
+
+                // Keep both the current balls and future serves under the speed limit
+                physics::limit_speed(ball, self.config.ball_max_speed);
+            }
+            self.ball_speed = self.ball_speed.min(self.config.ball_max_speed / std::f32::consts::SQRT_2);
+        }
+        #[cfg(feature = "lua")]
+        self.run_script(scripting::Hook::PaddleHit { hits: self.paddle_hits });
+    }
+
+    // Let the script react to what just happened and make the changes it asked for
+    #[cfg(feature = "lua")]
+    fn run_script(&mut self, hook: scripting::Hook) {
+        let Some(script) = self.script.as_mut() else {
+            return;
+        };
+        for command in script.run(hook) {
+            match command {
+                Command::SpawnBall { x, y, vel_x, vel_y } => self.spawn_ball(x, y, vel_x, vel_y),
+                Command::SpawnBlock { x, y, width, height } => {
+                    let body = GameObject { x, y, width, height, vel_x: 0.0, vel_y: 0.0 };
+                    self.spawn(Spawn::Block { body, colour: OBSTACLE_COLOUR, path: None });
+                }
+                // Pickups only fall in the modes with lives
+                Command::SpawnPickup { kind, x } if self.rules().has_lives() => {
+                    self.spawn(Spawn::PowerUp { kind, x });
+                }
+                Command::SpawnPickup { .. } => {}
+                Command::SetBallSpeed(speed) => {
+                    let speed = speed.min(self.config.ball_max_speed);
+                    for ball in self.balls.iter_mut() {
+                        let current = ball.vel_x.hypot(ball.vel_y);
+                        if current > 0.0 {
+                            ball.vel_x *= speed / current;
+                            ball.vel_y *= speed / current;
+                        }
+                    }
+                    self.ball_speed = speed / std::f32::consts::SQRT_2; // Serves are at ball_speed on each axis
+                }
+                Command::SetPaddleSpeed(speed) => self.config.paddle_speed = speed,
+            }
+        }
+    }
+
+    // On to the next level in breakout mode. Every boss::BOSS_EVERY levels is a boss in between
+    // the campaign's levels, which carries on after it
+    fn next_level(&mut self) {
+        self.events.push(GameEvent::LevelCleared { level: self.scoreboard.level });
+        self.scoreboard.level += 1;
+        if boss::is_boss_level(self.scoreboard.level) {
+            self.load_boss_level();
+        } else {
+            let from_campaign = self.campaign.as_mut().is_some_and(Campaign::advance);
+            self.load_level(from_campaign);
+        }
+        // Bolts still flying don't carry over into the new level
+        self.lasers.clear();
+        self.world.despawn_all(|collider| *collider == Collider::Bolt);
+        self.start_serve_pause();
+        #[cfg(feature = "lua")]
+        self.run_script(scripting::Hook::LevelStart { level: self.scoreboard.level });
+    }
+
+    // Set up the bricks for self.scoreboard.level, from the campaign's current level file or generated
+    // once the campaign has run out
+    fn load_level(&mut self, from_campaign: bool) {
+        let field_width = self.config.window_width as f32;
+        self.boss = None;
+        let level = self.campaign.as_ref().filter(|_| from_campaign).map(Campaign::current);
+        match level {
+            Some(level) => {
+                self.bricks = level.bricks(field_width);
+                self.world.despawn_all(|collider| *collider == Collider::Solid);
+                spawn_obstacles(&mut self.world, &level.obstacles);
+                self.background = level.background;
+                self.tiles = level.tiles.clone();
+                self.forces = (level.gravity, level.wind);
+                self.ball_speed = level.ball_speed.unwrap_or(self.config.ball_speed) * self.preset().ball_speed;
+                let message = self.locale.format("hud.level_named", &[&self.scoreboard.level, &level.name.to_uppercase()]);
+                self.show_hud_message(message.trim_end().to_string());
+            }
+            None => {
+                self.bricks = bricks::layout(self.scoreboard.level, field_width);
+                self.world.despawn_all(|collider| *collider == Collider::Solid);
+                self.background = None;
+                self.tiles = None;
+                self.forces = (0.0, 0.0);
+                self.show_hud_message(self.locale.format("hud.level", &[&self.scoreboard.level]));
+            }
+        }
+    }
+
+    // An empty field apart from the boss
+    fn load_boss_level(&mut self) {
+        self.bricks.clear();
+        self.world.despawn_all(|collider| *collider == Collider::Solid);
+        self.background = None;
+        self.tiles = None;
+        self.forces = (0.0, 0.0);
+        self.boss = Some(Boss::new(self.scoreboard.level, self.config.window_width as f32));
+        self.show_hud_message(self.locale.format("hud.boss", &[&self.scoreboard.level]));
+    }
+
+    // With position cues on, a sound at (x, y) is panned to that side of the field
+    // and played higher the closer it is to the bottom paddle
+    fn sound_cue(&self, (x, y): (f32, f32)) -> Cue {
+        if !self.config.position_cues {
+            return Cue::CENTRE;
+        }
+        let pan = (x / self.config.window_width as f32 * 2.0 - 1.0).clamp(-1.0, 1.0);
+        let nearness = (y / self.paddle.y).clamp(0.0, 1.0);
+        Cue {
+            pan,
+            pitch: 1.0 + nearness * CUE_PITCH_RISE,
+        }
+    }
+
+    // Points are multiplied by this, going up every COMBO_STEP paddle hits in a row
+    fn multiplier(&self) -> i32 {
+        (1 + self.combo / COMBO_STEP).min(MAX_MULTIPLIER) as i32
+    }
+
+    fn shake_screen(&mut self, amount: f32) {
+        if self.config.screen_shake {
+            self.shake = self.shake.max(amount);
+        }
+    }
+
+    // Die the shake away and pick this frame's offset for render()
+    fn update_shake(&mut self, frame_time: f32) {
+        self.shake *= (-SHAKE_DECAY * frame_time).exp();
+        if self.shake < 0.5 {
+            self.shake = 0.0;
+            self.shake_offset = (0.0, 0.0);
+            return;
+        }
+        let x = self.shake_rng.range(-self.shake, self.shake).round();
+        let y = self.shake_rng.range(-self.shake, self.shake).round();
+        self.shake_offset = (x, y);
+    }
+
+    fn start_serve_pause(&mut self) {
+        self.is_paused = true;
+        self.rally = 0;
+        self.pause_start = Some(self.clock.now());
+        // Park a single stationary ball in the middle while paused
+        self.reset_ball();
+        self.balls[0].vel_x = 0.0;
+        self.balls[0].vel_y = 0.0;
+        // Power-ups don't carry over to the next ball
+        self.world.despawn_all(|collider| matches!(collider, Collider::PowerUp(_)));
+        self.effects.clear();
+    }
+
+    // The number to show while waiting to serve, 3, 2 then 1. None when the ball is in play
+    fn serve_countdown(&self) -> Option<u64> {
+        // The tutorial has no countdown, it waits for the player to serve
+        let start = self.pause_start.filter(|_| self.is_paused && self.tutorial.is_none())?;
+        // Behind the pause screen the count stays where it was
+        let waited = self.paused_at.unwrap_or_else(|| self.clock.now()) - start;
+        Some(SERVE_COUNTDOWN.saturating_sub(waited).as_secs_f32().ceil().max(1.0) as u64)
+    }
+
+    fn reset_ball(&mut self) {
+        // Reset ball position and velocity, serving towards the player who lost the last point
+        // at a random angle to either side
+        let size = self.config.ball_size;
+        let speed = self.ball_speed * std::f32::consts::SQRT_2;
+        let angle = self.rng.range(MIN_SERVE_ANGLE, MAX_SERVE_ANGLE);
+        let side = if self.rng.coin_flip() { 1.0 } else { -1.0 };
+        self.balls = vec![GameObject {
+            x: self.config.window_width as f32 / 2.0 - size / 2.0,
+            y: self.config.window_height as f32 / 2.0 - size / 2.0,
+            width: size,
+            height: size,
+            vel_x: speed * angle.sin() * side,
+            vel_y: speed * angle.cos() * self.serve_dir,
+        }];
+        // Don't draw the new ball sliding over from where the old one was
+        self.prev_ball_positions.clear();
+        self.trails.clear();
+        // A served ball is always a normal one, and free of the magnet
+        self.ball_kinds.clear();
+        self.held_ball = None;
+    }
+
+    // F12 saves a screenshot once the current frame has been drawn, F10 a GIF of the last few seconds
+    fn handle_capture_keys(&mut self) {
+        if self.keys.just_pressed(Key::F12) {
+            self.screenshot_requested = true;
+        }
+        if self.keys.just_pressed(Key::F10) {
+            self.save_clip();
+        }
+    }
+
+    // Encoding takes a moment, so it happens on another thread and the game carries on
+    fn save_clip(&mut self) {
+        let name = image::timestamped_name("clip", "gif");
+        let gif = self.clip.encode_gif_later(self.clip.delay(1000.0 / self.config.frame_target_time as f32));
+        let save = move || match std::fs::write(&name, gif()) {
+            Ok(()) => println!("Clip saved to {}", name),
+            Err(e) => eprintln!("Error saving clip {}: {}", name, e),
+        };
+        // Without threads, as on the web, it just has to be done here
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::spawn(save);
+        #[cfg(target_arch = "wasm32")]
+        save();
+        self.show_hud_message(self.locale.get("hud.saving_clip").to_string());
+    }
+
+    fn save_screenshot(&mut self, buffer: &[u32]) {
+        let name = image::timestamped_name("screenshot", "png");
+        let (width, height) = self.config.render_size();
+        match image::save_png(Path::new(&name), buffer, width, height) {
+            Ok(()) => {
+                println!("Screenshot saved to {}", name);
+                self.show_hud_message(self.locale.get("hud.screenshot_saved").to_string());
+            }
+            Err(e) => eprintln!("Error saving screenshot {}: {}", name, e),
+        }
+    }
+
+    // F11 or Alt+Enter switches between a normal and a borderless window
+    fn handle_fullscreen_key(&mut self) {
+        let alt_down = self.keys.is_down(Key::LeftAlt) || self.keys.is_down(Key::RightAlt);
+        let toggle = self.keys.just_pressed(Key::F11) || (alt_down && self.keys.just_pressed(Key::Enter));
+        if !toggle {
+            return;
+        }
+
+        let fullscreen = !self.config.fullscreen;
+        let Some(display) = self.display.as_mut() else {
+            return;
+        };
+        match display.set_fullscreen(fullscreen) {
+            Ok(()) => {
+                self.config.fullscreen = fullscreen;
+                self.last_frame_time = self.clock.now(); // Don't count the time spent swapping windows
+            }
+            Err(e) => eprintln!("Error switching fullscreen: {}", e),
+        }
+    }
+
+    // F9 switches the CRT effect on and off
+    fn handle_crt_key(&mut self) {
+        if !self.keys.just_pressed(Key::F9) {
+            return;
+        }
+        self.config.crt = !self.config.crt;
+        let (width, height) = self.config.render_size();
+        self.crt = self.config.crt.then(|| CrtFilter::new(width, height));
+        let message = if self.config.crt { "hud.crt_on" } else { "hud.crt_off" };
+        self.show_hud_message(self.locale.get(message).to_string());
+    }
+
+    // Draw the frame and show it in the window, scaled to fit if the window has been resized.
+    // Only the parts that changed are drawn over the last frame when that's enough.
+    // The frame is also kept for F10 clips and saved if a screenshot was asked for, after any CRT effect
+    pub fn present(&mut self) -> Result<(), GameError> {
+        let Some(mut display) = self.display.take() else {
+            return Ok(());
+        };
+        self.frame_stats.record();
+        let (width, height) = (self.config.window_width, self.config.window_height);
+        if display.size() != (width, height) {
+            // A lower render resolution, drawn whole as the dirty rectangles are in playfield pixels
+            self.render(&mut Scaled::new(display.as_mut(), width, height));
+        } else {
+            match self.dirty_rects() {
+                Some(rects) => self.render(&mut DirtyRects::new(display.as_mut(), &rects)),
+                None => self.render(display.as_mut()),
+            }
+        }
+        if let (Some(crt), Some(frame)) = (self.crt.as_mut(), display.pixels_mut()) {
+            crt.apply(frame);
+        }
+        if let Some(frame) = display.pixels() {
+            self.clip.push(frame, display.size().0);
+            if self.screenshot_requested {
+                self.screenshot_requested = false;
+                self.save_screenshot(frame);
+            }
+        }
+        let border = match self.config.bars {
+            Bars::Black => 0xFF000000,
+            Bars::Theme => self.palette().background,
+        };
+        display.set_border_colour(self.daylight.as_ref().map_or(border, |(_, lut)| lut.map(border)));
+        let shown = display.present().map_err(GameError::Window);
+        self.display = Some(display);
+        shown
+    }
+}
+
+// However the game closes, the stats from this session are kept
+impl Drop for Game {
+    fn drop(&mut self) {
+        if let Err(e) = self.stats.save() {
+            eprintln!("Error saving stats: {}", e);
+        }
+    }
+}
+
+// Letter typed for a key, used for entering initials
+fn key_letter(key: Key) -> Option<char> {
+    const LETTERS: [Key; 26] = [
+        Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+        Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    ];
+    LETTERS.iter().position(|&letter| letter == key).map(|i| (b'A' + i as u8) as char)
+}
@@ -0,0 +1,119 @@
+// dirty rectangles: while a game runs undisturbed only the moving things and the HUD change from one frame
+// to the next, so present() leaves the display's last frame in place and redraws just around those.
+// anything else changing, like another scene, a brick breaking or the screen shaking, redraws the whole frame
+
+use super::{Game, GameState};
+use crate::achievements::Achievement;
+use crate::hud;
+use crate::tutorial::Step;
+use crate::renderer::Rect;
+
+const EDGE_MARGIN: f32 = 2.0; // Extra pixels around moving things for their blended edges
+
+// Everything besides the moving things that shows in the frame. While it stays the same the last frame can be reused
+#[derive(PartialEq)]
+struct Layout {
+    scenes: usize,
+    state: GameState,
+    theme: usize,
+    daylight: Option<u32>,
+    background: Option<u32>,
+    level: u32,
+    bricks: usize,
+    brick_hits: u32,
+    boss_health: Option<u32>,
+    world: u64, // Entities spawned or despawned, the still ones aren't among the moving things
+    shake_offset: (f32, f32),
+    countdown: Option<u64>,
+    demo: bool,
+    effects: bool, // Particles or score popups on screen
+    shield: bool,
+    toast: Option<Achievement>,
+    tutorial: Option<Step>,
+}
+
+#[derive(Default)]
+pub(super) struct Redraw {
+    layout: Option<Layout>,
+    moving: Vec<Rect>, // Where the moving things were drawn last frame, to be painted over in this one
+}
+
+impl Game {
+    // The parts of the frame to draw again, None for all of it
+    pub(super) fn dirty_rects(&mut self) -> Option<Vec<Rect>> {
+        let layout = Layout {
+            scenes: self.scenes.len(),
+            state: self.state(),
+            theme: self.theme,
+            daylight: self.daylight.as_ref().map(|(step, _)| *step),
+            background: self.background,
+            level: self.scoreboard.level,
+            bricks: self.bricks.len(),
+            brick_hits: self.bricks.iter().map(|brick| brick.hits_left).sum(),
+            boss_health: self.boss.as_ref().map(|boss| boss.health),
+            world: self.world.changes(),
+            shake_offset: self.shake_offset,
+            countdown: self.serve_countdown(),
+            demo: self.demo,
+            effects: self.has_effects(),
+            shield: self.shield_wall().is_some(),
+            toast: self.toasts.front().copied(),
+            tutorial: self.tutorial.as_ref().map(|tutorial| tutorial.step()),
+        };
+        let moving = self.moving_rects();
+        let last_moving = std::mem::replace(&mut self.redraw.moving, moving.clone());
+        let unchanged = self.redraw.layout.as_ref() == Some(&layout);
+        self.redraw.layout = Some(layout);
+
+        // Things all over the frame that change every frame, and the CRT effect which leaves nothing to reuse
+        let overlays = self.show_debug || self.show_frame_stats || self.crt.is_some() || self.has_effects();
+        if !unchanged || overlays || self.state() != GameState::Playing {
+            return None;
+        }
+
+        let (width, height) = (self.config.window_width, self.config.window_height);
+        let strip = hud::HEIGHT.min(height);
+        let mut rects = vec![
+            Rect { x: 0, y: 0, width, height: strip },
+            Rect { x: 0, y: height - strip, width, height: strip },
+        ];
+        rects.extend(last_moving);
+        rects.extend(moving);
+        Some(rects)
+    }
+
+    fn has_effects(&self) -> bool {
+        !self.particles.is_empty() || !self.popups.is_empty()
+    }
+
+    // Around each ball and its trail, the paddles, the laser's bolts, the falling pickups and the stars
+    // as they are drawn this frame
+    fn moving_rects(&self) -> Vec<Rect> {
+        let field = (self.config.window_width, self.config.window_height);
+        let around = |x, y, width, height| Rect::around(x, y, width, height, EDGE_MARGIN, field);
+        let mut rects = Vec::new();
+        let balls = self.drawn_balls();
+        for (i, ball) in balls.iter().enumerate() {
+            let mut rect = around(ball.x, ball.y, ball.width, ball.height);
+            for &(x, y) in self.trails.get(i).into_iter().flatten() {
+                rect = rect.union(around(x, y, ball.width, ball.height));
+            }
+            rects.push(rect);
+        }
+        for paddle in self.drawn_paddles() {
+            rects.push(around(paddle.x, paddle.y, paddle.width, paddle.height));
+        }
+        if let Some(boss) = &self.boss {
+            rects.push(around(boss.body.x, boss.body.y, boss.body.width, boss.body.height));
+        }
+        for (entity, _) in self.world.velocities.iter() {
+            if let Some(body) = self.world.body(entity) {
+                rects.push(around(body.x, body.y, body.width, body.height));
+            }
+        }
+        if self.config.starfield && self.tiles.is_none() {
+            rects.extend(self.starfield.rects(self.play_time));
+        }
+        rects
+    }
+}
@@ -0,0 +1,561 @@
+// drawing the game through a Renderer, which needs no window so it works headless too
+
+use super::{Game, GameMode, INITIALS_LENGTH, PHYSICS_STEP};
+use crate::assets::Texture;
+use crate::balls::BallKind;
+use crate::ecs::{Collider, World};
+use crate::highscores::HighScore;
+use crate::hud::{self, Corner, Hud};
+use crate::leaderboard::Status;
+use crate::menu::Settings;
+use crate::renderer::{Camera, PixelBuffer, Renderer, Tinted};
+use crate::tutorial::Step;
+use crate::{font, highscores, keybindings, menu, modes, palette, powerups, GameObject};
+use std::time::Duration;
+
+const HUD_MESSAGE_TIME: Duration = Duration::from_millis(1500);
+const DEBUG_VECTOR_TIME: f32 = 0.1; // Velocity arrows reach where the ball will be this many seconds on
+const DEBUG_TEXT_COLOUR: u32 = 0xFF00FF00;
+const DEBUG_HITBOX_COLOUR: u32 = 0xFF00FFFF;
+const DEBUG_VECTOR_COLOUR: u32 = 0xFFFFFF00;
+const DEBUG_COLLISION_COLOUR: u32 = 0xFFFF4040;
+const DEMO_TEXT_Y: usize = 400;
+const TUTORIAL_TEXT_Y: usize = 400;
+const TOAST_Y: usize = 60; // Just below the HUD
+const TOAST_PADDING: usize = 12;
+const MAX_RINGS: u32 = 2; // Rings drawn inside a tough brick, one for each hit it needs after the next
+const RING_SPACING: f32 = 4.0;
+const RING_DARKEN: f32 = 0.5;
+const HATCH_SPACING: usize = 6; // pixels between the stripes across an obstacle
+const BOSS_BAR_HEIGHT: f32 = 6.0; // The boss's health bar runs along its bottom edge
+const BOSS_BAR_MARGIN: f32 = 4.0;
+const COUNTDOWN_SCALE: usize = 10;
+const COUNTDOWN_GAP: usize = 120; // How far above the middle, where the ball waits, the countdown is drawn
+const DEBUFF_RING_INSET: f32 = 2.0; // Risky pickups have a ring this far inside their edge
+const DEBUFF_MARK_COLOUR: u32 = 0xFFFFFFFF;
+
+impl Game {
+    // Draw the current frame, the renderer is left to present it
+    pub fn render(&self, renderer: &mut dyn Renderer) {
+        match &self.daylight {
+            Some((_, lut)) => self.render_scenes(&mut Tinted::new(renderer, lut)),
+            None => self.render_scenes(renderer),
+        }
+    }
+
+    fn render_scenes(&self, renderer: &mut dyn Renderer) {
+        // Start from the top scene that isn't an overlay and draw upwards from there
+        let first = self.scenes.iter().rposition(|scene| !scene.is_overlay()).unwrap_or(0);
+        for scene in &self.scenes[first..] {
+            scene.render(self, renderer);
+        }
+        self.render_toast(renderer);
+        if self.show_frame_stats {
+            self.frame_stats.draw(renderer, self.config.frame_target_time as f32);
+        }
+    }
+
+    // Draw the current frame into a buffer the size of the playfield
+    pub fn render_into(&self, buffer: &mut [u32]) {
+        self.render(&mut PixelBuffer::new(buffer, self.config.window_width));
+    }
+
+    pub(super) fn render_menu(&self, renderer: &mut dyn Renderer) {
+        let palette = self.palette();
+        renderer.clear(palette.background);
+        let settings = Settings {
+            difficulty: self.preset().name,
+            theme: palette.name,
+            skin: self.profile.selected().name,
+            time_scale: self.time_scale,
+            modes: &self.modes,
+        };
+        self.menu.render(renderer, palette, &self.locale, &settings);
+    }
+
+    pub(super) fn render_controls(&self, renderer: &mut dyn Renderer) {
+        let palette = self.palette();
+        renderer.clear(palette.background);
+        menu::render_controls(renderer, &self.config.keys, palette, &self.locale);
+    }
+
+    pub(super) fn render_stats(&self, renderer: &mut dyn Renderer) {
+        let palette = self.palette();
+        renderer.clear(palette.background);
+        menu::render_stats(renderer, &self.stats, palette, &self.locale);
+    }
+
+    pub(super) fn render_shop(&self, renderer: &mut dyn Renderer) {
+        let palette = self.palette();
+        renderer.clear(palette.background);
+        let quit = keybindings::key_name(self.config.keys.quit).to_uppercase();
+        self.shop.render(renderer, palette, &self.locale, &self.profile, &quit);
+    }
+
+    pub(super) fn render_playing(&self, renderer: &mut dyn Renderer) {
+        // Levels can have their own background colour, and tiles over it in place of the stars
+        let background = self.background.unwrap_or(self.palette().background);
+        renderer.clear(background);
+        if let Some(tiles) = &self.tiles {
+            tiles.draw(renderer);
+        } else if self.config.starfield {
+            self.starfield.draw(renderer, self.play_time, background, self.palette().hud);
+        }
+        self.render_playfield(renderer);
+        if self.demo {
+            menu::draw_centred(renderer, DEMO_TEXT_Y, self.locale.get("play.demo"), 3, self.palette().hud_dim);
+        }
+    }
+
+    // What the tutorial is waiting for the player to do, with the keys to do it
+    pub(super) fn render_tutorial(&self, renderer: &mut dyn Renderer) {
+        let Some(tutorial) = &self.tutorial else {
+            return;
+        };
+        let keys = &self.config.keys;
+        let name = |key| keybindings::key_name(key).to_uppercase();
+        let prompt = match tutorial.step() {
+            Step::Move => self.locale.format(tutorial.prompt_key(), &[&name(keys.move_left), &name(keys.move_right)]),
+            Step::Serve => self.locale.format(tutorial.prompt_key(), &[&name(keys.serve)]),
+            Step::Angle | Step::Done => self.locale.get(tutorial.prompt_key()).to_string(),
+        };
+        menu::draw_centred(renderer, TUTORIAL_TEXT_Y, &prompt, 2, self.palette().hud);
+    }
+
+    // Drawn over the frozen game, dimmed underneath the pause text
+    pub(super) fn render_paused(&self, renderer: &mut dyn Renderer) {
+        let palette = self.palette();
+        renderer.dim();
+        menu::draw_centred(renderer, 250, self.locale.get("play.paused"), 5, palette.hud);
+        let help = self.locale.format(
+            "play.pause_help",
+            &[
+                &keybindings::key_name(self.config.keys.pause).to_uppercase(),
+                &keybindings::key_name(self.config.keys.quit).to_uppercase(),
+            ],
+        );
+        menu::draw_centred(renderer, 320, &help, 2, palette.hud_dim);
+    }
+
+    // A newly unlocked achievement in a box at the top of the screen
+    fn render_toast(&self, renderer: &mut dyn Renderer) {
+        let Some(achievement) = self.toasts.front() else {
+            return;
+        };
+        let palette = self.palette();
+        let title = self.locale.format("achievement.unlocked", &[&self.locale.get(achievement.name_key())]);
+        let goal = self.locale.get(achievement.goal_key());
+        let width = font::text_width(&title, 2).max(font::text_width(goal, 2)) + TOAST_PADDING * 2;
+        let height = font::GLYPH_HEIGHT * 2 * 3 + TOAST_PADDING * 2;
+        let toast = GameObject {
+            x: (self.config.window_width.saturating_sub(width) / 2) as f32,
+            y: TOAST_Y as f32,
+            width: width as f32,
+            height: height as f32,
+            vel_x: 0.0,
+            vel_y: 0.0,
+        };
+        draw_object_colour(renderer, &toast, palette.background);
+        draw_outline(renderer, &toast, palette.hud);
+        let line_y = TOAST_Y + TOAST_PADDING;
+        menu::draw_centred(renderer, line_y, &title, 2, palette.hud);
+        menu::draw_centred(renderer, line_y + font::GLYPH_HEIGHT * 2 * 2, goal, 2, palette.hud_dim);
+    }
+
+    // Each ball's recent positions, fading into the background the older they are
+    fn render_trails(&self, renderer: &mut dyn Renderer) {
+        let palette = self.palette();
+        let background = self.background.unwrap_or(palette.background);
+        for (index, (trail, ball)) in self.trails.iter().zip(&self.balls).enumerate() {
+            let ball_colour = self.ball_kind(index).colour(palette.ball, background);
+            for (i, &(x, y)) in trail.iter().enumerate() {
+                let colour = palette::mix(background, ball_colour, 0.5 * (i + 1) as f32 / (trail.len() + 1) as f32);
+                renderer.fill_circle(x, y, ball.width, colour);
+            }
+        }
+    }
+
+    fn render_playfield(&self, renderer: &mut dyn Renderer) {
+        self.render_hud(renderer);
+        // The HUD stays still while the playfield shakes
+        let (shake_x, shake_y) = self.shake_offset;
+        self.render_objects(&mut Camera::new(renderer, shake_x, shake_y));
+        if let Some(count) = self.serve_countdown() {
+            let y = (self.config.window_height / 2).saturating_sub(COUNTDOWN_GAP);
+            menu::draw_centred(renderer, y, &count.to_string(), COUNTDOWN_SCALE, self.palette().hud);
+        }
+        if self.show_debug {
+            self.render_debug(renderer);
+        }
+    }
+
+    fn render_objects(&self, renderer: &mut dyn Renderer) {
+        let palette = self.palette();
+        for brick in &self.bricks {
+            // Bricks that need another hit are drawn dimmer until they crack, with a ring inside
+            // for each extra hit so they can be told apart without the colour
+            let colour = palette.bricks[brick.row % palette.bricks.len()];
+            let colour = if brick.hits_left > 1 { colour } else { colour | 0xFF202020 };
+            draw_object_colour(renderer, &brick.body, colour);
+            let ring_colour = palette::mix(colour, 0xFF000000, RING_DARKEN);
+            for ring in 1..brick.hits_left.min(MAX_RINGS + 1) {
+                draw_outline(renderer, &inset(&brick.body, ring as f32 * RING_SPACING), ring_colour);
+            }
+        }
+
+        // The boss, with how much health it has left along the bottom
+        if let Some(boss) = &self.boss {
+            let colour = palette.bricks[0];
+            draw_object_colour(renderer, &boss.body, colour);
+            let mut bar = inset(&boss.body, BOSS_BAR_MARGIN);
+            bar.y += bar.height - BOSS_BAR_HEIGHT;
+            bar.height = BOSS_BAR_HEIGHT;
+            draw_object_colour(renderer, &bar, palette::mix(colour, 0xFF000000, RING_DARKEN));
+            bar.width *= boss.health_left();
+            draw_object_colour(renderer, &bar, palette.hud);
+        }
+
+        // Obstacles, pickups and laser bolts
+        draw_sprites(&self.world, renderer);
+        if let Some(wall) = self.shield_wall() {
+            draw_object_colour(renderer, &wall, powerups::SHIELD_COLOUR);
+        }
+
+        // Render balls and paddles, the config's ball sprite is only for normal balls
+        let background = self.background.unwrap_or(palette.background);
+        self.render_trails(renderer);
+        for (i, drawn) in self.drawn_balls().iter().enumerate() {
+            let kind = self.ball_kind(i);
+            match self.ball_texture.as_ref().filter(|_| kind == BallKind::Normal) {
+                Some(texture) => renderer.draw_texture(drawn.x, drawn.y, drawn.width, drawn.height, texture),
+                None => renderer.fill_circle(drawn.x, drawn.y, drawn.width, kind.colour(palette.ball, background)),
+            }
+        }
+        for (i, paddle) in self.drawn_paddles().iter().enumerate() {
+            // Player 1's paddle is the bottom one, the first
+            let skin = self.skin_texture.as_ref().filter(|_| i == 0);
+            draw_object(renderer, paddle, skin.or(self.paddle_texture.as_ref()), palette.paddle);
+        }
+        self.particles.draw(renderer, background);
+        self.popups.draw(renderer, palette.hud, background);
+    }
+
+    // The balls and paddles where they are drawn, part way between the last two physics steps
+    // so movement stays smooth at any frame rate
+    pub(super) fn drawn_balls(&self) -> Vec<GameObject> {
+        let interpolate = self.prev_ball_positions.len() == self.balls.len();
+        let mut balls = self.balls.clone();
+        if interpolate {
+            for (drawn, &(prev_x, prev_y)) in balls.iter_mut().zip(&self.prev_ball_positions) {
+                drawn.x = lerp(prev_x, drawn.x, self.interpolation);
+                drawn.y = lerp(prev_y, drawn.y, self.interpolation);
+            }
+        }
+        balls
+    }
+
+    fn ball_kind(&self, index: usize) -> BallKind {
+        self.ball_kinds.get(index).copied().unwrap_or_default()
+    }
+
+    pub(super) fn drawn_paddles(&self) -> Vec<GameObject> {
+        let mut paddle = self.paddle.clone();
+        paddle.x = lerp(self.prev_paddle_x, self.paddle.x, self.interpolation);
+        let mut paddles = vec![paddle];
+        if self.rules().has_top_paddle() {
+            let mut top_paddle = self.top_paddle.clone();
+            top_paddle.x = lerp(self.prev_top_paddle_x, self.top_paddle.x, self.interpolation);
+            paddles.push(top_paddle);
+        }
+        paddles
+    }
+
+    // Where the physics has everything, without interpolation or shake: hitboxes, where each ball
+    // will be in a tenth of a second and the last few bounces, with the numbers along the bottom
+    fn render_debug(&self, renderer: &mut dyn Renderer) {
+        for brick in &self.bricks {
+            draw_outline(renderer, &brick.body, DEBUG_HITBOX_COLOUR);
+        }
+        for (entity, _) in self.world.colliders.iter() {
+            if let Some(body) = self.world.body(entity) {
+                draw_outline(renderer, &body, DEBUG_HITBOX_COLOUR);
+            }
+        }
+        draw_outline(renderer, &self.paddle, DEBUG_HITBOX_COLOUR);
+        if self.rules().has_top_paddle() {
+            draw_outline(renderer, &self.top_paddle, DEBUG_HITBOX_COLOUR);
+        }
+        for ball in &self.balls {
+            draw_outline(renderer, ball, DEBUG_HITBOX_COLOUR);
+            let (x, y) = ball.centre();
+            let (to_x, to_y) = (x + ball.vel_x * DEBUG_VECTOR_TIME, y + ball.vel_y * DEBUG_VECTOR_TIME);
+            draw_line(renderer, (x, y), (to_x, to_y), DEBUG_VECTOR_COLOUR);
+        }
+        for &(x, y) in &self.collision_points {
+            renderer.fill_rect(x - 3.0, y, 7.0, 1.0, DEBUG_COLLISION_COLOUR);
+            renderer.fill_rect(x, y - 3.0, 1.0, 7.0, DEBUG_COLLISION_COLOUR);
+        }
+
+        let speed = self.balls.first().map_or(0.0, |ball| (ball.vel_x * ball.vel_x + ball.vel_y * ball.vel_y).sqrt());
+        let velocity = self.balls.first().map_or((0.0, 0.0), |ball| (ball.vel_x, ball.vel_y));
+        let lines = [
+            format!(
+                "DT {:.1}MS  STEP {:.1}MS  LEFT OVER {:.0}%",
+                self.debug_frame_time * 1000.0,
+                PHYSICS_STEP * 1000.0,
+                self.interpolation * 100.0
+            ),
+            format!(
+                "SPEED {:.0} (X{:.2})  VEL {:.0},{:.0}  BALLS {}  HITS {}",
+                speed,
+                self.ball_speed / self.config.ball_speed,
+                velocity.0,
+                velocity.1,
+                self.balls.len(),
+                self.paddle_hits
+            ),
+            format!(
+                "PARTICLES {}  POPUPS {}  ENTITIES {}  BOLTS {}",
+                self.particles.stats(),
+                self.popups.stats(),
+                self.world.stats(),
+                self.lasers.count(&self.world)
+            ),
+        ];
+        for (i, line) in lines.iter().rev().enumerate() {
+            let y = self.config.window_height - hud::MARGIN - font::GLYPH_HEIGHT - i * (font::GLYPH_HEIGHT + 2);
+            renderer.draw_text(hud::MARGIN, y, line, 1, DEBUG_TEXT_COLOUR);
+        }
+    }
+
+    pub(super) fn render_game_over(&self, renderer: &mut dyn Renderer) {
+        let palette = self.palette();
+        renderer.clear(palette.background);
+        menu::draw_centred(renderer, 40, self.locale.get("game_over.title"), 6, palette.hud);
+        let result = match self.mode {
+            GameMode::Solo | GameMode::Breakout => self.locale.format("game_over.final_score", &[&self.scoreboard.score]),
+            GameMode::Survival => self.locale.format("game_over.survived", &[&format_seconds(self.scoreboard.score)]),
+            GameMode::TwoPlayer | GameMode::VsAi => self.winner_text().to_string(),
+            GameMode::Plugin(_) => self.summary(),
+        };
+        menu::draw_centred(renderer, 110, &result, 3, palette.hud);
+
+        if let Some(initials) = &self.initials {
+            // Pad with underscores so the player can see how many letters are left
+            let mut shown = initials.clone();
+            while shown.len() < INITIALS_LENGTH {
+                shown.push('_');
+            }
+            menu::draw_centred(renderer, 170, self.locale.get("game_over.new_high_score"), 2, palette.hud);
+            menu::draw_centred(renderer, 210, &shown, 4, palette.hud);
+            return;
+        }
+
+        if let Some(table) = self.score_table() {
+            // With an online leaderboard the global top 10 goes alongside this machine's
+            let width = self.config.window_width;
+            let heading = self.locale.get("game_over.high_scores");
+            match &self.leaderboard {
+                Some(leaderboard) => {
+                    self.render_score_table(renderer, width / 4, heading, &table.entries);
+                    let heading = self.locale.get("game_over.global_scores");
+                    let (entries, note) = match leaderboard.status() {
+                        Status::Ready(entries) => (&entries[..], None),
+                        Status::Loading => (&[][..], Some("game_over.loading")),
+                        Status::Offline => (&[][..], Some("game_over.offline")),
+                    };
+                    self.render_score_table(renderer, width * 3 / 4, heading, entries);
+                    if let Some(note) = note {
+                        menu::draw_centred_on(renderer, width * 3 / 4, 200, self.locale.get(note), 2, palette.hud_dim);
+                    }
+                }
+                None => self.render_score_table(renderer, width / 2, heading, &table.entries),
+            }
+        }
+        let quit = keybindings::key_name(self.config.keys.quit).to_uppercase();
+        let help = self.locale.format("game_over.help", &[&quit]);
+        menu::draw_centred(renderer, 480, &help, 2, palette.hud_dim);
+    }
+
+    // A high score table under its heading, centred on centre_x
+    fn render_score_table(&self, renderer: &mut dyn Renderer, centre_x: usize, heading: &str, entries: &[HighScore]) {
+        let palette = self.palette();
+        menu::draw_centred_on(renderer, centre_x, 170, heading, 2, palette.hud);
+        for (i, entry) in entries.iter().enumerate() {
+            let score = match self.mode {
+                GameMode::Survival => format_seconds(entry.score),
+                _ => entry.score.to_string(),
+            };
+            let line = format!(
+                "{:>2}. {:<3} {:>6}  {}",
+                i + 1,
+                entry.initials,
+                score,
+                highscores::format_date(entry.timestamp)
+            );
+            menu::draw_centred_on(renderer, centre_x, 200 + i * 24, &line, 2, palette.hud_dim);
+        }
+    }
+
+    fn render_hud(&self, renderer: &mut dyn Renderer) {
+        let (left, centre, corner) = match self.mode {
+            GameMode::Solo => (
+                self.score_text(),
+                self.locale.format("hud.high_score", &[&self.high_scores.best().max(self.scoreboard.score)]),
+                Corner::Lives(self.scoreboard.lives),
+            ),
+            GameMode::Breakout => {
+                (self.score_text(), self.locale.format("hud.level", &[&self.scoreboard.level]), Corner::Lives(self.scoreboard.lives))
+            }
+            GameMode::Survival => {
+                let speed = self.balls.iter().map(|ball| ball.vel_x.hypot(ball.vel_y)).fold(0.0, f32::max);
+                (
+                    self.locale.format("hud.time", &[&format_seconds(self.scoreboard.score)]),
+                    self.locale.format("hud.best_time", &[&format_seconds(self.survival_scores.best().max(self.scoreboard.score))]),
+                    Corner::Text(self.locale.format("hud.speed", &[&speed.round()])),
+                )
+            }
+            GameMode::TwoPlayer | GameMode::VsAi => {
+                let p2_key = if self.mode == GameMode::VsAi { "hud.cpu" } else { "hud.p2" };
+                (
+                    self.locale.format("hud.p1", &[&self.scoreboard.p1_score]),
+                    self.locale.format("hud.first_to", &[&modes::WIN_SCORE]),
+                    Corner::Text(self.locale.format(p2_key, &[&self.scoreboard.p2_score])),
+                )
+            }
+            // A registered mode's own summary, the HUD doesn't know what else it keeps score of
+            GameMode::Plugin(_) => {
+                let corner = if self.rules().has_lives() { Corner::Lives(self.scoreboard.lives) } else { Corner::Text(String::new()) };
+                (self.summary(), self.rules().name().to_uppercase(), corner)
+            }
+        };
+
+        // Notices like volume changes briefly replace the centre text
+        let centre = match &self.hud_message {
+            Some((message, shown_at)) if self.since(*shown_at) < HUD_MESSAGE_TIME => message.clone(),
+            _ => centre,
+        };
+
+        let effects: Vec<String> = self
+            .effects
+            .iter()
+            .map(|effect| format!("{} {}", self.locale.get(effect.kind.name_key()), effect.remaining.ceil()))
+            .collect();
+        let hud = Hud {
+            left,
+            centre,
+            corner,
+            forces: self.forces_text(),
+            effects: effects.join("  "),
+            ball_texture: self.ball_texture.as_ref(),
+        };
+        hud.draw(renderer, self.palette());
+    }
+
+    // The score, followed by the combo once there is one going
+    fn score_text(&self) -> String {
+        if self.combo < 2 {
+            return self.locale.format("hud.score", &[&self.scoreboard.score]);
+        }
+        self.locale.format("hud.score_combo", &[&self.scoreboard.score, &self.combo, &self.multiplier()])
+    }
+
+    // e.g. "GRAVITY 150  WIND < 40", an arrow showing which way the wind blows
+    fn forces_text(&self) -> String {
+        let (gravity, wind) = self.forces;
+        let mut parts = Vec::new();
+        if gravity != 0.0 {
+            parts.push(self.locale.format("hud.gravity", &[&gravity.round()]));
+        }
+        if wind != 0.0 {
+            let arrow = if wind < 0.0 { "<" } else { ">" };
+            parts.push(self.locale.format("hud.wind", &[&arrow, &wind.abs().round()]));
+        }
+        parts.join("  ")
+    }
+}
+
+// e.g. 1:05 for 65 seconds
+fn format_seconds(seconds: i32) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+fn draw_outline(renderer: &mut dyn Renderer, object: &GameObject, colour: u32) {
+    renderer.fill_rect(object.x, object.y, object.width, 1.0, colour);
+    renderer.fill_rect(object.x, object.y + object.height - 1.0, object.width, 1.0, colour);
+    renderer.fill_rect(object.x, object.y, 1.0, object.height, colour);
+    renderer.fill_rect(object.x + object.width - 1.0, object.y, 1.0, object.height, colour);
+}
+
+// The object shrunk by the same amount on every side
+fn inset(object: &GameObject, by: f32) -> GameObject {
+    GameObject {
+        x: object.x + by,
+        y: object.y + by,
+        width: (object.width - 2.0 * by).max(0.0),
+        height: (object.height - 2.0 * by).max(0.0),
+        vel_x: object.vel_x,
+        vel_y: object.vel_y,
+    }
+}
+
+// Diagonal stripes across an object, two pixels wide
+fn draw_hatching(renderer: &mut dyn Renderer, object: &GameObject, colour: u32) {
+    let (width, height) = (object.width as usize, object.height as usize);
+    for row in 0..height {
+        let mut column = (HATCH_SPACING - row % HATCH_SPACING) % HATCH_SPACING;
+        while column < width {
+            let stripe = (width - column).min(2) as f32;
+            renderer.fill_rect(object.x + column as f32, object.y + row as f32, stripe, 1.0, colour);
+            column += HATCH_SPACING;
+        }
+    }
+}
+
+// A line of single pixels, one for each pixel along its longer side
+fn draw_line(renderer: &mut dyn Renderer, from: (f32, f32), to: (f32, f32), colour: u32) {
+    let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs()).ceil().max(1.0);
+    for i in 0..=steps as usize {
+        let t = i as f32 / steps;
+        renderer.fill_rect(lerp(from.0, to.0, t), lerp(from.1, to.1, t), 1.0, 1.0, colour);
+    }
+}
+
+fn draw_object_colour(renderer: &mut dyn Renderer, object: &GameObject, colour: u32) {
+    renderer.fill_rect(object.x, object.y, object.width, object.height, colour);
+}
+
+// With its sprite stretched to fit when there is one, otherwise a plain rectangle blended in at its exact position
+fn draw_object(renderer: &mut dyn Renderer, object: &GameObject, texture: Option<&Texture>, colour: u32) {
+    match texture {
+        Some(texture) => renderer.draw_texture(object.x, object.y, object.width, object.height, texture),
+        None => renderer.fill_rect_smooth(object.x, object.y, object.width, object.height, colour),
+    }
+}
+
+// Sprite system: draw everything in the world that has somewhere to be drawn, with its symbol if it has one
+fn draw_sprites(world: &World, renderer: &mut dyn Renderer) {
+    for (entity, sprite) in world.sprites.iter() {
+        let Some(body) = world.body(entity) else {
+            continue;
+        };
+        draw_object_colour(renderer, &body, sprite.colour);
+        // Obstacles are striped so they don't look like bricks in any colour
+        if world.colliders.get(entity) == Some(&Collider::Solid) {
+            draw_hatching(renderer, &body, palette::mix(sprite.colour, 0xFF000000, RING_DARKEN));
+        }
+        let debuff = matches!(world.colliders.get(entity), Some(Collider::PowerUp(kind)) if kind.is_debuff());
+        if debuff {
+            draw_outline(renderer, &inset(&body, DEBUFF_RING_INSET), DEBUFF_MARK_COLOUR);
+        }
+        if let Some(symbol) = sprite.symbol {
+            let x = body.x as usize + (body.width as usize).saturating_sub(font::text_width(symbol, 2)) / 2;
+            let y = body.y as usize + (body.height as usize).saturating_sub(font::GLYPH_HEIGHT * 2) / 2;
+            renderer.draw_text(x, y, symbol, 2, if debuff { DEBUFF_MARK_COLOUR } else { 0xFF000000 });
+        }
+    }
+}
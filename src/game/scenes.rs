@@ -0,0 +1,167 @@
+// the screens of the game as a stack of scenes. the top scene gets the input each frame,
+// overlays like the pause screen are drawn over the scenes underneath them
+
+use super::{Game, GameState};
+use crate::renderer::Renderer;
+
+pub trait Scene {
+    // Which of the game's states this scene is, for code that only needs to know that much
+    fn state(&self) -> GameState;
+
+    fn handle_input(&self, game: &mut Game);
+
+    // Runs after handle_input(), for scenes that move things along every frame
+    fn update(&self, _game: &mut Game) {}
+
+    fn render(&self, game: &Game, renderer: &mut dyn Renderer);
+
+    // Overlays are drawn on top of the scene below rather than instead of it
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+pub(super) struct MenuScene;
+
+impl Scene for MenuScene {
+    fn state(&self) -> GameState {
+        GameState::Menu
+    }
+
+    fn handle_input(&self, game: &mut Game) {
+        game.update_menu();
+    }
+
+    fn render(&self, game: &Game, renderer: &mut dyn Renderer) {
+        game.render_menu(renderer);
+    }
+}
+
+// The list of keys, opened from the title screen
+pub(super) struct ControlsScene;
+
+impl Scene for ControlsScene {
+    fn state(&self) -> GameState {
+        GameState::Menu
+    }
+
+    fn handle_input(&self, game: &mut Game) {
+        game.update_info_screen();
+    }
+
+    fn render(&self, game: &Game, renderer: &mut dyn Renderer) {
+        game.render_controls(renderer);
+    }
+}
+
+// Totals over every game played, opened from the title screen
+pub(super) struct StatsScene;
+
+impl Scene for StatsScene {
+    fn state(&self) -> GameState {
+        GameState::Menu
+    }
+
+    fn handle_input(&self, game: &mut Game) {
+        game.update_info_screen();
+    }
+
+    fn render(&self, game: &Game, renderer: &mut dyn Renderer) {
+        game.render_stats(renderer);
+    }
+}
+
+// Coins spent on skins and power-ups, opened from the title screen
+pub(super) struct ShopScene;
+
+impl Scene for ShopScene {
+    fn state(&self) -> GameState {
+        GameState::Menu
+    }
+
+    fn handle_input(&self, game: &mut Game) {
+        game.update_shop();
+    }
+
+    fn render(&self, game: &Game, renderer: &mut dyn Renderer) {
+        game.render_shop(renderer);
+    }
+}
+
+pub(super) struct PlayScene;
+
+impl Scene for PlayScene {
+    fn state(&self) -> GameState {
+        GameState::Playing
+    }
+
+    fn handle_input(&self, game: &mut Game) {
+        game.process_input();
+    }
+
+    fn update(&self, game: &mut Game) {
+        game.update();
+    }
+
+    fn render(&self, game: &Game, renderer: &mut dyn Renderer) {
+        game.render_playing(renderer);
+    }
+}
+
+// A 1 player game with the tutorial's prompts over it
+pub(super) struct TutorialScene;
+
+impl Scene for TutorialScene {
+    fn state(&self) -> GameState {
+        GameState::Playing
+    }
+
+    fn handle_input(&self, game: &mut Game) {
+        game.update_tutorial();
+    }
+
+    fn update(&self, game: &mut Game) {
+        game.update();
+    }
+
+    fn render(&self, game: &Game, renderer: &mut dyn Renderer) {
+        game.render_playing(renderer);
+        game.render_tutorial(renderer);
+    }
+}
+
+pub(super) struct PauseScene;
+
+impl Scene for PauseScene {
+    fn state(&self) -> GameState {
+        GameState::Paused
+    }
+
+    fn handle_input(&self, game: &mut Game) {
+        game.update_paused();
+    }
+
+    fn render(&self, game: &Game, renderer: &mut dyn Renderer) {
+        game.render_paused(renderer);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
+
+pub(super) struct GameOverScene;
+
+impl Scene for GameOverScene {
+    fn state(&self) -> GameState {
+        GameState::GameOver
+    }
+
+    fn handle_input(&self, game: &mut Game) {
+        game.update_game_over();
+    }
+
+    fn render(&self, game: &Game, renderer: &mut dyn Renderer) {
+        game.render_game_over(renderer);
+    }
+}
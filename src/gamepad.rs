@@ -0,0 +1,130 @@
+// gamepad support using the linux joystick interface (/dev/input/js*)
+// pads are picked up and dropped as they are plugged in and out,
+// on other platforms no pads are ever found and the keyboard is used as before
+
+use crate::clock::Instant;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::time::Duration;
+
+const RESCAN_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_DEVICES: usize = 4;
+const DEAD_ZONE: f32 = 0.2; // Ignore small stick movements around the centre
+
+// Axis numbers as reported by the joystick driver for most pads
+const AXIS_LEFT_STICK_X: usize = 0;
+const AXIS_DPAD_X: usize = 6;
+const MAX_AXES: usize = 8;
+
+// Event types from linux/joystick.h
+const JS_EVENT_AXIS: u8 = 0x02;
+const JS_EVENT_INIT: u8 = 0x80;
+
+struct Device {
+    path: String,
+    file: File,
+    axes: [i16; MAX_AXES],
+}
+
+pub struct Gamepads {
+    devices: Vec<Device>,
+    last_scan: Option<Instant>,
+}
+
+impl Default for Gamepads {
+    fn default() -> Self {
+        Gamepads::new()
+    }
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        Gamepads {
+            devices: Vec::new(),
+            last_scan: None,
+        }
+    }
+
+    // Read pending events and look for newly connected pads, call once per frame
+    pub fn poll(&mut self) {
+        let rescan_due = self.last_scan.is_none_or(|at| at.elapsed() >= RESCAN_INTERVAL);
+        if rescan_due {
+            self.last_scan = Some(Instant::now());
+            self.scan();
+        }
+
+        self.devices.retain_mut(|device| {
+            let connected = device.read_events();
+            if !connected {
+                println!("Gamepad disconnected: {}", device.path);
+            }
+            connected
+        });
+    }
+
+    // Horizontal input from the given pad in the range -1.0 to 1.0, or None if it isn't connected
+    pub fn horizontal(&self, pad: usize) -> Option<f32> {
+        let device = self.devices.get(pad)?;
+
+        // A pressed d-pad wins over the stick
+        let dpad = device.axes[AXIS_DPAD_X];
+        if dpad != 0 {
+            return Some(dpad.signum() as f32);
+        }
+
+        let stick = device.axes[AXIS_LEFT_STICK_X] as f32 / i16::MAX as f32;
+        if stick.abs() < DEAD_ZONE {
+            return Some(0.0);
+        }
+        // Rescale so movement starts from zero just outside the dead zone
+        Some(stick.signum() * (stick.abs() - DEAD_ZONE) / (1.0 - DEAD_ZONE))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn scan(&mut self) {
+        use std::os::unix::fs::OpenOptionsExt;
+        const O_NONBLOCK: i32 = 0o4000;
+
+        for i in 0..MAX_DEVICES {
+            let path = format!("/dev/input/js{}", i);
+            if self.devices.iter().any(|device| device.path == path) {
+                continue;
+            }
+            let file = std::fs::OpenOptions::new().read(true).custom_flags(O_NONBLOCK).open(&path);
+            if let Ok(file) = file {
+                println!("Gamepad connected: {}", path);
+                self.devices.push(Device {
+                    path,
+                    file,
+                    axes: [0; MAX_AXES],
+                });
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn scan(&mut self) {}
+}
+
+impl Device {
+    // Apply all queued events, returns false once the pad has gone away
+    fn read_events(&mut self) -> bool {
+        let mut event = [0u8; 8];
+        loop {
+            match self.file.read(&mut event) {
+                Ok(8) => {
+                    // struct js_event { u32 time; i16 value; u8 type; u8 number; }
+                    let value = i16::from_le_bytes([event[4], event[5]]);
+                    let kind = event[6] & !JS_EVENT_INIT;
+                    let number = event[7] as usize;
+                    if kind == JS_EVENT_AXIS && number < MAX_AXES {
+                        self.axes[number] = value;
+                    }
+                }
+                Ok(_) => return false,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return true,
+                Err(_) => return false,
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+// top 10 tables of 1 player scores, saved as toml in the user's data directory, one file for each table.
+// each entry is its own [section] so the config parser can read the file back
+
+use crate::clock;
+use crate::config;
+use std::path::PathBuf;
+
+pub const MAX_ENTRIES: usize = 10;
+const FILE_NAME: &str = "highscores.toml";
+const SURVIVAL_FILE_NAME: &str = "survival_scores.toml"; // Seconds survived, kept apart from the 1 player scores
+
+pub struct HighScore {
+    pub initials: String,
+    pub score: i32,
+    pub timestamp: u64, // seconds since the unix epoch
+}
+
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+    path: Option<PathBuf>,
+}
+
+impl HighScores {
+    // Load the 1 player table, an unreadable or missing file just gives an empty table
+    pub fn load() -> Self {
+        HighScores::load_file(FILE_NAME)
+    }
+
+    // Load the survival mode table, the same way
+    pub fn load_survival() -> Self {
+        HighScores::load_file(SURVIVAL_FILE_NAME)
+    }
+
+    fn load_file(name: &str) -> Self {
+        let path = data_dir().map(|dir| dir.join(name));
+        let entries = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|text| parse_entries(&text))
+            .unwrap_or_default();
+        HighScores { entries, path }
+    }
+
+    pub fn best(&self) -> i32 {
+        self.entries.first().map_or(0, |entry| entry.score)
+    }
+
+    // Does this score earn a place in the table?
+    pub fn qualifies(&self, score: i32) -> bool {
+        score > 0 && (self.entries.len() < MAX_ENTRIES || self.entries.iter().any(|entry| score > entry.score))
+    }
+
+    // Add a score, keeping the table sorted and trimmed, and return its position
+    pub fn insert(&mut self, initials: &str, score: i32) -> usize {
+        let timestamp = clock::unix_time().as_secs();
+        // Later entries go below earlier ones with the same score
+        let position = self.entries.iter().position(|entry| score > entry.score).unwrap_or(self.entries.len());
+        self.entries.insert(
+            position,
+            HighScore {
+                initials: initials.to_string(),
+                score,
+                timestamp,
+            },
+        );
+        self.entries.truncate(MAX_ENTRIES);
+        position
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()), // Nowhere to save, e.g. no home directory
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let mut text = String::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            text.push_str(&format!(
+                "[entry{}]\ninitials = \"{}\"\nscore = {}\ntimestamp = {}\n\n",
+                i + 1,
+                entry.initials,
+                entry.score,
+                entry.timestamp
+            ));
+        }
+        std::fs::write(path, text)
+    }
+}
+
+pub fn parse_entries(text: &str) -> Vec<HighScore> {
+    let values = match config::parse(text) {
+        Ok(values) => values,
+        Err(e) => {
+            eprintln!("Could not read high scores: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut entries: Vec<HighScore> = (1..=MAX_ENTRIES)
+        .filter_map(|i| {
+            let get = |key: &str| values.get(&format!("entry{}.{}", i, key));
+            Some(HighScore {
+                initials: get("initials")?.as_str()?.to_string(),
+                score: get("score")?.as_int()? as i32,
+                timestamp: get("timestamp")?.as_int()? as u64,
+            })
+        })
+        .collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+    entries
+}
+
+// Per-user data directory following each platform's convention
+pub fn data_dir() -> Option<PathBuf> {
+    let env_dir = |name: &str| std::env::var_os(name).filter(|dir| !dir.is_empty()).map(PathBuf::from);
+
+    let base = if cfg!(windows) {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env_dir("XDG_DATA_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".local").join("share")))
+    };
+    base.map(|dir| dir.join("pingpong"))
+}
+
+// Format a unix timestamp as YYYY-MM-DD (UTC)
+pub fn format_date(timestamp: u64) -> String {
+    // Convert days since the epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (timestamp / 86_400) as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
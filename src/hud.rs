@@ -0,0 +1,74 @@
+// the heads-up display over the playfield. every mode puts things in the same places:
+// the score in the top left, the level or target in the middle, lives as ball icons in the top right,
+// the level's gravity and wind in the bottom left and running power-ups in the bottom right
+
+use crate::assets::Texture;
+use crate::font;
+use crate::palette::Palette;
+use crate::renderer::Renderer;
+
+pub const SCALE: usize = 2;
+pub const MARGIN: usize = 4;
+pub const HEIGHT: usize = MARGIN * 2 + font::GLYPH_HEIGHT * SCALE; // Of the strips along the top and bottom
+const ICON_SIZE: usize = font::GLYPH_HEIGHT * SCALE; // As tall as the text beside it
+const ICON_SPACING: usize = 4;
+const MAX_LIFE_ICONS: i32 = 8; // More lives than this show as one icon and a count
+
+// What goes in the top right corner
+pub enum Corner {
+    Lives(i32),
+    Text(String), // Modes without lives, e.g. the other player's score
+}
+
+pub struct Hud<'a> {
+    pub left: String,
+    pub centre: String,
+    pub corner: Corner,
+    pub forces: String,  // Gravity and wind, empty when there are none
+    pub effects: String, // Running power-ups with their seconds left
+    pub ball_texture: Option<&'a Texture>, // Life icons use the ball's sprite when it has one
+}
+
+impl Hud<'_> {
+    pub fn draw(&self, renderer: &mut dyn Renderer, palette: &Palette) {
+        let (field_width, field_height) = renderer.size();
+        let colour = palette.hud;
+        let centre_x = field_width.saturating_sub(font::text_width(&self.centre, SCALE)) / 2;
+        renderer.draw_text(MARGIN, MARGIN, &self.left, SCALE, colour);
+        renderer.draw_text(centre_x, MARGIN, &self.centre, SCALE, colour);
+
+        match &self.corner {
+            Corner::Lives(lives) => self.draw_lives(renderer, *lives, palette),
+            Corner::Text(text) => {
+                let x = field_width.saturating_sub(MARGIN + font::text_width(text, SCALE));
+                renderer.draw_text(x, MARGIN, text, SCALE, colour);
+            }
+        }
+
+        let x = field_width.saturating_sub(MARGIN + font::text_width(&self.effects, SCALE));
+        let y = field_height.saturating_sub(MARGIN + font::GLYPH_HEIGHT * SCALE);
+        renderer.draw_text(MARGIN, y, &self.forces, SCALE, colour);
+        renderer.draw_text(x, y, &self.effects, SCALE, colour);
+    }
+
+    // A row of icons ending at the right margin
+    fn draw_lives(&self, renderer: &mut dyn Renderer, lives: i32, palette: &Palette) {
+        let field_width = renderer.size().0;
+        let (icons, count) = if lives > MAX_LIFE_ICONS { (1, format!("X{}", lives)) } else { (lives.max(0), String::new()) };
+
+        let mut x = field_width.saturating_sub(MARGIN + font::text_width(&count, SCALE));
+        renderer.draw_text(x, MARGIN, &count, SCALE, palette.hud);
+        if !count.is_empty() {
+            x = x.saturating_sub(ICON_SPACING);
+        }
+        for _ in 0..icons {
+            x = x.saturating_sub(ICON_SIZE);
+            let size = ICON_SIZE as f32;
+            match self.ball_texture {
+                Some(texture) => renderer.draw_texture(x as f32, MARGIN as f32, size, size, texture),
+                None => renderer.fill_circle(x as f32, MARGIN as f32, size, palette.ball),
+            }
+            x = x.saturating_sub(ICON_SPACING);
+        }
+    }
+}
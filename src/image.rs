@@ -0,0 +1,303 @@
+// writes frames out as image files, PNG for screenshots and animated GIF for clips
+// the PNG is uncompressed (stored deflate blocks), which keeps the encoder tiny at the cost of file size
+
+use crate::clock;
+use crate::highscores;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+const MAX_STORED_BLOCK: usize = 65_535;
+
+// Encode 0xAARRGGBB pixels as an RGB PNG
+pub fn encode_png(pixels: &[u32], width: usize, height: usize) -> Vec<u8> {
+    // Each row starts with a filter type byte, 0 for none
+    let mut raw = Vec::with_capacity(height * (width * 3 + 1));
+    for row in pixels.chunks(width).take(height) {
+        raw.push(0);
+        for &pixel in row {
+            raw.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8]);
+        }
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    header.extend_from_slice(&[8, 2, 0, 0, 0]); // 8 bit depth, truecolour, default compression/filter, no interlace
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+pub fn save_png(path: &Path, pixels: &[u32], width: usize, height: usize) -> std::io::Result<()> {
+    std::fs::write(path, encode_png(pixels, width, height))
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+// Wrap data in a zlib stream without compressing it
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_STORED_BLOCK * 5 + 11);
+    out.extend_from_slice(&[0x78, 0x01]);
+
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]); // A single empty final block
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(is_final as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65_521;
+        b = (b + a) % 65_521;
+    }
+    (b << 16) | a
+}
+
+// Recent frames kept for GIF clips, shrunk and reduced to 256 colours as they come in
+// so a few seconds of them don't take up much memory
+pub struct Clip {
+    frames: VecDeque<Vec<u8>>,
+    max_frames: usize,
+    every: u32, // Keep one frame in this many
+    counter: u32,
+    width: usize,
+    height: usize,
+}
+
+const CLIP_SHRINK: usize = 2;
+
+impl Clip {
+    pub fn new(seconds: f32, frame_rate: f32, clip_rate: f32, field_width: usize, field_height: usize) -> Self {
+        Clip {
+            frames: VecDeque::new(),
+            max_frames: (seconds * clip_rate).ceil() as usize,
+            every: (frame_rate / clip_rate).round().max(1.0) as u32,
+            counter: 0,
+            width: field_width / CLIP_SHRINK,
+            height: field_height / CLIP_SHRINK,
+        }
+    }
+
+    pub fn push(&mut self, frame: &[u32], field_width: usize) {
+        self.counter += 1;
+        if self.counter < self.every {
+            return;
+        }
+        self.counter = 0;
+
+        let mut small = Vec::with_capacity(self.width * self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                small.push(palette_index(frame[y * CLIP_SHRINK * field_width + x * CLIP_SHRINK]));
+            }
+        }
+        if self.frames.len() == self.max_frames {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(small);
+    }
+
+    // Take a copy of the kept frames and return the encoder for them, each frame is shown for
+    // the given number of hundredths of a second. The encoder can be run on another thread
+    pub fn encode_gif_later(&self, delay: u16) -> impl FnOnce() -> Vec<u8> + Send + 'static {
+        let frames: Vec<Vec<u8>> = self.frames.iter().cloned().collect();
+        let (width, height) = (self.width, self.height);
+        move || {
+            let frames: Vec<&[u8]> = frames.iter().map(Vec::as_slice).collect();
+            encode_gif(&frames, width, height, delay)
+        }
+    }
+
+    // Seconds between kept frames in hundredths, as GIF frame delays are given
+    pub fn delay(&self, frame_rate: f32) -> u16 {
+        (100.0 * self.every as f32 / frame_rate).round() as u16
+    }
+}
+
+// The fixed GIF palette is a 6x7x6 colour cube, green gets the extra level as the eye is most sensitive to it
+const RED_LEVELS: u32 = 6;
+const GREEN_LEVELS: u32 = 7;
+const BLUE_LEVELS: u32 = 6;
+
+fn palette_index(pixel: u32) -> u8 {
+    let level = |value: u32, levels: u32| (value * (levels - 1) + 127) / 255;
+    let r = level((pixel >> 16) & 0xFF, RED_LEVELS);
+    let g = level((pixel >> 8) & 0xFF, GREEN_LEVELS);
+    let b = level(pixel & 0xFF, BLUE_LEVELS);
+    ((r * GREEN_LEVELS + g) * BLUE_LEVELS + b) as u8
+}
+
+fn palette_rgb() -> Vec<u8> {
+    let mut colours = Vec::with_capacity(256 * 3);
+    let value = |level: u32, levels: u32| (level * 255 / (levels - 1)) as u8;
+    for r in 0..RED_LEVELS {
+        for g in 0..GREEN_LEVELS {
+            for b in 0..BLUE_LEVELS {
+                colours.extend_from_slice(&[value(r, RED_LEVELS), value(g, GREEN_LEVELS), value(b, BLUE_LEVELS)]);
+            }
+        }
+    }
+    colours.resize(256 * 3, 0); // The last few entries are unused
+    colours
+}
+
+// Encode frames of palette indices as a looping animated GIF
+pub fn encode_gif(frames: &[&[u8]], width: usize, height: usize, delay: u16) -> Vec<u8> {
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend_from_slice(&(width as u16).to_le_bytes());
+    gif.extend_from_slice(&(height as u16).to_le_bytes());
+    gif.extend_from_slice(&[0xF7, 0, 0]); // Global colour table of 256 entries
+    gif.extend_from_slice(&palette_rgb());
+    // NETSCAPE2.0 extension, loop forever
+    gif.extend_from_slice(&[0x21, 0xFF, 0x0B]);
+    gif.extend_from_slice(b"NETSCAPE2.0");
+    gif.extend_from_slice(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+
+    for frame in frames {
+        // Graphic control extension with the frame delay
+        gif.extend_from_slice(&[0x21, 0xF9, 0x04, 0x00]);
+        gif.extend_from_slice(&delay.to_le_bytes());
+        gif.extend_from_slice(&[0x00, 0x00]);
+
+        // Image descriptor covering the whole canvas, no local colour table
+        gif.push(0x2C);
+        gif.extend_from_slice(&[0, 0, 0, 0]);
+        gif.extend_from_slice(&(width as u16).to_le_bytes());
+        gif.extend_from_slice(&(height as u16).to_le_bytes());
+        gif.push(0);
+
+        gif.push(GIF_MIN_CODE_SIZE);
+        for block in lzw_encode(frame).chunks(255) {
+            gif.push(block.len() as u8);
+            gif.extend_from_slice(block);
+        }
+        gif.push(0);
+    }
+    gif.push(0x3B);
+    gif
+}
+
+const GIF_MIN_CODE_SIZE: u8 = 8;
+const MAX_CODE: u16 = 4095;
+
+// GIF flavoured LZW, codes are packed least significant bit first and grow from 9 to 12 bits
+fn lzw_encode(indices: &[u8]) -> Vec<u8> {
+    let clear: u16 = 1 << GIF_MIN_CODE_SIZE;
+    let end: u16 = clear + 1;
+    let mut writer = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = end + 1;
+    let mut code_size = GIF_MIN_CODE_SIZE as u32 + 1;
+
+    writer.write(clear, code_size);
+    let mut pixels = indices.iter();
+    let mut current = match pixels.next() {
+        Some(&first) => first as u16,
+        None => {
+            writer.write(end, code_size);
+            return writer.finish();
+        }
+    };
+
+    for &pixel in pixels {
+        if let Some(&code) = table.get(&(current, pixel)) {
+            current = code;
+            continue;
+        }
+        writer.write(current, code_size);
+        if next_code <= MAX_CODE {
+            table.insert((current, pixel), next_code);
+            // The decoder widens its codes one entry later than we add them
+            if next_code == 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+            next_code += 1;
+        } else {
+            // Table full, start again
+            writer.write(clear, code_size);
+            table.clear();
+            next_code = end + 1;
+            code_size = GIF_MIN_CODE_SIZE as u32 + 1;
+        }
+        current = pixel as u16;
+    }
+    writer.write(current, code_size);
+    writer.write(end, code_size);
+    writer.finish()
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u32) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+// File name like screenshot-2024-05-01-134502.png for the current time (UTC)
+pub fn timestamped_name(prefix: &str, extension: &str) -> String {
+    let now = clock::unix_time().as_secs();
+    let seconds = now % 86_400;
+    format!(
+        "{}-{}-{:02}{:02}{:02}.{}",
+        prefix,
+        highscores::format_date(now),
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        extension
+    )
+}
@@ -0,0 +1,161 @@
+// where the game reads the keyboard and mouse from, every display backend provides this and
+// ScriptedInput stands in for a player in tests and tools, see Game::set_input.
+// keys are minifb's Key whatever the backend, so key bindings mean the same thing everywhere.
+// the game reads them through an InputState, updated once a frame
+
+#[cfg(target_arch = "wasm32")]
+mod keys;
+
+#[cfg(target_arch = "wasm32")]
+pub use keys::{Key, KeyRepeat};
+#[cfg(not(target_arch = "wasm32"))]
+pub use minifb::{Key, KeyRepeat};
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub trait InputSource {
+    // False once the player has closed the window
+    fn is_open(&self) -> bool;
+
+    fn is_key_down(&self, key: Key) -> bool;
+
+    // Pressed since the last frame, KeyRepeat::Yes also counts the repeats of a held key
+    fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool;
+
+    // Every key pressed since the last frame, not counting repeats
+    fn keys_pressed(&self) -> Vec<Key>;
+
+    // The same with the repeats of held keys
+    fn keys_repeated(&self) -> Vec<Key>;
+
+    fn keys_down(&self) -> Vec<Key>;
+
+    // The pointer in playfield coordinates, None for backends without a mouse
+    fn mouse_position(&self) -> Option<(f32, f32)> {
+        None
+    }
+}
+
+// The keys held this frame and the last, so each key going down or up is seen on exactly one frame
+// however the backend reports it. Toggles go by just_pressed, movement by is_down
+#[derive(Default)]
+pub struct InputState {
+    down: Vec<Key>,
+    last_down: Vec<Key>,
+    pressed: Vec<Key>,  // Presses the backend saw, for taps too quick to be seen held
+    repeated: Vec<Key>, // The same with the repeats of held keys
+}
+
+impl InputState {
+    // Call once a frame, before anything reads the keys
+    pub fn update(&mut self, input: &dyn InputSource) {
+        self.last_down = std::mem::replace(&mut self.down, input.keys_down());
+        self.pressed = input.keys_pressed();
+        self.repeated = input.keys_repeated();
+    }
+
+    pub fn is_down(&self, key: Key) -> bool {
+        self.down.contains(&key)
+    }
+
+    pub fn just_pressed(&self, key: Key) -> bool {
+        (self.is_down(key) && !self.last_down.contains(&key)) || self.pressed.contains(&key)
+    }
+
+    pub fn just_released(&self, key: Key) -> bool {
+        self.last_down.contains(&key) && !self.is_down(key)
+    }
+
+    // Pressed or repeating while held, for moving through menus
+    pub fn pressed_or_repeated(&self, key: Key) -> bool {
+        self.just_pressed(key) || self.repeated.contains(&key)
+    }
+
+    // Every key that went down this frame, in the order they were pressed where the backend knows it
+    pub fn just_pressed_keys(&self) -> Vec<Key> {
+        let mut keys = self.pressed.clone();
+        for &key in &self.down {
+            if !self.last_down.contains(&key) && !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+}
+
+// Keys held down and let go by code instead of a player. Clones share the same keys, so a test can keep one
+// to press keys with between frames while the game reads the other. A key held for a frame is a press,
+// there are no repeats
+#[derive(Clone, Default)]
+pub struct ScriptedInput {
+    state: Rc<RefCell<Scripted>>,
+}
+
+#[derive(Default)]
+struct Scripted {
+    down: Vec<Key>,
+    mouse: Option<(f32, f32)>,
+    closed: bool,
+}
+
+impl ScriptedInput {
+    pub fn new() -> Self {
+        ScriptedInput::default()
+    }
+
+    pub fn hold(&self, key: Key) {
+        let mut state = self.state.borrow_mut();
+        if !state.down.contains(&key) {
+            state.down.push(key);
+        }
+    }
+
+    pub fn release(&self, key: Key) {
+        self.state.borrow_mut().down.retain(|&down| down != key);
+    }
+
+    pub fn release_all(&self) {
+        self.state.borrow_mut().down.clear();
+    }
+
+    pub fn move_mouse(&self, x: f32, y: f32) {
+        self.state.borrow_mut().mouse = Some((x, y));
+    }
+
+    // As if the window had been closed
+    pub fn close(&self) {
+        self.state.borrow_mut().closed = true;
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn is_open(&self) -> bool {
+        !self.state.borrow().closed
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.state.borrow().down.contains(&key)
+    }
+
+    // InputState sees the keys go down from one frame to the next, a script has no taps quicker than that
+    fn is_key_pressed(&self, _key: Key, _repeat: KeyRepeat) -> bool {
+        false
+    }
+
+    fn keys_pressed(&self) -> Vec<Key> {
+        Vec::new()
+    }
+
+    fn keys_repeated(&self) -> Vec<Key> {
+        Vec::new()
+    }
+
+    fn keys_down(&self) -> Vec<Key> {
+        self.state.borrow().down.clone()
+    }
+
+    fn mouse_position(&self) -> Option<(f32, f32)> {
+        self.state.borrow().mouse
+    }
+}
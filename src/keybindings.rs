@@ -0,0 +1,126 @@
+// rebindable keys for the in-game actions, set in the [keys] section of pingpong.toml
+// keys are named as in minifb's Key enum, e.g. "Left", "A", "Space", "Key1" or "NumPad4"
+
+use crate::input::Key;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    P2MoveLeft,
+    P2MoveRight,
+    Pause,
+    Quit,
+    Serve,
+}
+
+pub const ACTIONS: [Action; 7] = [
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::P2MoveLeft,
+    Action::P2MoveRight,
+    Action::Pause,
+    Action::Quit,
+    Action::Serve,
+];
+
+impl Action {
+    // Name used for the action in the config file
+    pub fn config_name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::P2MoveLeft => "p2_move_left",
+            Action::P2MoveRight => "p2_move_right",
+            Action::Pause => "pause",
+            Action::Quit => "quit",
+            Action::Serve => "serve",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KeyBindings {
+    pub move_left: Key,
+    pub move_right: Key,
+    pub p2_move_left: Key,
+    pub p2_move_right: Key,
+    pub pause: Key,
+    pub quit: Key,
+    pub serve: Key,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            move_left: Key::Left,
+            move_right: Key::Right,
+            p2_move_left: Key::A,
+            p2_move_right: Key::D,
+            pause: Key::P,
+            quit: Key::Escape,
+            serve: Key::Space,
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn key(&self, action: Action) -> Key {
+        match action {
+            Action::MoveLeft => self.move_left,
+            Action::MoveRight => self.move_right,
+            Action::P2MoveLeft => self.p2_move_left,
+            Action::P2MoveRight => self.p2_move_right,
+            Action::Pause => self.pause,
+            Action::Quit => self.quit,
+            Action::Serve => self.serve,
+        }
+    }
+
+    // Bind an action from its config file name, returns false for an unknown action
+    pub fn set(&mut self, config_name: &str, key: Key) -> bool {
+        let action = match ACTIONS.iter().find(|action| action.config_name() == config_name) {
+            Some(&action) => action,
+            None => return false,
+        };
+        let slot = match action {
+            Action::MoveLeft => &mut self.move_left,
+            Action::MoveRight => &mut self.move_right,
+            Action::P2MoveLeft => &mut self.p2_move_left,
+            Action::P2MoveRight => &mut self.p2_move_right,
+            Action::Pause => &mut self.pause,
+            Action::Quit => &mut self.quit,
+            Action::Serve => &mut self.serve,
+        };
+        *slot = key;
+        true
+    }
+}
+
+// Every key minifb knows about, used to look keys up by name
+const ALL_KEYS: [Key; 106] = [
+    Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9,
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+    Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8, Key::F9, Key::F10, Key::F11, Key::F12,
+    Key::F13, Key::F14, Key::F15,
+    Key::Down, Key::Left, Key::Right, Key::Up, Key::Apostrophe, Key::Backquote,
+    Key::Backslash, Key::Comma, Key::Equal, Key::LeftBracket, Key::Minus, Key::Period, Key::RightBracket, Key::Semicolon,
+    Key::Slash, Key::Backspace, Key::Delete, Key::End, Key::Enter, Key::Escape,
+    Key::Home, Key::Insert, Key::Menu, Key::PageDown, Key::PageUp,
+    Key::Pause, Key::Space, Key::Tab, Key::NumLock, Key::CapsLock, Key::ScrollLock,
+    Key::LeftShift, Key::RightShift, Key::LeftCtrl, Key::RightCtrl,
+    Key::NumPad0, Key::NumPad1, Key::NumPad2, Key::NumPad3, Key::NumPad4, Key::NumPad5, Key::NumPad6, Key::NumPad7,
+    Key::NumPad8, Key::NumPad9, Key::NumPadDot, Key::NumPadSlash, Key::NumPadAsterisk, Key::NumPadMinus,
+    Key::NumPadPlus, Key::NumPadEnter,
+    Key::LeftAlt, Key::RightAlt, Key::LeftSuper, Key::RightSuper,
+];
+
+// Look a key up by its minifb name, ignoring case
+pub fn parse_key(name: &str) -> Option<Key> {
+    ALL_KEYS.iter().copied().find(|key| key_name(*key).eq_ignore_ascii_case(name))
+}
+
+pub fn key_name(key: Key) -> String {
+    format!("{:?}", key)
+}
@@ -0,0 +1,100 @@
+// the laser power-up's shots. while it runs, holding the serve key fires pairs of bolts up from the ends of
+// the bottom paddle, and each bolt knocks out the first brick or obstacle it reaches, however tough.
+// the bolts are entities in the world, moved and cleared away off the top with everything else there, and
+// the world reuses their slots as they come and go
+
+use crate::bricks::{self, Brick, Grid};
+use crate::ecs::{self, Collider, Entity, Position, Size, Sprite, Velocity, World};
+use crate::GameObject;
+
+const BOLT_WIDTH: f32 = 3.0;
+const BOLT_HEIGHT: f32 = 12.0;
+const BOLT_SPEED: f32 = 600.0; // pixels/second
+const RELOAD_TIME: f32 = 0.35; // seconds between pairs of bolts
+const GUN_INSET: f32 = 4.0; // How far in from each end of the paddle the bolts leave from
+pub const BOLT_COLOUR: u32 = 0xFFFF5050;
+
+#[derive(Default)]
+pub struct Lasers {
+    reload: f32,                          // Seconds until the paddle can fire again
+    in_flight: Vec<(Entity, GameObject)>, // The bolts being checked for hits, kept to save allocating each frame
+}
+
+impl Lasers {
+    // Fire a pair of bolts from the paddle unless it's still reloading, true if it fired
+    pub fn fire(&mut self, paddle: &GameObject, world: &mut World) -> bool {
+        if self.reload > 0.0 {
+            return false;
+        }
+        self.reload = RELOAD_TIME;
+        for x in [paddle.x + GUN_INSET, paddle.x + paddle.width - GUN_INSET - BOLT_WIDTH] {
+            spawn_bolt(world, x, paddle.y - BOLT_HEIGHT);
+        }
+        true
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.reload = (self.reload - delta_time).max(0.0);
+    }
+
+    // Knock out the bricks the bolts have reached, returning where each one was and its points.
+    // A bolt is used up by the brick it hits
+    pub fn hit_bricks(&mut self, world: &mut World, bricks: &mut Vec<Brick>, grid: &mut Grid) -> Vec<((f32, f32), i32)> {
+        let mut hits = Vec::new();
+        self.gather(world);
+        for (bolt, body) in &self.in_flight {
+            if let Some(hit) = bricks::shoot(body, bricks, grid) {
+                hits.push(hit);
+                world.despawn(*bolt);
+            }
+        }
+        hits
+    }
+
+    // Knock out the obstacles the bolts have reached, returning where each one was
+    pub fn hit_obstacles(&mut self, world: &mut World) -> Vec<(f32, f32)> {
+        let mut hits = Vec::new();
+        self.gather(world);
+        for &(bolt, ref body) in &self.in_flight {
+            let Some(&(obstacle, _)) = ecs::touching(world, body, Collider::Bolt.filter()).first() else {
+                continue;
+            };
+            if let Some(body) = world.body(obstacle) {
+                hits.push(body.centre());
+            }
+            world.despawn(obstacle);
+            world.despawn(bolt);
+        }
+        hits
+    }
+
+    // Bolts in flight go with the world, this only has the reload to forget
+    pub fn clear(&mut self) {
+        self.reload = 0.0;
+    }
+
+    // How many bolts are in the world
+    pub fn count(&self, world: &World) -> usize {
+        world.colliders.iter().filter(|&(_, collider)| *collider == Collider::Bolt).count()
+    }
+
+    fn gather(&mut self, world: &World) {
+        self.in_flight.clear();
+        for (bolt, collider) in world.colliders.iter() {
+            if *collider == Collider::Bolt {
+                self.in_flight.extend(world.body(bolt).map(|body| (bolt, body)));
+            }
+        }
+    }
+}
+
+// A bolt flying up from (x, y), its top left
+pub fn spawn_bolt(world: &mut World, x: f32, y: f32) -> Entity {
+    let bolt = world.spawn();
+    world.positions.insert(bolt, Position { x, y });
+    world.velocities.insert(bolt, Velocity { x: 0.0, y: -BOLT_SPEED });
+    world.sizes.insert(bolt, Size { width: BOLT_WIDTH, height: BOLT_HEIGHT });
+    world.sprites.insert(bolt, Sprite { colour: BOLT_COLOUR, symbol: None });
+    world.colliders.insert(bolt, Collider::Bolt);
+    bolt
+}
@@ -0,0 +1,208 @@
+// the online leaderboard, for players who set a server in the config. scores are sent signed with the
+// config's key so the server can tell they came from the game, and the global top 10 is fetched back
+// for the game over screen. requests go through the system's curl on another thread, which handles https
+// without the game needing a TLS library, and anything that goes wrong just leaves the board offline.
+//
+// the server is sent a POST to {url}/scores of a [score] section in the config's toml:
+//   initials, score, board ("solo" or "survival"), timestamp and signature,
+//   the HMAC-SHA256 of "board|initials|score|timestamp" with the key, in hex
+// and answers GET {url}/scores?board=solo with the top 10 in the same format as highscores.toml
+
+use crate::clock;
+use crate::highscores::{self, HighScore};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+const TIMEOUT_SECONDS: &str = "5";
+
+pub enum Status {
+    Loading,
+    Ready(Vec<HighScore>),
+    Offline,
+}
+
+pub struct Leaderboard {
+    url: String,
+    key: String,
+    status: Status,
+    pending: Option<Receiver<Result<Vec<HighScore>, String>>>, // The request on its way, if there is one
+}
+
+impl Leaderboard {
+    // None when the config doesn't name a server
+    pub fn new(url: Option<&str>, key: Option<&str>) -> Option<Self> {
+        Some(Leaderboard {
+            url: url?.trim_end_matches('/').to_string(),
+            key: key.unwrap_or_default().to_string(),
+            status: Status::Offline,
+            pending: None,
+        })
+    }
+
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+
+    // Fetch the board's top 10 in the background
+    pub fn fetch(&mut self, board: &str) {
+        let url = self.board_url(board);
+        self.send(move || get(&url));
+    }
+
+    // Send a score and then fetch the top 10 with it in, in the background
+    pub fn submit(&mut self, board: &str, initials: &str, score: i32) {
+        let timestamp = clock::unix_time().as_secs();
+        let message = format!("{}|{}|{}|{}", board, initials, score, timestamp);
+        let signature = to_hex(&hmac_sha256(self.key.as_bytes(), message.as_bytes()));
+        let body = format!(
+            "[score]\nboard = \"{}\"\ninitials = \"{}\"\nscore = {}\ntimestamp = {}\nsignature = \"{}\"\n",
+            board, initials, score, timestamp, signature
+        );
+        let (scores_url, board_url) = (format!("{}/scores", self.url), self.board_url(board));
+        self.send(move || post(&scores_url, &body).and_then(|_| get(&board_url)));
+    }
+
+    // Pick up the answer once it arrives, call once a frame
+    pub fn poll(&mut self) {
+        let Some(pending) = &self.pending else {
+            return;
+        };
+        let result = match pending.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err("request thread stopped".to_string()),
+        };
+        self.pending = None;
+        self.status = match result {
+            Ok(entries) => Status::Ready(entries),
+            Err(e) => {
+                eprintln!("Leaderboard offline: {}", e);
+                Status::Offline
+            }
+        };
+    }
+
+    fn board_url(&self, board: &str) -> String {
+        format!("{}/scores?board={}", self.url, board)
+    }
+
+    // Run a request on its own thread, replacing any still on its way
+    fn send(&mut self, request: impl FnOnce() -> Result<Vec<HighScore>, String> + Send + 'static) {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(request()); // The game may have stopped waiting for it
+        });
+        self.pending = Some(receiver);
+        self.status = Status::Loading;
+    }
+}
+
+fn get(url: &str) -> Result<Vec<HighScore>, String> {
+    let text = curl(&[url], None)?;
+    Ok(highscores::parse_entries(&text))
+}
+
+fn post(url: &str, body: &str) -> Result<Vec<HighScore>, String> {
+    let args = ["-X", "POST", "-H", "Content-Type: application/toml", "--data-binary", "@-", url];
+    curl(&args, Some(body))?;
+    Ok(Vec::new())
+}
+
+// Run curl quietly, failing on HTTP errors, and return what it downloaded
+fn curl(args: &[&str], body: Option<&str>) -> Result<String, String> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--max-time", TIMEOUT_SECONDS])
+        .args(args)
+        .stdin(if body.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not run curl: {}", e))?;
+    if let (Some(body), Some(mut stdin)) = (body, child.stdin.take()) {
+        stdin.write_all(body.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    String::from_utf8(output.stdout).map_err(|e| e.to_string())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// HMAC (RFC 2104) over SHA-256
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&sha256(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner: Vec<u8> = block.iter().map(|byte| byte ^ 0x36).collect();
+    inner.extend_from_slice(message);
+    let mut outer: Vec<u8> = block.iter().map(|byte| byte ^ 0x5c).collect();
+    outer.extend_from_slice(&sha256(&inner));
+    sha256(&outer)
+}
+
+// SHA-256 (FIPS 180-4)
+fn sha256(data: &[u8]) -> [u8; 32] {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5, 0xd807aa98,
+        0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786,
+        0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8,
+        0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13,
+        0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819,
+        0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a,
+        0x5b9cca4f, 0x682e6ff3, 0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+    let mut hash: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+
+    // Pad with a 1 bit, zeros and the length in bits to a whole number of 64 byte blocks
+    let mut padded = data.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in padded.chunks(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = hash;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let t1 = h.wrapping_add(s1).wrapping_add(choice).wrapping_add(K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(majority);
+            (h, g, f, e, d, c, b, a) = (g, f, e, d.wrapping_add(t1), c, b, a, t1.wrapping_add(t2));
+        }
+        for (value, add) in hash.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (bytes, value) in digest.chunks_mut(4).zip(hash) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
@@ -0,0 +1,266 @@
+// level files for breakout mode, a plain text header followed by the brick grid:
+//
+//   name = First Wall
+//   ball_speed = 250
+//   background = 0x101020
+//   gravity = 150
+//   wind = -40
+//   obstacle = 300 380 200 12 0xC06030
+//   obstacle = 100 300 60 12 sweep 500 4
+//   tileset = tiles/space.png 40
+//   tiles:
+//   0000
+//   01.2
+//   bricks:
+//   1111111111
+//   2.2.2.2.2.
+//
+// each character of the grid is one brick, a digit is how many hits it takes
+// and '.' leaves a gap. obstacles are solid blocks given as x y width height, optionally a colour
+// and optionally a path to follow: sweep DISTANCE SECONDS goes across and back,
+// orbit RADIUS SECONDS goes round in a circle about x y. the seconds are for the whole way round.
+// gravity pulls the ball down and wind pushes it sideways (to the left if negative), both in pixels/second².
+// anything after a # in the header is a comment.
+// a tileset is an atlas image, relative to the level file, cut into square tiles of the given size.
+// the rows under tiles: say which tile goes where, 0-9 then a-z, with '.' showing the background colour,
+// repeated to cover the field. a tileset with no tiles: section covers the field in its first tile.
+// an arena for the other modes is the same header with no bricks
+
+use crate::bricks::{self, Brick};
+use crate::ecs::Path;
+use crate::tiles::Tilemap;
+use crate::GameObject;
+use std::rc::Rc;
+
+pub const LEVELS_DIR: &str = "levels";
+const LEVEL_EXTENSION: &str = "txt";
+
+// A solid block the ball bounces off
+pub struct Obstacle {
+    pub body: GameObject,
+    pub colour: Option<u32>, // None for the usual grey
+    pub path: Option<Path>,  // None stays still
+}
+
+pub struct Level {
+    pub name: String,
+    pub ball_speed: Option<f32>, // None keeps the speed from the config
+    pub background: Option<u32>, // None uses the theme's background
+    pub tiles: Option<Rc<Tilemap>>, // Drawn over the background colour, shared with the game while it's played
+    pub gravity: f32,
+    pub wind: f32,
+    pub obstacles: Vec<Obstacle>,
+    grid: Vec<Vec<u32>>,
+}
+
+impl Level {
+    pub fn bricks(&self, field_width: f32) -> Vec<Brick> {
+        bricks::from_grid(&self.grid, field_width)
+    }
+
+    // dir is where the level file is, which its tileset is found relative to
+    pub fn parse(text: &str, dir: &std::path::Path) -> Result<Self, String> {
+        let level = Level::parse_lines(text, dir)?;
+        if level.grid.iter().flatten().all(|&hits| hits == 0) {
+            return Err("level has no bricks".to_string());
+        }
+        Ok(level)
+    }
+
+    // An arena's obstacles and background, for the modes without bricks
+    pub fn parse_arena(text: &str, dir: &std::path::Path) -> Result<Self, String> {
+        let level = Level::parse_lines(text, dir)?;
+        if !level.grid.is_empty() {
+            return Err("arenas can't have bricks".to_string());
+        }
+        Ok(level)
+    }
+
+    pub fn load_arena(path: &str) -> Result<Self, String> {
+        let dir = std::path::Path::new(path).parent().unwrap_or(std::path::Path::new(""));
+        std::fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|text| Level::parse_arena(&text, dir))
+    }
+
+    fn parse_lines(text: &str, dir: &std::path::Path) -> Result<Self, String> {
+        let mut level = Level {
+            name: String::new(),
+            ball_speed: None,
+            background: None,
+            tiles: None,
+            gravity: 0.0,
+            wind: 0.0,
+            obstacles: Vec::new(),
+            grid: Vec::new(),
+        };
+        let mut in_grid = false;
+        let mut in_tiles = false;
+        let mut tileset = None; // The atlas file and tile size
+        let mut tile_rows = Vec::new();
+
+        for (line_no, raw_line) in text.lines().enumerate() {
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| format!("line {}: {}", line_no + 1, message);
+
+            if in_grid {
+                let row = line
+                    .chars()
+                    .map(|c| match c {
+                        '.' => Some(0),
+                        _ => c.to_digit(10),
+                    })
+                    .collect::<Option<Vec<u32>>>()
+                    .ok_or_else(|| error("bricks must be digits or '.'"))?;
+                level.grid.push(row);
+                continue;
+            }
+            if line == "bricks:" {
+                in_grid = true;
+                continue;
+            }
+            if line == "tiles:" {
+                in_tiles = true;
+                continue;
+            }
+            if in_tiles {
+                let row = Tilemap::parse_row(line).ok_or_else(|| error("tiles must be 0-9, a-z or '.'"))?;
+                tile_rows.push(row);
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or_else(|| error("expected key = value"))?;
+            let value = value.split('#').next().unwrap_or_default().trim();
+            match key.trim() {
+                "name" => level.name = value.to_string(),
+                "ball_speed" => {
+                    let speed = value.parse::<f32>().ok().filter(|&s| s > 0.0);
+                    level.ball_speed = Some(speed.ok_or_else(|| error("invalid ball_speed"))?);
+                }
+                "background" => {
+                    let colour = parse_colour(value).ok_or_else(|| error("invalid background colour"))?;
+                    level.background = Some(colour);
+                }
+                "gravity" => level.gravity = value.parse().map_err(|_| error("invalid gravity"))?,
+                "wind" => level.wind = value.parse().map_err(|_| error("invalid wind"))?,
+                "obstacle" => {
+                    let obstacle = parse_obstacle(value).ok_or_else(|| error("obstacle needs x y width height [colour] [path]"))?;
+                    level.obstacles.push(obstacle);
+                }
+                "tileset" => {
+                    let parsed = value
+                        .rsplit_once(char::is_whitespace)
+                        .and_then(|(file, size)| Some((file.trim(), size.parse::<usize>().ok().filter(|&size| size > 0)?)));
+                    tileset = Some(parsed.ok_or_else(|| error("tileset needs an image file and a tile size"))?);
+                }
+                other => return Err(error(&format!("unknown setting {}", other))),
+            }
+        }
+
+        match tileset {
+            Some((file, size)) => {
+                let tilemap = Tilemap::load(&dir.join(file), size, tile_rows).map_err(|e| format!("tileset {}: {}", file, e))?;
+                level.tiles = Some(Rc::new(tilemap));
+            }
+            None if !tile_rows.is_empty() => return Err("tiles: needs a tileset".to_string()),
+            None => {}
+        }
+        Ok(level)
+    }
+}
+
+// 0xRRGGBB, or the same without the 0x
+fn parse_colour(value: &str) -> Option<u32> {
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    u32::from_str_radix(hex, 16).ok().filter(|&colour| colour <= 0xFFFFFF).map(|colour| 0xFF000000 | colour)
+}
+
+fn parse_obstacle(value: &str) -> Option<Obstacle> {
+    let fields: Vec<&str> = value.split_whitespace().collect();
+    let (numbers, rest) = fields.split_at_checked(4)?;
+    let (colour, path) = match rest {
+        [] => (None, None),
+        [colour] => (Some(parse_colour(colour)?), None),
+        [kind, a, b] => (None, Some(parse_path(kind, a, b)?)),
+        [colour, kind, a, b] => (Some(parse_colour(colour)?), Some(parse_path(kind, a, b)?)),
+        _ => return None,
+    };
+    let numbers = numbers.iter().map(|n| n.parse::<f32>().ok()).collect::<Option<Vec<f32>>>()?;
+    match numbers[..] {
+        [x, y, width, height] if width > 0.0 && height > 0.0 => Some(Obstacle {
+            body: GameObject {
+                x,
+                y,
+                width,
+                height,
+                vel_x: 0.0,
+                vel_y: 0.0,
+            },
+            colour,
+            path,
+        }),
+        _ => None,
+    }
+}
+
+fn parse_path(kind: &str, size: &str, period: &str) -> Option<Path> {
+    let size = size.parse::<f32>().ok()?;
+    let period = period.parse::<f32>().ok().filter(|&period| period > 0.0)?;
+    match kind {
+        "sweep" => Some(Path::Sweep { distance: size, period }),
+        "orbit" => Some(Path::Orbit { radius: size, period }),
+        _ => None,
+    }
+}
+
+// The levels of a campaign played in order, loaded from the .txt files in a directory sorted by name
+pub struct Campaign {
+    levels: Vec<Level>,
+    current: usize,
+}
+
+impl Campaign {
+    // Files that fail to parse are reported and skipped, None if no playable level was found
+    pub fn load(dir: &str) -> Option<Self> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .ok()?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == LEVEL_EXTENSION))
+            .collect();
+        paths.sort();
+
+        let mut levels = Vec::new();
+        for path in paths {
+            let parsed = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| Level::parse(&text, std::path::Path::new(dir)));
+            match parsed {
+                Ok(level) => levels.push(level),
+                Err(e) => eprintln!("{}: {}", path.display(), e),
+            }
+        }
+
+        if levels.is_empty() {
+            return None;
+        }
+        Some(Campaign { levels, current: 0 })
+    }
+
+    pub fn restart(&mut self) {
+        self.current = 0;
+    }
+
+    pub fn current(&self) -> &Level {
+        &self.levels[self.current]
+    }
+
+    // Move on to the next level, returns false once the campaign is over
+    pub fn advance(&mut self) -> bool {
+        if self.current + 1 >= self.levels.len() {
+            return false;
+        }
+        self.current += 1;
+        true
+    }
+}
@@ -0,0 +1,65 @@
+// simple pong like game engine, the game can be run in a minifb window, headless or in a browser (web.rs).
+// main.rs is the command line frontend, anything else can drive a Game the same way:
+// Game::new, then Game::step and Game::render (or render_into for plain pixels) once a frame
+// by maths.earth
+
+pub mod achievements;
+pub mod ai;
+pub mod assets;
+pub mod audio;
+pub mod balls;
+pub mod bands;
+pub mod boss;
+pub mod bricks;
+pub mod clock;
+pub mod collision;
+pub mod config;
+pub mod crt;
+pub mod daynight;
+pub mod diagnostics;
+pub mod difficulty;
+#[cfg(feature = "discord")]
+pub mod discord;
+pub mod ecs;
+pub mod error;
+pub mod events;
+pub mod font;
+mod game;
+pub mod gamepad;
+pub mod highscores;
+pub mod hud;
+pub mod image;
+pub mod input;
+pub mod keybindings;
+pub mod lasers;
+pub mod leaderboard;
+pub mod levels;
+pub mod locale;
+pub mod menu;
+pub mod modes;
+pub mod net;
+pub mod palette;
+pub mod particles;
+pub mod physics;
+pub mod pool;
+pub mod popups;
+pub mod powerups;
+pub mod profile;
+pub mod renderer;
+pub mod replay;
+pub mod rng;
+#[cfg(feature = "lua")]
+pub mod scripting;
+pub mod shop;
+pub mod skins;
+pub mod starfield;
+pub mod stats;
+pub mod tiles;
+pub mod tutorial;
+pub mod viewport;
+#[cfg(feature = "web")]
+pub mod web;
+
+pub use error::GameError;
+pub use game::{open_window, Game, GameMode, GameState, Scene, HEADLESS_FRAME_TIME};
+pub use physics::GameObject;
@@ -0,0 +1,102 @@
+// the words on screen, looked up by key in a language file so translations can be added without changing code.
+// the files are in locales/ named after the language, e.g. locales/fr.toml, with english built in
+// to fill in anything a translation leaves out. {} in a string is filled in with the values given, in order
+
+use crate::config;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+
+pub const LOCALES_DIR: &str = "locales";
+pub const DEFAULT_LANGUAGE: &str = "en";
+const ENGLISH: &str = include_str!("../locales/en.toml");
+
+pub struct Locale {
+    strings: HashMap<String, String>, // "section.key" to the text
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::english()
+    }
+}
+
+impl Locale {
+    pub fn english() -> Self {
+        let strings = parse_strings(ENGLISH).expect("built in english strings should parse");
+        Locale { strings }
+    }
+
+    // The config's language, or else the system's, with english for anything it hasn't got.
+    // Only a language that was asked for in the config is reported if it won't load
+    pub fn new(configured: Option<&str>) -> Self {
+        let mut locale = Locale::english();
+        let strings = match configured {
+            Some(language) => read_language(language).map_err(|e| eprintln!("Could not load language {}", e)).ok(),
+            None => system_language().and_then(|language| read_language(&language).ok()),
+        };
+        if let Some(strings) = strings {
+            locale.strings.extend(strings);
+        }
+        locale
+    }
+
+    // The text for a key, or the key itself if not even english has it so the gap is easy to spot
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings.get(key).map_or(key, String::as_str)
+    }
+
+    // The text for a key with each {} replaced by the next value
+    pub fn format(&self, key: &str, values: &[&dyn Display]) -> String {
+        let mut values = values.iter();
+        let mut parts = self.get(key).split("{}");
+        let mut text = parts.next().unwrap_or_default().to_string();
+        for part in parts {
+            if let Some(value) = values.next() {
+                text.push_str(&value.to_string());
+            }
+            text.push_str(part);
+        }
+        text
+    }
+
+    // A name used in the config, like a theme's, as it is called in this language.
+    // Names no language file mentions are shown as they are
+    pub fn name(&self, section: &str, name: &str) -> String {
+        match self.strings.get(&format!("{}.{}", section, name)) {
+            Some(text) => text.clone(),
+            None => name.to_uppercase(),
+        }
+    }
+}
+
+// e.g. "fr" from LANG=fr_FR.UTF-8
+fn system_language() -> Option<String> {
+    let value = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())?;
+    let language = value.split(['_', '.', '@']).next()?.to_lowercase();
+    // The C locale is what's set when nobody chose a language
+    Some(language).filter(|language| !language.is_empty() && language != "c" && language != "posix")
+}
+
+fn read_language(language: &str) -> Result<HashMap<String, String>, String> {
+    if language == DEFAULT_LANGUAGE {
+        return Ok(HashMap::new()); // Built in
+    }
+    let path = Path::new(LOCALES_DIR).join(format!("{}.toml", language));
+    std::fs::read_to_string(&path)
+        .map_err(|e| format!("{}: {}", path.display(), e))
+        .and_then(|text| parse_strings(&text))
+}
+
+fn parse_strings(text: &str) -> Result<HashMap<String, String>, String> {
+    config::parse(text)?
+        .into_iter()
+        .map(|(key, value)| match value.as_str() {
+            Some(text) => Ok((key, text.to_string())),
+            None => Err(format!("{} should be a string", key)),
+        })
+        .collect()
+}
@@ -1,228 +1,139 @@
 // simple pong like game with 3 lives
 // using minifb to render game space
 // by maths.earth
+// the game lives in the library (lib.rs), this is just the command line frontend
+
+mod cli;
+mod pacing;
+
+use cli::Args;
+use game_loop::config::{self, Config};
+use game_loop::net::Connection;
+use game_loop::renderer::{self, Display};
+#[cfg(feature = "terminal")]
+use game_loop::renderer::TerminalRenderer;
+use game_loop::replay::Replay;
+use game_loop::rng::Rng;
+use game_loop::{open_window, Game, GameError, GameMode, GameState, HEADLESS_FRAME_TIME};
+use pacing::FrameLimiter;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
 
-extern crate minifb;
-use minifb::{Key, Window, WindowOptions};
-use std::time::{Duration, Instant};
-
-// Constants for window dimensions and frame timing
-const WINDOW_WIDTH: usize = 800;
-const WINDOW_HEIGHT: usize = 600;
-const FRAME_TARGET_TIME: u64 = 16; // ~60 FPS
-const PAUSE_DURATION: Duration = Duration::from_secs(2);
-
-struct GameObject {
-    x: f32,
-    y: f32,
-    width: f32,
-    height: f32,
-    vel_x: f32,
-    vel_y: f32,
-}
-
-struct Game {
-    window: Window,
-    ball: GameObject,
-    paddle: GameObject,
-    last_frame_time: Instant,
-    game_is_running: bool,
-    lives: i32,
-    score: i32,
-    is_paused: bool,
-    pause_start: Option<Instant>,
-    ball_reset_pending: bool,
-}
-
-impl Game {
-    fn new() -> Self {
-        let window = Window::new(
-            "Game Window",
-            WINDOW_WIDTH,
-            WINDOW_HEIGHT,
-            WindowOptions::default(),
-        )
-        .unwrap_or_else(|e| {
-            panic!("Error creating window: {}", e);
-        });
-
-        let ball = GameObject {
-            x: 20.0,
-            y: 20.0,
-            width: 15.0,
-            height: 15.0,
-            vel_x: 300.0,
-            vel_y: 300.0,
-        };
-
-        let paddle = GameObject {
-            width: 100.0,
-            height: 20.0,
-            x: (WINDOW_WIDTH as f32 / 2.0) - 50.0,
-            y: WINDOW_HEIGHT as f32 - 40.0,
-            vel_x: 0.0,
-            vel_y: 0.0,
-        };
-
-        Game {
-            window,
-            ball,
-            paddle,
-            last_frame_time: Instant::now(),
-            game_is_running: true,
-            lives: 3,
-            score: 0,
-            is_paused: false,
-            pause_start: None,
-            ball_reset_pending: false,
+fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
         }
+    };
+    if args.help {
+        println!("{}", cli::USAGE);
+        return;
     }
 
-    fn process_input(&mut self) {
-        // Handle input for exiting the game
-        if self.window.is_key_down(Key::Escape) {
-            self.game_is_running = false;
-        }
-
-        // Handle paddle movement input
-        if !self.is_paused {
-            if self.window.is_key_down(Key::Left) {
-                self.paddle.vel_x = -400.0;
-            } else if self.window.is_key_down(Key::Right) {
-                self.paddle.vel_x = 400.0;
-            } else {
-                self.paddle.vel_x = 0.0;
-            }
-        }
+    if let Err(e) = run(args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
     }
+}
 
-    fn update(&mut self) {
-        // Handle pause state
-        if self.is_paused {
-            if let Some(start) = self.pause_start {
-                if start.elapsed() >= PAUSE_DURATION {
-                    self.is_paused = false;
-                    self.pause_start = None;
-                    self.ball_reset_pending = true;
-                    self.reset_ball();
-                } else {
-                    return;
-                }
-            }
-        }
-
-        // Ensure ball reset is handled before updating positions
-        if self.ball_reset_pending {
-            self.ball_reset_pending = false;
-            self.last_frame_time = Instant::now(); // Reset the frame time to avoid large delta time
-            return;
-        }
-
-        // Calculate delta time for consistent movement
-        let current_time = Instant::now();
-        let delta_time = (current_time - self.last_frame_time).as_secs_f32();
-        self.last_frame_time = current_time;
-
-        // Update ball and paddle positions
-        self.ball.x += self.ball.vel_x * delta_time;
-        self.ball.y += self.ball.vel_y * delta_time;
-        self.paddle.x += self.paddle.vel_x * delta_time;
-
-        // Handle ball collision with window boundaries
-        if self.ball.x <= 0.0 || self.ball.x + self.ball.width >= WINDOW_WIDTH as f32 {
-            self.ball.vel_x = -self.ball.vel_x;
-        }
-
-        if self.ball.y <= 0.0 {
-            self.ball.vel_y = -self.ball.vel_y;
-        }
+fn run(args: Args) -> Result<(), GameError> {
+    let config_path = args.config_path.clone().unwrap_or_else(|| config::CONFIG_FILE.to_string());
+    let mut config = Config::load(&config_path);
+    args.apply(&mut config);
+    for warning in config.validate() {
+        eprintln!("{}", warning);
+    }
+    // Print the seed so a run can be repeated with --seed
+    let seed = args.seed.unwrap_or_else(Rng::seed_from_time);
+    println!("Seed: {}", seed);
 
-        // Handle ball collision with paddle
-        if self.ball.y + self.ball.height >= self.paddle.y
-            && self.ball.x + self.ball.width >= self.paddle.x
-            && self.ball.x <= self.paddle.x + self.paddle.width
-        {
-            self.ball.vel_y = -self.ball.vel_y;
-            self.score += 1;
-        }
+    let replay = match &args.replay_path {
+        Some(path) => Some(Replay::load(Path::new(path)).map_err(|error| GameError::Replay { path: path.clone(), error })?),
+        None => None,
+    };
 
-        // Prevent paddle from moving out of window boundaries
-        if self.paddle.x <= 0.0 {
-            self.paddle.x = 0.0;
-        }
+    if args.headless {
+        return run_headless(config, args.mode.unwrap_or(GameMode::Solo), args.frames, seed, replay);
+    }
 
-        if self.paddle.x >= WINDOW_WIDTH as f32 - self.paddle.width {
-            self.paddle.x = WINDOW_WIDTH as f32 - self.paddle.width;
+    // Connect before opening the window, hosting waits here for the other player
+    let net = if let Some(port) = args.host_port {
+        Some(Connection::host(port, args.udp))
+    } else {
+        args.connect_address.as_deref().map(|address| Connection::connect(address, args.udp))
+    };
+    let net = net.transpose().map_err(|e| GameError::Network(e.to_string()))?;
+
+    let frame_time = Duration::from_millis(config.frame_target_time);
+    let display = if args.terminal { terminal_display(&config)? } else { open_window(&config)? };
+
+    // The game runs on a thread of its own at a steady rate, while this one shows its frames on the display
+    // (which has to stay on the main thread) and passes the input back
+    let (thread_display, presenter) = renderer::split(display, frame_time);
+    let mode = args.mode;
+    let game_thread = thread::spawn(move || {
+        let mut game = Game::with_display(config, Some(Box::new(thread_display)), seed);
+        game.watch_config(&config_path);
+
+        // A replay, network game or a mode on the command line skips the title screen and starts a game straight away
+        if let Some(net) = net {
+            game.start_network_game(net);
+        } else if let Some(replay) = replay {
+            game.start_replay(replay);
+        } else if let Some(mode) = mode {
+            game.start_game(mode);
         }
 
-        // Handle ball falling out of window (losing a life)
-        if self.ball.y + self.ball.height > WINDOW_HEIGHT as f32 {
-            self.lives -= 1;
-            if self.lives > 0 {
-                self.is_paused = true;
-                self.pause_start = Some(Instant::now());
-                // Move ball to a safe position off-screen before pausing
-                self.ball.x = WINDOW_WIDTH as f32 / 2.0 - self.ball.width / 2.0;
-                self.ball.y = WINDOW_HEIGHT as f32 / 2.0 - self.ball.height / 2.0;
-                self.ball.vel_x = 0.0;
-                self.ball.vel_y = 0.0;
-            } else {
-                self.game_is_running = false;
-            }
+        // Main game loop, each state handles its own input and update
+        let mut limiter = FrameLimiter::new(frame_time);
+        while game.is_running() {
+            game.step();
+            game.present()?;
+            limiter.wait();
         }
-    }
-
-    fn reset_ball(&mut self) {
-        // Reset ball position and velocity
-        self.ball.x = WINDOW_WIDTH as f32 / 2.0 - self.ball.width / 2.0;
-        self.ball.y = WINDOW_HEIGHT as f32 / 2.0 - self.ball.height / 2.0;
-        self.ball.vel_x = 300.0;
-        self.ball.vel_y = 300.0;
-    }
+        Ok(())
+    });
 
-    fn render(&mut self, buffer: &mut [u32]) {
-        // Clear the screen
-        for i in buffer.iter_mut() {
-            *i = 0;
-        }
+    let shown = presenter.run().map_err(GameError::Window);
+    let played = game_thread.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+    // The game stops with an error of its own when the display goes, so the display's error is the one to report
+    shown.and(played)
+}
 
-        // Render ball
-        for y in 0..self.ball.height as usize {
-            for x in 0..self.ball.width as usize {
-                let index = (self.ball.y as usize + y) * WINDOW_WIDTH + (self.ball.x as usize + x);
-                if index < buffer.len() {
-                    buffer[index] = 0xFFFFFFFF;
-                }
-            }
-        }
+#[cfg(feature = "terminal")]
+fn terminal_display(config: &Config) -> Result<Box<dyn Display>, GameError> {
+    let (width, height) = config.render_size();
+    let display = TerminalRenderer::new(width, height)
+        .map_err(|e| GameError::Terminal(e.to_string()))?;
+    Ok(Box::new(display))
+}
 
-        // Render paddle
-        for y in 0..self.paddle.height as usize {
-            for x in 0..self.paddle.width as usize {
-                let index = (self.paddle.y as usize + y) * WINDOW_WIDTH + (self.paddle.x as usize + x);
-                if index < buffer.len() {
-                    buffer[index] = 0xFFFFFFFF;
-                }
-            }
-        }
+#[cfg(not(feature = "terminal"))]
+fn terminal_display(_config: &Config) -> Result<Box<dyn Display>, GameError> {
+    Err(GameError::Terminal(
+        "this build can't play in the terminal, build it with: cargo build --features terminal".to_string(),
+    ))
+}
 
-        // Update window with buffer
-        self.window.update_with_buffer(&buffer, WINDOW_WIDTH, WINDOW_HEIGHT).unwrap();
+// Play a game with no window, sound or delays between frames, the CPU controls every paddle
+// unless a replay is given. Stops at game over or after the given number of frames and prints how it went
+fn run_headless(config: Config, mode: GameMode, frames: u64, seed: u64, replay: Option<Replay>) -> Result<(), GameError> {
+    let mut game = Game::new(config, true, seed)?;
+    match replay {
+        Some(replay) => game.start_replay(replay),
+        None => game.start_game(mode),
     }
-}
 
-fn main() {
-    let mut game = Game::new();
-    let mut buffer: Vec<u32> = vec![0; WINDOW_WIDTH * WINDOW_HEIGHT];
-
-    // Main game loop
-    while game.game_is_running && game.window.is_open() {
-        game.process_input();
-        game.update();
-        game.render(&mut buffer);
-        std::thread::sleep(Duration::from_millis(FRAME_TARGET_TIME));
+    let mut frame = 0;
+    while frame < frames && game.state() == GameState::Playing {
+        game.step();
+        frame += 1;
     }
 
-    println!("Game Over! Lives remaining: {}", game.lives);
-    println!("Final Score: {}", game.score);
+    println!("Headless run finished after {} frames ({:.1}s of game time)", frame, frame as f32 * HEADLESS_FRAME_TIME);
+    println!("{}", game.summary());
+    Ok(())
 }
@@ -0,0 +1,174 @@
+// title screen with a keyboard driven menu
+
+use crate::font;
+use crate::keybindings::{self, Action, KeyBindings};
+use crate::locale::Locale;
+use crate::modes::Modes;
+use crate::palette::Palette;
+use crate::renderer::Renderer;
+use crate::stats::Stats;
+use crate::GameMode;
+
+const TITLE_SCALE: usize = 6;
+const ITEM_SCALE: usize = 3;
+const ITEM_SPACING: usize = 32; // Closer together when registered modes make the menu longer
+const ITEMS_Y: usize = 200;
+
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MenuItem {
+    Play(GameMode),
+    Tutorial,
+    Difficulty, // Enter moves on to the next preset
+    Theme,      // ...and this on to the next colour theme
+    Skin,       // ...and this on to the next unlocked paddle skin
+    Shop,
+    Speed,      // ...and this through the assist speeds
+    Controls,
+    Stats,
+    Quit,
+}
+
+impl MenuItem {
+    fn label(self, locale: &Locale, settings: &Settings) -> String {
+        let key = match self {
+            MenuItem::Play(GameMode::Solo) => "menu.solo",
+            MenuItem::Play(GameMode::TwoPlayer) => "menu.two_player",
+            MenuItem::Play(GameMode::VsAi) => "menu.vs_cpu",
+            MenuItem::Play(GameMode::Breakout) => "menu.breakout",
+            MenuItem::Play(GameMode::Survival) => "menu.survival",
+            MenuItem::Play(mode @ GameMode::Plugin(_)) => return settings.modes.get(mode).name().to_uppercase(),
+            MenuItem::Tutorial => "menu.tutorial",
+            MenuItem::Difficulty => {
+                return locale.format("menu.difficulty", &[&locale.name("difficulty", settings.difficulty)])
+            }
+            MenuItem::Theme => return locale.format("menu.theme", &[&locale.name("theme", settings.theme)]),
+            MenuItem::Skin => return locale.format("menu.skin", &[&locale.name("skin", settings.skin)]),
+            MenuItem::Speed => return locale.format("menu.speed", &[&(settings.time_scale * 100.0).round()]),
+            MenuItem::Controls => "menu.controls",
+            MenuItem::Stats => "menu.stats",
+            MenuItem::Shop => "menu.shop",
+            MenuItem::Quit => "menu.quit",
+        };
+        locale.get(key).to_string()
+    }
+}
+
+const DEFAULT_ITEMS: [MenuItem; 14] = [
+    MenuItem::Play(GameMode::Solo),
+    MenuItem::Play(GameMode::TwoPlayer),
+    MenuItem::Play(GameMode::VsAi),
+    MenuItem::Play(GameMode::Breakout),
+    MenuItem::Play(GameMode::Survival),
+    MenuItem::Tutorial,
+    MenuItem::Difficulty,
+    MenuItem::Theme,
+    MenuItem::Skin,
+    MenuItem::Speed,
+    MenuItem::Controls,
+    MenuItem::Stats,
+    MenuItem::Shop,
+    MenuItem::Quit,
+];
+
+// The current settings, shown in the menu items that change them
+pub struct Settings<'a> {
+    pub difficulty: &'a str,
+    pub theme: &'a str,
+    pub skin: &'a str,
+    pub time_scale: f32,
+    pub modes: &'a Modes, // For the names of registered modes
+}
+
+pub struct Menu {
+    items: Vec<MenuItem>,
+    selected: usize,
+}
+
+impl Default for Menu {
+    fn default() -> Self {
+        Menu::new()
+    }
+}
+
+impl Menu {
+    pub fn new() -> Self {
+        Menu {
+            items: DEFAULT_ITEMS.to_vec(),
+            selected: 0,
+        }
+    }
+
+    // A registered mode goes after the built in ones
+    pub fn add_mode(&mut self, mode: GameMode) {
+        let after = self.items.iter().rposition(|item| matches!(item, MenuItem::Play(_))).map_or(0, |i| i + 1);
+        self.items.insert(after, MenuItem::Play(mode));
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+    }
+
+    pub fn move_down(&mut self) {
+        self.selected = (self.selected + 1) % self.items.len();
+    }
+
+    pub fn selected_item(&self) -> MenuItem {
+        self.items[self.selected]
+    }
+
+    pub fn render(&self, renderer: &mut dyn Renderer, palette: &Palette, locale: &Locale, settings: &Settings) {
+        draw_centred(renderer, 80, locale.get("menu.title"), TITLE_SCALE, palette.hud);
+        let room = renderer.size().1.saturating_sub(ITEMS_Y + font::GLYPH_HEIGHT * ITEM_SCALE);
+        let spacing = ITEM_SPACING.min(room / self.items.len().max(2).saturating_sub(1));
+        for (i, item) in self.items.iter().enumerate() {
+            let y = ITEMS_Y + i * spacing;
+            let label = item.label(locale, settings);
+            if i == self.selected {
+                draw_centred(renderer, y, &format!("> {} <", label), ITEM_SCALE, palette.hud);
+            } else {
+                draw_centred(renderer, y, &label, ITEM_SCALE, palette.hud_dim);
+            }
+        }
+    }
+}
+
+// The controls screen, built from the current bindings so rebound keys show up correctly
+pub fn render_controls(renderer: &mut dyn Renderer, keys: &KeyBindings, palette: &Palette, locale: &Locale) {
+    draw_centred(renderer, 80, locale.get("menu.title"), TITLE_SCALE, palette.hud);
+    let name = |action| keybindings::key_name(keys.key(action)).to_uppercase();
+    let lines = [
+        locale.format("controls.move", &[&name(Action::MoveLeft), &name(Action::MoveRight)]),
+        locale.format("controls.move_top", &[&name(Action::P2MoveLeft), &name(Action::P2MoveRight)]),
+        locale.format("controls.pause_quit", &[&name(Action::Pause), &name(Action::Quit)]),
+        locale.format("controls.serve", &[&name(Action::Serve)]),
+        locale.get("controls.choose").to_string(),
+        locale.get("controls.select").to_string(),
+        locale.get("controls.theme").to_string(),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        draw_centred(renderer, 220 + i * 30, line, 2, palette.hud);
+    }
+    draw_centred(renderer, 480, locale.get("menu.back"), 2, palette.hud_dim);
+}
+
+// Totals over every game played
+pub fn render_stats(renderer: &mut dyn Renderer, stats: &Stats, palette: &Palette, locale: &Locale) {
+    draw_centred(renderer, 80, locale.get("stats.title"), TITLE_SCALE, palette.hud);
+    for (i, line) in stats.lines(locale).iter().enumerate() {
+        draw_centred(renderer, 220 + i * 40, line, 3, palette.hud);
+    }
+    draw_centred(renderer, 480, locale.get("menu.back"), 2, palette.hud_dim);
+}
+
+// Draw a line of text centred horizontally at the given height
+pub fn draw_centred(renderer: &mut dyn Renderer, y: usize, text: &str, scale: usize, colour: u32) {
+    let centre_x = renderer.size().0 / 2;
+    draw_centred_on(renderer, centre_x, y, text, scale, colour);
+}
+
+// ...or centred on centre_x, for text in columns
+pub fn draw_centred_on(renderer: &mut dyn Renderer, centre_x: usize, y: usize, text: &str, scale: usize, colour: u32) {
+    let x = centre_x.saturating_sub(font::text_width(text, scale) / 2);
+    renderer.draw_text(x, y, text, scale, colour);
+}
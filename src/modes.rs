@@ -0,0 +1,290 @@
+// the rules of each game mode: what's on the field, how points are scored and when the game is over.
+// the built in modes are here, more can be registered at startup with Game::register_mode, or with the
+// plugins feature loaded from libraries in the plugins folder. the game asks Modes for a GameMode's rules
+// rather than matching on it, so a new variant only needs a Rules of its own
+
+use crate::physics;
+use crate::{GameMode, GameObject};
+#[cfg(feature = "plugins")]
+use libloading::Library;
+
+pub const WIN_SCORE: i32 = 5; // Points needed to win a two-player match
+const SURVIVAL_ACCELERATION: f32 = 8.0; // pixels/second² the ball speeds up by in survival mode
+pub const PLUGINS_DIR: &str = "plugins"; // Where the plugins feature looks for libraries with more modes
+#[cfg(feature = "plugins")]
+const REGISTER_SYMBOL: &str = "register_modes"; // Each plugin exports `fn register_modes(modes: &mut Modes)`
+
+// How the game is going, kept by the game and changed by the rules
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Scoreboard {
+    pub score: i32,
+    pub lives: i32,
+    pub level: u32,
+    pub p1_score: i32, // In the modes with a top paddle, player 1 is at the bottom
+    pub p2_score: i32,
+}
+
+// The end of the field a ball went out at
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum End {
+    Top,
+    Bottom,
+}
+
+// What losing a ball means
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+    Play,  // Carry on with the balls still in play
+    Serve, // Serve again, towards the end the ball went out at
+    GameOver,
+}
+
+// What the rules can see and change every step
+pub struct Field<'a> {
+    pub scoreboard: &'a mut Scoreboard,
+    pub balls: &'a mut [GameObject],
+    pub play_time: f32, // Seconds simulated this game
+    pub max_speed: f32, // Balls have to stay under this, pixels/second
+}
+
+pub trait Rules {
+    // Shown on the title screen for registered modes and to discord
+    fn name(&self) -> &str;
+
+    // A paddle at the top for player 2 or the CPU, without one the top of the field is a wall
+    fn has_top_paddle(&self) -> bool {
+        false
+    }
+
+    // Played with lives, power-ups and the combo multiplier
+    fn has_lives(&self) -> bool {
+        false
+    }
+
+    // Levels of bricks to clear instead of the arena
+    fn has_bricks(&self) -> bool {
+        false
+    }
+
+    // Points for each return off the bottom paddle, before the multiplier
+    fn return_points(&self) -> i32 {
+        0
+    }
+
+    // Called every physics step once the paddles have moved, before the balls do
+    fn update(&self, _field: Field, _delta_time: f32) {}
+
+    // A ball has gone out at one end, balls_left are still in play
+    fn ball_lost(&self, scoreboard: &mut Scoreboard, end: End, balls_left: usize) -> Outcome;
+
+    // One line on how the game is going, for headless runs
+    fn summary(&self, scoreboard: &Scoreboard) -> String;
+}
+
+// Losing the last ball in play costs a life
+fn lose_life(scoreboard: &mut Scoreboard, balls_left: usize) -> Outcome {
+    if balls_left > 0 {
+        return Outcome::Play;
+    }
+    scoreboard.lives -= 1;
+    if scoreboard.lives > 0 {
+        Outcome::Serve
+    } else {
+        Outcome::GameOver
+    }
+}
+
+// A ball past a paddle is a point for the other player, up to WIN_SCORE
+fn win_point(scoreboard: &mut Scoreboard, end: End) -> Outcome {
+    match end {
+        End::Bottom => scoreboard.p2_score += 1,
+        End::Top => scoreboard.p1_score += 1,
+    }
+    if scoreboard.p1_score >= WIN_SCORE || scoreboard.p2_score >= WIN_SCORE {
+        Outcome::GameOver
+    } else {
+        Outcome::Serve
+    }
+}
+
+struct Solo;
+
+impl Rules for Solo {
+    fn name(&self) -> &str {
+        "1 player"
+    }
+
+    fn has_lives(&self) -> bool {
+        true
+    }
+
+    fn return_points(&self) -> i32 {
+        1
+    }
+
+    fn ball_lost(&self, scoreboard: &mut Scoreboard, _end: End, balls_left: usize) -> Outcome {
+        lose_life(scoreboard, balls_left)
+    }
+
+    fn summary(&self, scoreboard: &Scoreboard) -> String {
+        format!("Score: {}  Lives: {}  Level: {}", scoreboard.score, scoreboard.lives, scoreboard.level)
+    }
+}
+
+// The top paddle is player 2's, or the CPU's in VS CPU
+struct TwoPlayer {
+    name: &'static str,
+    p2_name: &'static str,
+}
+
+impl Rules for TwoPlayer {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn has_top_paddle(&self) -> bool {
+        true
+    }
+
+    fn ball_lost(&self, scoreboard: &mut Scoreboard, end: End, _balls_left: usize) -> Outcome {
+        win_point(scoreboard, end)
+    }
+
+    fn summary(&self, scoreboard: &Scoreboard) -> String {
+        format!("P1 {} - {} {}", scoreboard.p1_score, scoreboard.p2_score, self.p2_name)
+    }
+}
+
+struct Breakout;
+
+impl Rules for Breakout {
+    fn name(&self) -> &str {
+        "Breakout"
+    }
+
+    fn has_lives(&self) -> bool {
+        true
+    }
+
+    fn has_bricks(&self) -> bool {
+        true
+    }
+
+    fn ball_lost(&self, scoreboard: &mut Scoreboard, _end: End, balls_left: usize) -> Outcome {
+        lose_life(scoreboard, balls_left)
+    }
+
+    fn summary(&self, scoreboard: &Scoreboard) -> String {
+        Solo.summary(scoreboard)
+    }
+}
+
+// One ball that never stops speeding up, scored in whole seconds kept in play
+struct Survival;
+
+impl Rules for Survival {
+    fn name(&self) -> &str {
+        "Survival"
+    }
+
+    fn update(&self, field: Field, delta_time: f32) {
+        field.scoreboard.score = field.play_time as i32;
+        for ball in field.balls.iter_mut() {
+            physics::accelerate(ball, SURVIVAL_ACCELERATION * delta_time, field.max_speed);
+        }
+    }
+
+    fn ball_lost(&self, _scoreboard: &mut Scoreboard, _end: End, _balls_left: usize) -> Outcome {
+        Outcome::GameOver
+    }
+
+    fn summary(&self, scoreboard: &Scoreboard) -> String {
+        format!("Survived: {}s", scoreboard.score)
+    }
+}
+
+const TWO_PLAYER: TwoPlayer = TwoPlayer { name: "2 players", p2_name: "PLAYER 2" };
+const VS_AI: TwoPlayer = TwoPlayer { name: "VS CPU", p2_name: "CPU" };
+
+// The built in modes and any registered at startup, which are GameMode::Plugin(index)
+pub struct Modes {
+    plugins: Vec<Box<dyn Rules>>,
+    #[cfg(feature = "plugins")]
+    libraries: Vec<Library>, // Dropped after the modes they registered, whose code is in them
+}
+
+impl Default for Modes {
+    fn default() -> Self {
+        Modes::new()
+    }
+}
+
+impl Modes {
+    pub fn new() -> Self {
+        Modes {
+            plugins: Vec::new(),
+            #[cfg(feature = "plugins")]
+            libraries: Vec::new(),
+        }
+    }
+
+    pub fn register(&mut self, rules: Box<dyn Rules>) -> GameMode {
+        self.plugins.push(rules);
+        GameMode::Plugin(self.plugins.len() - 1)
+    }
+
+    // The registered modes, in the order they were registered
+    pub fn plugins(&self) -> impl Iterator<Item = GameMode> {
+        (0..self.plugins.len()).map(GameMode::Plugin)
+    }
+
+    // False for a registered mode that isn't here, e.g. from a replay recorded with other plugins
+    pub fn contains(&self, mode: GameMode) -> bool {
+        match mode {
+            GameMode::Plugin(index) => index < self.plugins.len(),
+            _ => true,
+        }
+    }
+
+    pub fn get(&self, mode: GameMode) -> &dyn Rules {
+        match mode {
+            GameMode::Solo => &Solo,
+            GameMode::TwoPlayer => &TWO_PLAYER,
+            GameMode::VsAi => &VS_AI,
+            GameMode::Breakout => &Breakout,
+            GameMode::Survival => &Survival,
+            GameMode::Plugin(index) => self.plugins[index].as_ref(),
+        }
+    }
+
+    // Load every library in dir and let it register its modes. Plugins are Rust libraries
+    // (crate-type = ["cdylib"]) built against this crate with the same compiler
+    #[cfg(feature = "plugins")]
+    pub fn load_plugins(&mut self, dir: &str) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return; // No plugins folder is fine
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == std::env::consts::DLL_EXTENSION))
+            .collect();
+        paths.sort(); // Registered in the same order every time, replays refer to modes by it
+        for path in paths {
+            // SAFETY: a plugin is trusted like the game itself, and must be built with the same compiler
+            // and version of this crate for its register function and Rules to match ours
+            let loaded = unsafe {
+                Library::new(&path).and_then(|library| {
+                    let register = *library.get::<fn(&mut Modes)>(REGISTER_SYMBOL.as_bytes())?;
+                    Ok((library, register))
+                })
+            };
+            match loaded {
+                Ok((library, register)) => {
+                    register(self);
+                    self.libraries.push(library);
+                }
+                Err(e) => eprintln!("Could not load plugin {}: {}", path.display(), e),
+            }
+        }
+    }
+}
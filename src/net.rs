@@ -0,0 +1,315 @@
+// two player games over the network, one paddle per machine.
+// the host runs the game and plays the bottom paddle, the client plays the top paddle.
+// every frame the client sends its paddle speed and the host sends back the state of the game,
+// over TCP or, with --udp, over UDP where late or lost packets are simply skipped.
+// messages are single lines of text starting with a sequence number:
+//   <seq> I <paddle speed>
+//   <seq> S <last input seq> <paddle x> <paddle speed> <top paddle x> <p1 score> <p2 score> <game over 0/1>
+//           <ball count> (<x> <y> <vel x> <vel y>)...
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_PORT: u16 = 7878;
+const UDP_TIMEOUT: Duration = Duration::from_secs(5); // UDP has no hang up, silence this long counts as one
+const HANDSHAKE_RETRY: Duration = Duration::from_millis(500);
+const HANDSHAKE_ATTEMPTS: u32 = 20;
+const HELLO: &[u8] = b"HELLO";
+const WELCOME: &[u8] = b"WELCOME";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Role {
+    Host,
+    Client,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub input_seq: u32, // The last client input the host had when it sent this
+    pub paddle_x: f32,
+    pub paddle_vel: f32,
+    pub top_paddle_x: f32,
+    pub p1_score: i32,
+    pub p2_score: i32,
+    pub game_over: bool,
+    pub balls: Vec<[f32; 4]>, // x, y, vel x, vel y
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Input { paddle_vel: f32 },
+    State(Snapshot),
+}
+
+impl Message {
+    fn encode(&self, seq: u32) -> String {
+        match self {
+            Message::Input { paddle_vel } => format!("{} I {}\n", seq, paddle_vel),
+            Message::State(state) => {
+                let mut line = format!(
+                    "{} S {} {} {} {} {} {} {} {}",
+                    seq,
+                    state.input_seq,
+                    state.paddle_x,
+                    state.paddle_vel,
+                    state.top_paddle_x,
+                    state.p1_score,
+                    state.p2_score,
+                    state.game_over as u8,
+                    state.balls.len()
+                );
+                for ball in &state.balls {
+                    line.push_str(&format!(" {} {} {} {}", ball[0], ball[1], ball[2], ball[3]));
+                }
+                line.push('\n');
+                line
+            }
+        }
+    }
+
+    fn decode(line: &str) -> Option<(u32, Message)> {
+        let mut fields = line.split_whitespace();
+        let seq = fields.next()?.parse().ok()?;
+        let message = match fields.next()? {
+            "I" => Message::Input {
+                paddle_vel: fields.next()?.parse().ok()?,
+            },
+            "S" => {
+                let input_seq = fields.next()?.parse().ok()?;
+                let paddle_x = fields.next()?.parse().ok()?;
+                let paddle_vel = fields.next()?.parse().ok()?;
+                let top_paddle_x = fields.next()?.parse().ok()?;
+                let p1_score = fields.next()?.parse().ok()?;
+                let p2_score = fields.next()?.parse().ok()?;
+                let game_over = fields.next()? == "1";
+                let count: usize = fields.next()?.parse().ok()?;
+                let mut balls = Vec::with_capacity(count.min(16));
+                for _ in 0..count {
+                    let mut ball = [0.0; 4];
+                    for value in ball.iter_mut() {
+                        *value = fields.next()?.parse().ok()?;
+                    }
+                    balls.push(ball);
+                }
+                Message::State(Snapshot {
+                    input_seq,
+                    paddle_x,
+                    paddle_vel,
+                    top_paddle_x,
+                    p1_score,
+                    p2_score,
+                    game_over,
+                    balls,
+                })
+            }
+            _ => return None,
+        };
+        Some((seq, message))
+    }
+}
+
+enum Transport {
+    Tcp {
+        stream: TcpStream,
+        pending: Vec<u8>, // Received bytes that don't make up a whole line yet
+    },
+    Udp {
+        socket: UdpSocket,
+        peer: SocketAddr,
+        last_heard: Instant,
+    },
+}
+
+pub struct Connection {
+    pub role: Role,
+    transport: Transport,
+    next_seq: u32,
+    last_received_seq: u32, // Anything older than this arrived out of order and is dropped
+}
+
+impl Connection {
+    // Wait for the other player to connect, blocks until they do
+    pub fn host(port: u16, udp: bool) -> std::io::Result<Self> {
+        println!("Waiting for the other player on port {}...", port);
+        let transport = if udp {
+            let socket = UdpSocket::bind(("0.0.0.0", port))?;
+            let mut buffer = [0u8; 64];
+            let peer = loop {
+                let (len, from) = socket.recv_from(&mut buffer)?;
+                if &buffer[..len] == HELLO {
+                    break from;
+                }
+            };
+            socket.send_to(WELCOME, peer)?;
+            println!("Player connected from {}", peer);
+            udp_transport(socket, peer)?
+        } else {
+            let listener = TcpListener::bind(("0.0.0.0", port))?;
+            let (stream, address) = listener.accept()?;
+            println!("Player connected from {}", address);
+            tcp_transport(stream)?
+        };
+        Ok(Connection::new(transport, Role::Host))
+    }
+
+    // Address is host:port, or just the host to use the default port
+    pub fn connect(address: &str, udp: bool) -> std::io::Result<Self> {
+        let address = if address.contains(':') {
+            address.to_string()
+        } else {
+            format!("{}:{}", address, DEFAULT_PORT)
+        };
+        let transport = if udp {
+            let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+            socket.connect(&address)?;
+            let peer = socket.peer_addr()?;
+            udp_handshake(&socket)?;
+            udp_transport(socket, peer)?
+        } else {
+            tcp_transport(TcpStream::connect(&address)?)?
+        };
+        println!("Connected to {}", address);
+        Ok(Connection::new(transport, Role::Client))
+    }
+
+    fn new(transport: Transport, role: Role) -> Self {
+        Connection {
+            role,
+            transport,
+            next_seq: 1,
+            last_received_seq: 0,
+        }
+    }
+
+    // Returns the message's sequence number, or None once the connection has gone
+    pub fn send(&mut self, message: &Message) -> Option<u32> {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let bytes = message.encode(seq);
+        let sent = match &mut self.transport {
+            Transport::Tcp { stream, .. } => {
+                // The messages are tiny so a would-block here means the other end has stopped reading,
+                // switch to blocking for the write rather than lose part of a line
+                let _ = stream.set_nonblocking(false);
+                let sent = stream.write_all(bytes.as_bytes()).is_ok();
+                let _ = stream.set_nonblocking(true);
+                sent
+            }
+            // A datagram that doesn't make it is just lost, the next frame's message replaces it
+            Transport::Udp { socket, peer, .. } => match socket.send_to(bytes.as_bytes(), *peer) {
+                Ok(_) => true,
+                Err(e) => e.kind() == ErrorKind::WouldBlock,
+            },
+        };
+        sent.then_some(seq)
+    }
+
+    // Everything that has arrived since the last call in order, None once the connection has gone
+    pub fn receive(&mut self) -> Option<Vec<(u32, Message)>> {
+        let lines = match &mut self.transport {
+            Transport::Tcp { stream, pending } => receive_tcp(stream, pending)?,
+            Transport::Udp {
+                socket,
+                peer,
+                last_heard,
+            } => {
+                let lines = receive_udp(socket, *peer)?;
+                if !lines.is_empty() {
+                    *last_heard = Instant::now();
+                } else if last_heard.elapsed() > UDP_TIMEOUT {
+                    return None;
+                }
+                lines
+            }
+        };
+
+        let mut messages = Vec::new();
+        for (seq, message) in lines.iter().filter_map(|line| Message::decode(line)) {
+            if seq <= self.last_received_seq {
+                continue;
+            }
+            self.last_received_seq = seq;
+            messages.push((seq, message));
+        }
+        Some(messages)
+    }
+}
+
+fn tcp_transport(stream: TcpStream) -> std::io::Result<Transport> {
+    // Small messages every frame, send them straight away rather than batching them up
+    stream.set_nodelay(true)?;
+    stream.set_nonblocking(true)?;
+    Ok(Transport::Tcp {
+        stream,
+        pending: Vec::new(),
+    })
+}
+
+fn udp_transport(socket: UdpSocket, peer: SocketAddr) -> std::io::Result<Transport> {
+    socket.set_read_timeout(None)?;
+    socket.set_nonblocking(true)?;
+    Ok(Transport::Udp {
+        socket,
+        peer,
+        last_heard: Instant::now(),
+    })
+}
+
+// Keep saying hello until the host answers, in case the first few packets are lost
+fn udp_handshake(socket: &UdpSocket) -> std::io::Result<()> {
+    socket.set_read_timeout(Some(HANDSHAKE_RETRY))?;
+    let mut buffer = [0u8; 64];
+    for _ in 0..HANDSHAKE_ATTEMPTS {
+        socket.send(HELLO)?;
+        match socket.recv(&mut buffer) {
+            Ok(len) if &buffer[..len] == WELCOME => return Ok(()),
+            Ok(_) => {}
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Err(std::io::Error::new(ErrorKind::TimedOut, "no answer from the host"))
+}
+
+fn receive_tcp(stream: &mut TcpStream, pending: &mut Vec<u8>) -> Option<Vec<String>> {
+    let mut chunk = [0u8; 4096];
+    loop {
+        match stream.read(&mut chunk) {
+            Ok(0) => return None,
+            Ok(n) => pending.extend_from_slice(&chunk[..n]),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(_) => return None,
+        }
+    }
+
+    let mut lines = Vec::new();
+    while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+        let line: Vec<u8> = pending.drain(..=end).collect();
+        lines.push(String::from_utf8_lossy(&line).into_owned());
+    }
+    Some(lines)
+}
+
+fn receive_udp(socket: &UdpSocket, peer: SocketAddr) -> Option<Vec<String>> {
+    let mut lines = Vec::new();
+    let mut buffer = [0u8; 2048];
+    loop {
+        match socket.recv_from(&mut buffer) {
+            // A repeated hello is a client that missed our welcome, so it's sent again. Stray packets from anyone
+            // else are ignored
+            Ok((len, from)) if from == peer && &buffer[..len] == HELLO => {
+                let _ = socket.send_to(WELCOME, peer);
+            }
+            Ok((len, from)) if from == peer => {
+                lines.push(String::from_utf8_lossy(&buffer[..len]).into_owned());
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Some(lines),
+            // Windows reports an earlier send to a closed port here, the timeout notices a real hang up
+            Err(e) if e.kind() == ErrorKind::ConnectionReset => {}
+            Err(_) => return None,
+        }
+    }
+}
@@ -0,0 +1,43 @@
+// keeps frames a steady distance apart, however long each one took to run and draw.
+// the time left over is slept off apart from the last moment, which is spun through instead
+// because a sleep can wake up a millisecond or more late
+
+use std::time::{Duration, Instant};
+
+const SPIN_TIME: Duration = Duration::from_micros(1500);
+
+pub struct FrameLimiter {
+    frame_time: Duration,
+    next_frame: Instant, // When the next frame should start
+}
+
+impl FrameLimiter {
+    pub fn new(frame_time: Duration) -> Self {
+        FrameLimiter {
+            frame_time,
+            next_frame: Instant::now() + frame_time,
+        }
+    }
+
+    // Wait until it's time to start the next frame
+    pub fn wait(&mut self) {
+        let now = Instant::now();
+        if now >= self.next_frame + self.frame_time {
+            // A whole frame behind, e.g. while the window was being dragged, so start again from now
+            // rather than rushing through frames to catch up
+            self.next_frame = now + self.frame_time;
+            return;
+        }
+
+        if let Some(remaining) = self.next_frame.checked_duration_since(now) {
+            if remaining > SPIN_TIME {
+                std::thread::sleep(remaining - SPIN_TIME);
+            }
+            while Instant::now() < self.next_frame {
+                std::hint::spin_loop();
+            }
+        }
+        // Frames a little late start the next one sooner, so the average rate holds
+        self.next_frame += self.frame_time;
+    }
+}
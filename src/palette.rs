@@ -0,0 +1,110 @@
+// colour themes, picked with theme = "name" in the [window] section of pingpong.toml,
+// from the title screen or cycled in game with T.
+// the last few are for players who can't tell some colours apart, so they keep to colours that stay distinct
+
+#[derive(Clone, Copy, Debug)]
+pub struct Palette {
+    pub name: &'static str,
+    pub background: u32,
+    pub ball: u32,
+    pub paddle: u32,
+    pub hud: u32,     // HUD, titles and selected menu items
+    pub hud_dim: u32, // Help lines and unselected menu items
+    pub bricks: &'static [u32], // Brick colours by row from the top, starting over if there are more rows
+}
+
+const RAINBOW: [u32; 8] = [
+    0xFFE03030, 0xFFE07030, 0xFFE0C030, 0xFF30C030, 0xFF30C0C0, 0xFF3080E0, 0xFF8050E0, 0xFFC050C0,
+];
+
+pub const THEMES: [Palette; 8] = [
+    Palette {
+        name: "classic",
+        background: 0xFF000000,
+        ball: 0xFFFFFFFF,
+        paddle: 0xFFFFFFFF,
+        hud: 0xFFFFFFFF,
+        hud_dim: 0xFF808080,
+        bricks: &RAINBOW,
+    },
+    Palette {
+        name: "green",
+        background: 0xFF001000,
+        ball: 0xFF40FF40,
+        paddle: 0xFF30D030,
+        hud: 0xFF40FF40,
+        hud_dim: 0xFF208020,
+        bricks: &RAINBOW,
+    },
+    Palette {
+        name: "amber",
+        background: 0xFF100800,
+        ball: 0xFFFFC040,
+        paddle: 0xFFE0A020,
+        hud: 0xFFFFC040,
+        hud_dim: 0xFF906010,
+        bricks: &RAINBOW,
+    },
+    Palette {
+        name: "ocean",
+        background: 0xFF081828,
+        ball: 0xFFF0F0F0,
+        paddle: 0xFF40B0F0,
+        hud: 0xFFA0E0FF,
+        hud_dim: 0xFF4878A0,
+        bricks: &RAINBOW,
+    },
+    Palette {
+        name: "paper",
+        background: 0xFFF0ECE0,
+        ball: 0xFF202020,
+        paddle: 0xFF404040,
+        hud: 0xFF202020,
+        hud_dim: 0xFF909090,
+        bricks: &RAINBOW,
+    },
+    // Blues against oranges and yellows, which red-green colour blindness leaves apart
+    Palette {
+        name: "deuteranopia",
+        background: 0xFF000000,
+        ball: 0xFFFFFFFF,
+        paddle: 0xFF56B4E9,
+        hud: 0xFFFFFFFF,
+        hud_dim: 0xFF909090,
+        bricks: &[0xFFE69F00, 0xFF56B4E9, 0xFFF0E442, 0xFF0072B2, 0xFFCC79A7, 0xFF009E73],
+    },
+    // As above without the reds, which look dark to protanopes
+    Palette {
+        name: "protanopia",
+        background: 0xFF000000,
+        ball: 0xFFFFFFFF,
+        paddle: 0xFFF0E442,
+        hud: 0xFFFFFFFF,
+        hud_dim: 0xFF909090,
+        bricks: &[0xFFF0E442, 0xFF0072B2, 0xFFE69F00, 0xFF56B4E9, 0xFFFFFFFF, 0xFF009E73],
+    },
+    // Only white and yellow on black
+    Palette {
+        name: "contrast",
+        background: 0xFF000000,
+        ball: 0xFFFFFF00,
+        paddle: 0xFFFFFFFF,
+        hud: 0xFFFFFFFF,
+        hud_dim: 0xFFFFFF00,
+        bricks: &[0xFFFFFFFF, 0xFFFFFF00],
+    },
+];
+
+// Index of the theme with the given name, ignoring case
+pub fn find(name: &str) -> Option<usize> {
+    THEMES.iter().position(|theme| theme.name.eq_ignore_ascii_case(name))
+}
+
+// A colour part way from one to another, t from 0 to 1
+pub fn mix(from: u32, to: u32, t: f32) -> u32 {
+    let channel = |shift: u32| {
+        let (a, b) = (((from >> shift) & 0xFF) as f32, ((to >> shift) & 0xFF) as f32);
+        ((a + (b - a) * t).round() as u32) << shift
+    };
+    0xFF000000 | channel(16) | channel(8) | channel(0)
+}
@@ -0,0 +1,90 @@
+// little squares thrown out from a point that fall and fade away, for celebrating things like combos.
+// they are only for show, with random numbers of their own so they never change how a game plays out
+
+use crate::palette;
+use crate::pool::{Pool, PoolStats};
+use crate::renderer::Renderer;
+use crate::rng::Rng;
+
+const SEED: u64 = 0x5041525449434C45;
+const GRAVITY: f32 = 400.0; // pixels/second²
+const SIZE: f32 = 4.0;
+const LIFETIME: (f32, f32) = (0.4, 0.9); // Seconds, each particle lasts somewhere between these
+const SLOTS: usize = 512; // Made room for up front, a few bursts' worth
+
+struct Particle {
+    x: f32,
+    y: f32,
+    vel_x: f32,
+    vel_y: f32,
+    age: f32,
+    lifetime: f32,
+    colour: u32,
+}
+
+pub struct Particles {
+    particles: Pool<Particle>,
+    rng: Rng,
+}
+
+impl Default for Particles {
+    fn default() -> Self {
+        Particles::new()
+    }
+}
+
+impl Particles {
+    pub fn new() -> Self {
+        Particles {
+            particles: Pool::with_capacity(SLOTS),
+            rng: Rng::new(SEED),
+        }
+    }
+
+    // Throw count particles out from (x, y) in every direction, up to speed pixels a second
+    pub fn burst(&mut self, x: f32, y: f32, count: usize, speed: f32, colour: u32) {
+        for _ in 0..count {
+            let angle = self.rng.range(0.0, std::f32::consts::TAU);
+            let speed = self.rng.range(speed / 4.0, speed);
+            self.particles.spawn(Particle {
+                x,
+                y,
+                vel_x: speed * angle.cos(),
+                vel_y: speed * angle.sin(),
+                age: 0.0,
+                lifetime: self.rng.range(LIFETIME.0, LIFETIME.1),
+                colour,
+            });
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for particle in self.particles.iter_mut() {
+            particle.vel_y += GRAVITY * delta_time;
+            particle.x += particle.vel_x * delta_time;
+            particle.y += particle.vel_y * delta_time;
+            particle.age += delta_time;
+        }
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+    }
+
+    // Each fades into the background as it gets older
+    pub fn draw(&self, renderer: &mut dyn Renderer, background: u32) {
+        for particle in self.particles.iter() {
+            let colour = palette::mix(particle.colour, background, particle.age / particle.lifetime);
+            renderer.fill_rect(particle.x - SIZE / 2.0, particle.y - SIZE / 2.0, SIZE, SIZE, colour);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        self.particles.stats()
+    }
+}
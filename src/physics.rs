@@ -0,0 +1,109 @@
+// the rectangles everything in the game is made of, and ball physics helpers shared by the paddles.
+// these are pure functions of what they're given, so tests/physics_properties.rs can check them on their own
+
+use crate::collision;
+
+#[derive(Clone)]
+pub struct GameObject {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub vel_x: f32,
+    pub vel_y: f32,
+}
+
+impl GameObject {
+    pub fn centre(&self) -> (f32, f32) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+}
+
+// How many steps to move the ball in over time seconds, each no longer than max_travel so a fast ball
+// can't jump clean over a paddle, brick or wall between collision checks
+pub fn substeps(ball: &GameObject, time: f32, max_travel: f32) -> u32 {
+    // Without a limit to go by the whole time is one step
+    if max_travel <= 0.0 {
+        return 1;
+    }
+    let travel = ball.vel_x.abs().max(ball.vel_y.abs()) * time;
+    (travel / max_travel).ceil().max(1.0) as u32
+}
+
+// Move the ball for time seconds and bounce it off the side walls, returning true if it bounced.
+// It only turns round when heading into a wall, as an angled paddle bounce can leave it overlapping one,
+// and it's kept inside the field either way
+pub fn move_ball(ball: &mut GameObject, time: f32, field_width: f32) -> bool {
+    ball.x += ball.vel_x * time;
+    ball.y += ball.vel_y * time;
+    let bounced = (ball.x <= 0.0 && ball.vel_x < 0.0) || (ball.x + ball.width >= field_width && ball.vel_x > 0.0);
+    if bounced {
+        ball.vel_x = -ball.vel_x;
+    }
+    ball.x = ball.x.clamp(0.0, (field_width - ball.width).max(0.0));
+    bounced
+}
+
+// Whether the ball touches the paddle while heading into it, direction is the way paddle_bounce sends it off
+pub fn hits_paddle(ball: &GameObject, paddle: &GameObject, direction: f32) -> bool {
+    ball.vel_y * direction < 0.0 && collision::touches(ball, paddle)
+}
+
+// Keep the paddle between the side walls
+pub fn clamp_paddle(paddle: &mut GameObject, field_width: f32) {
+    paddle.x = paddle.x.min(field_width - paddle.width).max(0.0);
+}
+
+// Steepest angle away from straight up/down the ball can leave a paddle at
+const MAX_BOUNCE_ANGLE: f32 = 60.0 * std::f32::consts::PI / 180.0;
+
+// Send the ball off a paddle at an angle that depends on where it hit:
+// the centre sends it straight back, the edges send it off sharply to that side.
+// The ball keeps its speed, direction is -1.0 to go up the screen or 1.0 to go down
+pub fn paddle_bounce(ball: &mut GameObject, paddle: &GameObject, direction: f32) {
+    let ball_centre = ball.x + ball.width / 2.0;
+    let paddle_centre = paddle.x + paddle.width / 2.0;
+    let offset = ((ball_centre - paddle_centre) / (paddle.width / 2.0)).clamp(-1.0, 1.0);
+
+    let speed = (ball.vel_x * ball.vel_x + ball.vel_y * ball.vel_y).sqrt();
+    let angle = offset * MAX_BOUNCE_ANGLE;
+    ball.vel_x = speed * angle.sin();
+    ball.vel_y = direction * speed * angle.cos();
+}
+
+// Add some of the paddle's sideways speed to the ball so players can put "english" on it,
+// then cap the ball's overall speed so repeated spin can't make it uncatchable
+pub fn apply_spin(ball: &mut GameObject, paddle: &GameObject, transfer: f32, max_speed: f32) {
+    ball.vel_x += paddle.vel_x * transfer;
+    limit_speed(ball, max_speed);
+}
+
+// A copy of the ball heading angle radians round from where it was going, at the same speed
+pub fn turned(ball: &GameObject, angle: f32) -> GameObject {
+    let (sin, cos) = angle.sin_cos();
+    GameObject {
+        vel_x: ball.vel_x * cos - ball.vel_y * sin,
+        vel_y: ball.vel_x * sin + ball.vel_y * cos,
+        ..ball.clone()
+    }
+}
+
+// Speed the ball up by amount keeping its direction, no faster than max_speed
+pub fn accelerate(ball: &mut GameObject, amount: f32, max_speed: f32) {
+    let speed = ball.vel_x.hypot(ball.vel_y);
+    if speed > 0.0 {
+        let scale = (speed + amount).min(max_speed) / speed;
+        ball.vel_x *= scale;
+        ball.vel_y *= scale;
+    }
+}
+
+// Slow the ball down to max_speed if it's going faster, keeping its direction
+pub fn limit_speed(ball: &mut GameObject, max_speed: f32) {
+    let speed = (ball.vel_x * ball.vel_x + ball.vel_y * ball.vel_y).sqrt();
+    if speed > max_speed {
+        let scale = max_speed / speed;
+        ball.vel_x *= scale;
+        ball.vel_y *= scale;
+    }
+}
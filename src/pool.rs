@@ -0,0 +1,85 @@
+// storage for short-lived things that come and go every few frames, like particles and popups. the ones
+// in use are kept at the front, and one that's finished moves behind them where the next spawn reuses its
+// slot, so once a pool has grown to what a game needs nothing more is allocated for it
+
+use std::fmt;
+
+pub struct Pool<T> {
+    items: Vec<T>, // Those in use first, then finished ones waiting to be reused
+    live: usize,
+}
+
+// How full a pool is, for the debug overlay
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct PoolStats {
+    pub live: usize,
+    pub slots: usize, // In use or waiting to be reused, the most there have been at once
+}
+
+impl fmt::Display for PoolStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.live, self.slots)
+    }
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Pool { items: Vec::new(), live: 0 }
+    }
+}
+
+impl<T> Pool<T> {
+    // Room for this many before the pool has to grow
+    pub fn with_capacity(capacity: usize) -> Self {
+        Pool { items: Vec::with_capacity(capacity), live: 0 }
+    }
+
+    // Reuses the slot of a finished item if there is one
+    pub fn spawn(&mut self, item: T) {
+        if self.live < self.items.len() {
+            self.items[self.live] = item;
+        } else {
+            self.items.push(item);
+        }
+        self.live += 1;
+    }
+
+    // Finish the items keep returns false for. The last one in use takes each one's place, so the order
+    // isn't kept
+    pub fn retain(&mut self, mut keep: impl FnMut(&mut T) -> bool) {
+        let mut i = 0;
+        while i < self.live {
+            if keep(&mut self.items[i]) {
+                i += 1;
+            } else {
+                self.live -= 1;
+                self.items.swap(i, self.live);
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items[..self.live].iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.items[..self.live].iter_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self.live
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.live == 0
+    }
+
+    // Finish everything, keeping the slots
+    pub fn clear(&mut self) {
+        self.live = 0;
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        PoolStats { live: self.live, slots: self.items.len() }
+    }
+}
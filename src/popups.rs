@@ -0,0 +1,67 @@
+// short lived text in the playfield, like the "+10" that floats up from a brick as it breaks.
+// each one rises from where it was put and fades into the background
+
+use crate::font;
+use crate::palette;
+use crate::pool::{Pool, PoolStats};
+use crate::renderer::Renderer;
+
+const LIFETIME: f32 = 0.8; // Seconds
+const RISE_SPEED: f32 = 50.0; // pixels/second
+const SCALE: usize = 2;
+
+struct Popup {
+    text: String,
+    x: f32, // Centre of the text
+    y: f32,
+    age: f32,
+}
+
+#[derive(Default)]
+pub struct Popups {
+    popups: Pool<Popup>,
+}
+
+impl Popups {
+    pub fn new() -> Self {
+        Popups::default()
+    }
+
+    // Show the text centred on (x, y)
+    pub fn spawn(&mut self, x: f32, y: f32, text: String) {
+        self.popups.spawn(Popup { text, x, y, age: 0.0 });
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for popup in self.popups.iter_mut() {
+            popup.y -= RISE_SPEED * delta_time;
+            popup.age += delta_time;
+        }
+        self.popups.retain(|popup| popup.age < LIFETIME);
+    }
+
+    pub fn draw(&self, renderer: &mut dyn Renderer, colour: u32, background: u32) {
+        let height = (font::GLYPH_HEIGHT * SCALE) as f32;
+        for popup in self.popups.iter() {
+            let width = font::text_width(&popup.text, SCALE) as f32;
+            let (x, y) = (popup.x - width / 2.0, popup.y - height / 2.0);
+            if x < 0.0 || y < 0.0 {
+                continue;
+            }
+            let colour = palette::mix(colour, background, popup.age / LIFETIME);
+            renderer.draw_text(x as usize, y as usize, &popup.text, SCALE, colour);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.popups.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.popups.clear();
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        self.popups.stats()
+    }
+}
@@ -0,0 +1,250 @@
+// falling pickups that the bottom paddle can catch in the modes with lives, power-ups and the coins spent in the shop
+
+use crate::balls;
+use crate::lasers;
+use crate::ecs::{Collider, Entity, Position, Size, Sprite, Velocity, World};
+use crate::rng::Rng;
+
+const PICKUP_SIZE: f32 = 20.0;
+const FALL_SPEED: f32 = 150.0;
+const MIN_SPAWN_INTERVAL: f32 = 6.0; // seconds between pickups
+const MAX_SPAWN_INTERVAL: f32 = 10.0;
+const COIN_SIZE: f32 = 14.0;
+const COIN_COLOUR: u32 = 0xFFE0C020;
+pub const SHIELD_COLOUR: u32 = 0xFF70D8FF;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PowerUpKind {
+    WidePaddle,
+    SlowBall,
+    ExtraLife,
+    MultiBall,
+    HeavyBall, // These three turn the balls in play into another kind of ball
+    GhostBall,
+    SplitBall,
+    ShrinkPaddle, // These three are risky, they make the game harder for a while
+    InvertControls,
+    FastBall,
+    Shield, // A wall under the bottom paddle that sends back one ball it misses
+    Laser,
+    Magnet, // The bottom paddle catches a ball and holds it until the serve key launches it
+}
+
+// New kinds go on the end, replays store a power-up as where it is in here
+pub const KINDS: [PowerUpKind; 13] = [
+    PowerUpKind::WidePaddle,
+    PowerUpKind::SlowBall,
+    PowerUpKind::ExtraLife,
+    PowerUpKind::MultiBall,
+    PowerUpKind::HeavyBall,
+    PowerUpKind::GhostBall,
+    PowerUpKind::SplitBall,
+    PowerUpKind::ShrinkPaddle,
+    PowerUpKind::InvertControls,
+    PowerUpKind::FastBall,
+    PowerUpKind::Shield,
+    PowerUpKind::Laser,
+    PowerUpKind::Magnet,
+];
+
+impl PowerUpKind {
+    // How long the effect lasts in seconds, None for effects that happen once when caught
+    pub fn duration(self) -> Option<f32> {
+        match self {
+            PowerUpKind::WidePaddle => Some(10.0),
+            PowerUpKind::SlowBall => Some(8.0),
+            PowerUpKind::ShrinkPaddle | PowerUpKind::FastBall => Some(8.0),
+            PowerUpKind::InvertControls => Some(6.0),
+            PowerUpKind::Shield => Some(15.0), // Or until a ball bounces off it
+            PowerUpKind::Laser => Some(10.0),
+            PowerUpKind::Magnet => Some(12.0),
+            _ => None,
+        }
+    }
+
+    // Risky pickups are dark with a white letter and ring, so they stand out from the rest
+    pub fn is_debuff(self) -> bool {
+        matches!(self, PowerUpKind::ShrinkPaddle | PowerUpKind::InvertControls | PowerUpKind::FastBall)
+    }
+
+    pub fn colour(self) -> u32 {
+        match self {
+            PowerUpKind::WidePaddle => 0xFF3080FF,
+            PowerUpKind::SlowBall => 0xFF30C0C0,
+            PowerUpKind::ExtraLife => 0xFF30C030,
+            PowerUpKind::MultiBall => 0xFFE0A020,
+            PowerUpKind::HeavyBall => balls::HEAVY_COLOUR,
+            PowerUpKind::GhostBall => balls::GHOST_COLOUR,
+            PowerUpKind::SplitBall => balls::SPLIT_COLOUR,
+            PowerUpKind::ShrinkPaddle => 0xFF902028,
+            PowerUpKind::InvertControls => 0xFF602090,
+            PowerUpKind::FastBall => 0xFFA04010,
+            PowerUpKind::Shield => SHIELD_COLOUR,
+            PowerUpKind::Laser => lasers::BOLT_COLOUR,
+            PowerUpKind::Magnet => 0xFFC0C0C0,
+        }
+    }
+
+    // Letter drawn on the pickup
+    pub fn symbol(self) -> &'static str {
+        match self {
+            PowerUpKind::WidePaddle => "W",
+            PowerUpKind::SlowBall => "S",
+            PowerUpKind::ExtraLife => "+",
+            PowerUpKind::MultiBall => "M",
+            PowerUpKind::HeavyBall => "H",
+            PowerUpKind::GhostBall => "G",
+            PowerUpKind::SplitBall => "2",
+            PowerUpKind::ShrinkPaddle => "N",
+            PowerUpKind::InvertControls => "I",
+            PowerUpKind::FastBall => "F",
+            PowerUpKind::Shield => "=",
+            PowerUpKind::Laser => "L",
+            PowerUpKind::Magnet => "U",
+        }
+    }
+
+    // Name in the profile and scripts
+    pub fn key(self) -> &'static str {
+        match self {
+            PowerUpKind::WidePaddle => "wide",
+            PowerUpKind::SlowBall => "slow",
+            PowerUpKind::ExtraLife => "life",
+            PowerUpKind::MultiBall => "multi",
+            PowerUpKind::HeavyBall => "heavy",
+            PowerUpKind::GhostBall => "ghost",
+            PowerUpKind::SplitBall => "split",
+            PowerUpKind::ShrinkPaddle => "shrink",
+            PowerUpKind::InvertControls => "invert",
+            PowerUpKind::FastBall => "fast",
+            PowerUpKind::Shield => "shield",
+            PowerUpKind::Laser => "laser",
+            PowerUpKind::Magnet => "magnet",
+        }
+    }
+
+    // Key of the name shown on the HUD, in the language files
+    pub fn name_key(self) -> &'static str {
+        match self {
+            PowerUpKind::WidePaddle => "powerup.wide",
+            PowerUpKind::SlowBall => "powerup.slow",
+            PowerUpKind::ExtraLife => "powerup.life",
+            PowerUpKind::MultiBall => "powerup.multi",
+            PowerUpKind::HeavyBall => "powerup.heavy",
+            PowerUpKind::GhostBall => "powerup.ghost",
+            PowerUpKind::SplitBall => "powerup.split",
+            PowerUpKind::ShrinkPaddle => "powerup.shrink",
+            PowerUpKind::InvertControls => "powerup.invert",
+            PowerUpKind::FastBall => "powerup.fast",
+            PowerUpKind::Shield => "powerup.shield",
+            PowerUpKind::Laser => "powerup.laser",
+            PowerUpKind::Magnet => "powerup.magnet",
+        }
+    }
+}
+
+// A timed effect that is currently running
+pub struct ActiveEffect {
+    pub kind: PowerUpKind,
+    pub remaining: f32,
+}
+
+// Decides when the next pickup falls, what it is and where from
+pub struct Spawner {
+    timer: f32,
+    next_spawn: f32,
+}
+
+impl Default for Spawner {
+    fn default() -> Self {
+        Spawner::new()
+    }
+}
+
+impl Spawner {
+    pub fn new() -> Self {
+        Spawner {
+            timer: 0.0,
+            next_spawn: MAX_SPAWN_INTERVAL,
+        }
+    }
+
+    // Spawns a new pickup when one is due, dropped from somewhere across the field
+    pub fn update(&mut self, delta_time: f32, field_width: f32, rng: &mut Rng, world: &mut World) -> Option<Entity> {
+        self.timer += delta_time;
+        if self.timer < self.next_spawn {
+            return None;
+        }
+        self.timer = 0.0;
+        self.next_spawn = rng.range(MIN_SPAWN_INTERVAL, MAX_SPAWN_INTERVAL);
+
+        let kind = KINDS[rng.index(KINDS.len())];
+        let x = rng.range(0.0, field_width - PICKUP_SIZE);
+        Some(spawn_pickup(world, kind, x))
+    }
+}
+
+pub fn spawn_pickup(world: &mut World, kind: PowerUpKind, x: f32) -> Entity {
+    let pickup = world.spawn();
+    world.positions.insert(pickup, Position { x, y: 0.0 });
+    world.velocities.insert(pickup, Velocity { x: 0.0, y: FALL_SPEED });
+    world.sizes.insert(pickup, Size { width: PICKUP_SIZE, height: PICKUP_SIZE });
+    world.sprites.insert(
+        pickup,
+        Sprite {
+            colour: kind.colour(),
+            symbol: Some(kind.symbol()),
+        },
+    );
+    world.colliders.insert(pickup, Collider::PowerUp(kind));
+    pickup
+}
+
+// A coin falling from (x, y), its centre
+pub fn spawn_coin(world: &mut World, x: f32, y: f32) -> Entity {
+    let coin = world.spawn();
+    world.positions.insert(coin, Position { x: x - COIN_SIZE / 2.0, y: y - COIN_SIZE / 2.0 });
+    world.velocities.insert(coin, Velocity { x: 0.0, y: FALL_SPEED });
+    world.sizes.insert(coin, Size { width: COIN_SIZE, height: COIN_SIZE });
+    world.sprites.insert(
+        coin,
+        Sprite {
+            colour: COIN_COLOUR,
+            symbol: Some("C"),
+        },
+    );
+    world.colliders.insert(coin, Collider::Coin);
+    coin
+}
+
+// Start a timed effect, catching one that is already running restarts its timer
+pub fn activate(effects: &mut Vec<ActiveEffect>, kind: PowerUpKind) {
+    let duration = match kind.duration() {
+        Some(duration) => duration,
+        None => return,
+    };
+    match effects.iter_mut().find(|effect| effect.kind == kind) {
+        Some(effect) => effect.remaining = duration,
+        None => effects.push(ActiveEffect {
+            kind,
+            remaining: duration,
+        }),
+    }
+}
+
+// Count down the running effects and drop the ones that have run out
+pub fn tick(effects: &mut Vec<ActiveEffect>, delta_time: f32) {
+    for effect in effects.iter_mut() {
+        effect.remaining -= delta_time;
+    }
+    effects.retain(|effect| effect.remaining > 0.0);
+}
+
+// Stop a timed effect early
+pub fn deactivate(effects: &mut Vec<ActiveEffect>, kind: PowerUpKind) {
+    effects.retain(|effect| effect.kind != kind);
+}
+
+pub fn is_active(effects: &[ActiveEffect], kind: PowerUpKind) -> bool {
+    effects.iter().any(|effect| effect.kind == kind)
+}
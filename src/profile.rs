@@ -0,0 +1,188 @@
+// what the player has earned across games, kept in profile.toml in the user's data directory: the best score,
+// which unlocks paddle skins, the skin picked on the title screen, the coins caught for the shop and what they bought.
+// it's saved as soon as any of that changes
+
+use crate::config;
+use crate::highscores;
+use crate::powerups::{self, PowerUpKind};
+use crate::skins::{Skin, SKINS};
+use std::path::PathBuf;
+
+const FILE_NAME: &str = "profile.toml";
+
+#[derive(Default)]
+pub struct Profile {
+    best_score: i32, // Most points scored in one game, which decides the skins unlocked
+    selected: usize, // Index into SKINS
+    coins: u32,
+    bought: Vec<usize>,            // Skins bought in the shop, indices into SKINS
+    stock: Vec<(PowerUpKind, u32)>, // Power-ups bought for the start of the next game, with how many of each
+    path: Option<PathBuf>,
+}
+
+impl Profile {
+    // Load the profile, an unreadable or missing file starts with only the classic skin and no coins
+    pub fn load() -> Self {
+        let path = highscores::data_dir().map(|dir| dir.join(FILE_NAME));
+        let mut profile = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|text| parse_profile(&text))
+            .unwrap_or_default();
+        if !profile.is_unlocked(profile.selected) {
+            profile.selected = 0;
+        }
+        profile.path = path;
+        profile
+    }
+
+    pub fn is_unlocked(&self, skin: usize) -> bool {
+        SKINS.get(skin).is_some_and(|found| found.score <= self.best_score) || self.bought.contains(&skin)
+    }
+
+    pub fn selected(&self) -> &'static Skin {
+        &SKINS[self.selected]
+    }
+
+    // On to the next unlocked skin, back to classic after the last
+    pub fn next_skin(&mut self) {
+        self.selected = (self.selected + 1..SKINS.len()).find(|&skin| self.is_unlocked(skin)).unwrap_or(0);
+        self.save_or_report();
+    }
+
+    // The score so far in a game, returning the skins it unlocked. Saved straight away when it unlocks any,
+    // the best score only matters for reaching the next one
+    pub fn record_score(&mut self, score: i32) -> Vec<&'static Skin> {
+        if score <= self.best_score {
+            return Vec::new();
+        }
+        let unlocked: Vec<_> = (0..SKINS.len())
+            .filter(|&skin| !self.is_unlocked(skin) && SKINS[skin].score <= score)
+            .map(|skin| &SKINS[skin])
+            .collect();
+        self.best_score = score;
+        if !unlocked.is_empty() {
+            self.save_or_report();
+        }
+        unlocked
+    }
+
+    pub fn coins(&self) -> u32 {
+        self.coins
+    }
+
+    pub fn add_coins(&mut self, coins: u32) {
+        self.coins += coins;
+        self.save_or_report();
+    }
+
+    // Pay for something if there are enough coins, true once paid
+    fn spend(&mut self, price: u32) -> bool {
+        if price > self.coins {
+            return false;
+        }
+        self.coins -= price;
+        true
+    }
+
+    // Buy a skin that isn't unlocked yet, true if it was bought
+    pub fn buy_skin(&mut self, skin: usize) -> bool {
+        if self.is_unlocked(skin) || !self.spend(SKINS[skin].price) {
+            return false;
+        }
+        self.bought.push(skin);
+        self.save_or_report();
+        true
+    }
+
+    // Buy one of a power-up for the start of the next game, true if it was bought
+    pub fn buy_power_up(&mut self, kind: PowerUpKind, price: u32) -> bool {
+        if !self.spend(price) {
+            return false;
+        }
+        match self.stock.iter_mut().find(|(stocked, _)| *stocked == kind) {
+            Some((_, count)) => *count += 1,
+            None => self.stock.push((kind, 1)),
+        }
+        self.save_or_report();
+        true
+    }
+
+    // How many of a power-up are waiting for the next game
+    pub fn stocked(&self, kind: PowerUpKind) -> u32 {
+        self.stock.iter().find(|(stocked, _)| *stocked == kind).map_or(0, |(_, count)| *count)
+    }
+
+    // One of each power-up in stock, taken out of it for a game that's starting
+    pub fn take_stock(&mut self) -> Vec<PowerUpKind> {
+        let kinds: Vec<PowerUpKind> = self.stock.iter().map(|(kind, _)| *kind).collect();
+        if kinds.is_empty() {
+            return kinds;
+        }
+        for (_, count) in self.stock.iter_mut() {
+            *count -= 1;
+        }
+        self.stock.retain(|(_, count)| *count > 0);
+        self.save_or_report();
+        kinds
+    }
+
+    fn save_or_report(&self) {
+        if let Err(e) = self.save() {
+            eprintln!("Error saving profile: {}", e);
+        }
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()), // Nowhere to save, e.g. no home directory
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let mut text = format!(
+            "[profile]\nbest_score = {}\nskin = \"{}\"\ncoins = {}\n",
+            self.best_score,
+            self.selected().name,
+            self.coins
+        );
+        text.push_str("\n[bought]\n");
+        for &skin in &self.bought {
+            text.push_str(&format!("{} = true\n", SKINS[skin].name));
+        }
+        text.push_str("\n[stock]\n");
+        for (kind, count) in &self.stock {
+            text.push_str(&format!("{} = {}\n", kind.key(), count));
+        }
+        std::fs::write(path, text)
+    }
+}
+
+fn parse_profile(text: &str) -> Profile {
+    let values = match config::parse(text) {
+        Ok(values) => values,
+        Err(e) => {
+            eprintln!("Could not read profile: {}", e);
+            return Profile::default();
+        }
+    };
+
+    let count = |key: &str| values.get(key).and_then(|value| value.as_int()).unwrap_or(0).max(0);
+    let skin = values.get("profile.skin").and_then(|value| value.as_str());
+    Profile {
+        best_score: count("profile.best_score") as i32,
+        selected: skin.and_then(|name| SKINS.iter().position(|skin| skin.name == name)).unwrap_or(0),
+        coins: count("profile.coins") as u32,
+        bought: (0..SKINS.len())
+            .filter(|skin| values.get(&format!("bought.{}", SKINS[*skin].name)).and_then(|value| value.as_bool()) == Some(true))
+            .collect(),
+        stock: powerups::KINDS
+            .into_iter()
+            .map(|kind| (kind, count(&format!("stock.{}", kind.key())) as u32))
+            .filter(|&(_, count)| count > 0)
+            .collect(),
+        path: None,
+    }
+}
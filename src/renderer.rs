@@ -0,0 +1,579 @@
+// everything the game draws goes through the Renderer trait, so it doesn't care what it is drawn on.
+// PixelBuffer draws into a plain frame of 0xRRGGBB pixels, WindowRenderer (renderer/window.rs)
+// draws into one of those and shows it in a minifb window, SdlDisplay (renderer/sdl.rs) does the same in an SDL2 one
+// and CanvasDisplay (renderer/canvas.rs) hands it to a web page.
+// ThreadDisplay (renderer/threaded.rs) stands in for any of them on a thread of its own.
+// a Display is a backend the game can be played on, a renderer that also provides the input
+
+#[cfg(feature = "web")]
+mod canvas;
+#[cfg(feature = "sdl2")]
+mod sdl;
+#[cfg(feature = "terminal")]
+mod terminal;
+#[cfg(not(target_arch = "wasm32"))]
+mod threaded;
+#[cfg(not(target_arch = "wasm32"))]
+mod window;
+
+#[cfg(feature = "web")]
+pub use canvas::{CanvasDisplay, KeyEvent, Page};
+#[cfg(feature = "sdl2")]
+pub use sdl::SdlDisplay;
+#[cfg(feature = "terminal")]
+pub use terminal::TerminalRenderer;
+#[cfg(not(target_arch = "wasm32"))]
+pub use threaded::{split, Presenter, ThreadDisplay};
+#[cfg(not(target_arch = "wasm32"))]
+pub use window::WindowRenderer;
+
+use crate::assets::Texture;
+use crate::daynight::ColourLut;
+use crate::font;
+use crate::input::InputSource;
+
+pub trait Renderer {
+    // Size of the frame being drawn, in pixels. The playfield's unless a lower render resolution is set
+    fn size(&self) -> (usize, usize);
+
+    fn clear(&mut self, colour: u32);
+
+    // Positions can be slightly off the field, backends skip whatever doesn't fit
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32);
+
+    // A rectangle at a fractional position, for things that move. Backends that can blend draw the edge pixels
+    // by how much of them it covers, so slow movement glides rather than stepping a whole pixel at a time
+    fn fill_rect_smooth(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.fill_rect(x, y, width, height, colour);
+    }
+
+    // A circle filling the square with its top left corner at (x, y), edges smoothed where the backend can
+    fn fill_circle(&mut self, x: f32, y: f32, size: f32, colour: u32) {
+        self.fill_rect(x, y, size, size, colour);
+    }
+
+    // A texture stretched over the rectangle, blended by its alpha
+    fn draw_texture(&mut self, x: f32, y: f32, width: f32, height: f32, texture: &Texture);
+
+    // Text in the built in font with its top left corner at (x, y)
+    fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: usize, colour: u32);
+
+    // Darken everything drawn so far, for overlays. Backends that can't read back what they drew can skip it
+    fn dim(&mut self) {}
+
+    // Show the finished frame
+    fn present(&mut self) -> Result<(), String>;
+
+    // The frame as drawn, at the render resolution, for screenshots and clips
+    fn pixels(&self) -> Option<&[u32]> {
+        None
+    }
+
+    // The same frame to change in place before it is shown, for post-processing
+    fn pixels_mut(&mut self) -> Option<&mut [u32]> {
+        None
+    }
+
+    // Colour for any space around the playfield
+    fn set_border_colour(&mut self, _colour: u32) {}
+}
+
+pub trait Display: Renderer + InputSource {
+    // True when present() waits for the screen to refresh, which paces the frames by itself
+    fn vsync(&self) -> bool {
+        false
+    }
+
+    fn set_fullscreen(&mut self, _fullscreen: bool) -> Result<(), String> {
+        Err("fullscreen isn't supported here".to_string())
+    }
+}
+
+// Software renderer over a frame buffer owned by someone else, for headless use,
+// screenshots and as the drawing code of backends that end up with pixels anyway
+pub struct PixelBuffer<'a> {
+    pixels: &'a mut [u32],
+    width: usize,
+}
+
+impl<'a> PixelBuffer<'a> {
+    pub fn new(pixels: &'a mut [u32], width: usize) -> Self {
+        PixelBuffer { pixels, width }
+    }
+}
+
+impl Renderer for PixelBuffer<'_> {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.pixels.len() / self.width)
+    }
+
+    fn clear(&mut self, colour: u32) {
+        self.pixels.fill(colour);
+    }
+
+    // Each row is filled as one slice, which the compiler turns into wide stores, cut off at the right and
+    // bottom edges
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        // Ball positions can be slightly negative for a frame, so skip pixels off the top or sides
+        if x < 0.0 || y < 0.0 {
+            return;
+        }
+        let (left, top) = (x as usize, y as usize);
+        let right = (left + width as usize).min(self.width);
+        if left >= right {
+            return;
+        }
+        for row in self.pixels.chunks_exact_mut(self.width).skip(top).take(height as usize) {
+            row[left..right].fill(colour);
+        }
+    }
+
+    fn fill_rect_smooth(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        let field_height = self.pixels.len() / self.width;
+        let (left, top) = (x.floor().max(0.0) as usize, y.floor().max(0.0) as usize);
+        let right = ((x + width).ceil().max(0.0) as usize).min(self.width);
+        let bottom = ((y + height).ceil().max(0.0) as usize).min(field_height);
+        // How much of the pixel starting at p the span from start to end covers, 0 to 1
+        let overlap = |p: usize, start: f32, end: f32| (end.min(p as f32 + 1.0) - start.max(p as f32)).clamp(0.0, 1.0);
+        for py in top..bottom {
+            let cover_y = overlap(py, y, y + height);
+            for px in left..right {
+                let coverage = cover_y * overlap(px, x, x + width);
+                let index = py * self.width + px;
+                let alpha = (coverage * 255.0).round() as u32;
+                self.pixels[index] = blend(self.pixels[index], alpha << 24 | (colour & 0xFFFFFF));
+            }
+        }
+    }
+
+    // Each pixel is blended in by how much of it the circle covers, going by its centre's distance from the edge
+    fn fill_circle(&mut self, x: f32, y: f32, size: f32, colour: u32) {
+        let field_height = self.pixels.len() / self.width;
+        let radius = size / 2.0;
+        let (centre_x, centre_y) = (x + radius, y + radius);
+        let (left, top) = (x.floor().max(0.0) as usize, y.floor().max(0.0) as usize);
+        let right = ((x + size).ceil().max(0.0) as usize).min(self.width);
+        let bottom = ((y + size).ceil().max(0.0) as usize).min(field_height);
+        for py in top..bottom {
+            for px in left..right {
+                let distance = (px as f32 + 0.5 - centre_x).hypot(py as f32 + 0.5 - centre_y);
+                let coverage = (radius - distance + 0.5).clamp(0.0, 1.0);
+                if coverage > 0.0 {
+                    let index = py * self.width + px;
+                    let alpha = (coverage * 255.0).round() as u32;
+                    self.pixels[index] = blend(self.pixels[index], alpha << 24 | (colour & 0xFFFFFF));
+                }
+            }
+        }
+    }
+
+    fn draw_texture(&mut self, x: f32, y: f32, width: f32, height: f32, texture: &Texture) {
+        let (width, height) = (width as usize, height as usize);
+        let field_height = self.pixels.len() / self.width;
+        for row in 0..height {
+            for column in 0..width {
+                let (px, py) = (x as isize + column as isize, y as isize + row as isize);
+                if px < 0 || py < 0 || px as usize >= self.width || py as usize >= field_height {
+                    continue;
+                }
+                let index = py as usize * self.width + px as usize;
+                self.pixels[index] = blend(self.pixels[index], texture.sample(column, row, width, height));
+            }
+        }
+    }
+
+    fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: usize, colour: u32) {
+        font::draw_text(self.pixels, self.width, x, y, text, scale, colour);
+    }
+
+    fn dim(&mut self) {
+        // Halve each colour channel, masking off the bit that would bleed into the channel below
+        for pixel in self.pixels.iter_mut() {
+            *pixel = (*pixel >> 1) & 0x007F7F7F;
+        }
+    }
+
+    fn present(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn pixels(&self) -> Option<&[u32]> {
+        Some(self.pixels)
+    }
+
+    fn pixels_mut(&mut self) -> Option<&mut [u32]> {
+        Some(self.pixels)
+    }
+}
+
+// Draws through another renderer with everything moved by an offset, for screen shake
+pub struct Camera<'a> {
+    renderer: &'a mut dyn Renderer,
+    x: f32,
+    y: f32,
+}
+
+impl<'a> Camera<'a> {
+    pub fn new(renderer: &'a mut dyn Renderer, x: f32, y: f32) -> Self {
+        Camera { renderer, x, y }
+    }
+}
+
+impl Renderer for Camera<'_> {
+    fn size(&self) -> (usize, usize) {
+        self.renderer.size()
+    }
+
+    fn clear(&mut self, colour: u32) {
+        self.renderer.clear(colour);
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.renderer.fill_rect(x + self.x, y + self.y, width, height, colour);
+    }
+
+    fn fill_rect_smooth(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.renderer.fill_rect_smooth(x + self.x, y + self.y, width, height, colour);
+    }
+
+    fn fill_circle(&mut self, x: f32, y: f32, size: f32, colour: u32) {
+        self.renderer.fill_circle(x + self.x, y + self.y, size, colour);
+    }
+
+    fn draw_texture(&mut self, x: f32, y: f32, width: f32, height: f32, texture: &Texture) {
+        self.renderer.draw_texture(x + self.x, y + self.y, width, height, texture);
+    }
+
+    fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: usize, colour: u32) {
+        let x = (x as f32 + self.x).max(0.0) as usize;
+        let y = (y as f32 + self.y).max(0.0) as usize;
+        self.renderer.draw_text(x, y, text, scale, colour);
+    }
+
+    fn dim(&mut self) {
+        self.renderer.dim();
+    }
+
+    fn present(&mut self) -> Result<(), String> {
+        self.renderer.present()
+    }
+
+    fn pixels(&self) -> Option<&[u32]> {
+        self.renderer.pixels()
+    }
+
+    fn pixels_mut(&mut self) -> Option<&mut [u32]> {
+        self.renderer.pixels_mut()
+    }
+
+    fn set_border_colour(&mut self, colour: u32) {
+        self.renderer.set_border_colour(colour);
+    }
+}
+
+// Draws the playfield through a renderer whose frame is a different size, for a lower render resolution.
+// Positions and sizes are scaled to the frame, text to the nearest whole scale that still shows
+pub struct Scaled<'a> {
+    renderer: &'a mut dyn Renderer,
+    field_width: usize,
+    field_height: usize,
+    scale_x: f32,
+    scale_y: f32,
+}
+
+impl<'a> Scaled<'a> {
+    pub fn new(renderer: &'a mut dyn Renderer, field_width: usize, field_height: usize) -> Self {
+        let (width, height) = renderer.size();
+        let (scale_x, scale_y) = (width as f32 / field_width as f32, height as f32 / field_height as f32);
+        Scaled { renderer, field_width, field_height, scale_x, scale_y }
+    }
+}
+
+impl Renderer for Scaled<'_> {
+    fn size(&self) -> (usize, usize) {
+        (self.field_width, self.field_height)
+    }
+
+    fn clear(&mut self, colour: u32) {
+        self.renderer.clear(colour);
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        let (sx, sy) = (self.scale_x, self.scale_y);
+        self.renderer.fill_rect(x * sx, y * sy, (width * sx).max(1.0), (height * sy).max(1.0), colour);
+    }
+
+    fn fill_rect_smooth(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        let (sx, sy) = (self.scale_x, self.scale_y);
+        self.renderer.fill_rect_smooth(x * sx, y * sy, width * sx, height * sy, colour);
+    }
+
+    fn fill_circle(&mut self, x: f32, y: f32, size: f32, colour: u32) {
+        self.renderer.fill_circle(x * self.scale_x, y * self.scale_y, size * self.scale_x.min(self.scale_y), colour);
+    }
+
+    fn draw_texture(&mut self, x: f32, y: f32, width: f32, height: f32, texture: &Texture) {
+        let (sx, sy) = (self.scale_x, self.scale_y);
+        self.renderer.draw_texture(x * sx, y * sy, width * sx, height * sy, texture);
+    }
+
+    fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: usize, colour: u32) {
+        let (x, y) = ((x as f32 * self.scale_x) as usize, (y as f32 * self.scale_y) as usize);
+        let scale = (scale as f32 * self.scale_y).round().max(1.0) as usize;
+        self.renderer.draw_text(x, y, text, scale, colour);
+    }
+
+    fn dim(&mut self) {
+        self.renderer.dim();
+    }
+
+    fn present(&mut self) -> Result<(), String> {
+        self.renderer.present()
+    }
+
+    fn pixels(&self) -> Option<&[u32]> {
+        self.renderer.pixels()
+    }
+
+    fn pixels_mut(&mut self) -> Option<&mut [u32]> {
+        self.renderer.pixels_mut()
+    }
+
+    fn set_border_colour(&mut self, colour: u32) {
+        self.renderer.set_border_colour(colour);
+    }
+}
+
+// Draws through another renderer with every colour looked up in a table first, for the day and night cycle
+pub struct Tinted<'a> {
+    renderer: &'a mut dyn Renderer,
+    lut: &'a ColourLut,
+}
+
+impl<'a> Tinted<'a> {
+    pub fn new(renderer: &'a mut dyn Renderer, lut: &'a ColourLut) -> Self {
+        Tinted { renderer, lut }
+    }
+}
+
+impl Renderer for Tinted<'_> {
+    fn size(&self) -> (usize, usize) {
+        self.renderer.size()
+    }
+
+    fn clear(&mut self, colour: u32) {
+        self.renderer.clear(self.lut.map(colour));
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.renderer.fill_rect(x, y, width, height, self.lut.map(colour));
+    }
+
+    fn fill_rect_smooth(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.renderer.fill_rect_smooth(x, y, width, height, self.lut.map(colour));
+    }
+
+    fn fill_circle(&mut self, x: f32, y: f32, size: f32, colour: u32) {
+        self.renderer.fill_circle(x, y, size, self.lut.map(colour));
+    }
+
+    fn draw_texture(&mut self, x: f32, y: f32, width: f32, height: f32, texture: &Texture) {
+        self.renderer.draw_texture(x, y, width, height, &self.lut.map_texture(texture));
+    }
+
+    fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: usize, colour: u32) {
+        self.renderer.draw_text(x, y, text, scale, self.lut.map(colour));
+    }
+
+    fn dim(&mut self) {
+        self.renderer.dim();
+    }
+
+    fn present(&mut self) -> Result<(), String> {
+        self.renderer.present()
+    }
+
+    fn pixels(&self) -> Option<&[u32]> {
+        self.renderer.pixels()
+    }
+
+    fn pixels_mut(&mut self) -> Option<&mut [u32]> {
+        self.renderer.pixels_mut()
+    }
+
+    fn set_border_colour(&mut self, colour: u32) {
+        self.renderer.set_border_colour(self.lut.map(colour));
+    }
+}
+
+// A part of the playfield, in whole pixels
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    // The whole pixels an area at a fractional position touches, grown by a margin and cut to the field
+    pub fn around(x: f32, y: f32, width: f32, height: f32, margin: f32, field: (usize, usize)) -> Rect {
+        let left = (x - margin).floor().clamp(0.0, field.0 as f32) as usize;
+        let top = (y - margin).floor().clamp(0.0, field.1 as f32) as usize;
+        let right = (x + width + margin).ceil().clamp(0.0, field.0 as f32) as usize;
+        let bottom = (y + height + margin).ceil().clamp(0.0, field.1 as f32) as usize;
+        Rect {
+            x: left,
+            y: top,
+            width: right.saturating_sub(left),
+            height: bottom.saturating_sub(top),
+        }
+    }
+
+    // The smallest rectangle holding both
+    pub fn union(self, other: Rect) -> Rect {
+        let (left, top) = (self.x.min(other.x), self.y.min(other.y));
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        Rect {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        }
+    }
+
+    fn touches(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        let (right, bottom) = ((self.x + self.width) as f32, (self.y + self.height) as f32);
+        x < right && x + width > self.x as f32 && y < bottom && y + height > self.y as f32
+    }
+
+    fn contains(&self, x: usize, y: usize) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+}
+
+// Draws through another renderer only inside the given rectangles, leaving the rest of the last frame as it was.
+// Clearing and plain rectangles are cut down to the rectangles. Anything blended is drawn whole if it touches one,
+// then the pixels it changed outside them are put back, as blending it again over itself would darken its edges
+pub struct DirtyRects<'a> {
+    renderer: &'a mut dyn Renderer,
+    rects: &'a [Rect],
+}
+
+impl<'a> DirtyRects<'a> {
+    pub fn new(renderer: &'a mut dyn Renderer, rects: &'a [Rect]) -> Self {
+        DirtyRects { renderer, rects }
+    }
+
+    fn touches(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        self.rects.iter().any(|rect| rect.touches(x, y, width, height))
+    }
+
+    fn draw_clipped(&mut self, x: f32, y: f32, width: f32, height: f32, draw: impl FnOnce(&mut dyn Renderer)) {
+        if !self.touches(x, y, width, height) {
+            return;
+        }
+        let field = self.renderer.size();
+        let bounds = Rect::around(x, y, width, height, 1.0, field);
+        let mut outside = Vec::new();
+        if let Some(pixels) = self.renderer.pixels() {
+            for py in bounds.y..bounds.y + bounds.height {
+                for px in bounds.x..bounds.x + bounds.width {
+                    if !self.rects.iter().any(|rect| rect.contains(px, py)) {
+                        let index = py * field.0 + px;
+                        outside.push((index, pixels[index]));
+                    }
+                }
+            }
+        }
+        draw(&mut *self.renderer);
+        if let Some(pixels) = self.renderer.pixels_mut() {
+            for (index, pixel) in outside {
+                pixels[index] = pixel;
+            }
+        }
+    }
+}
+
+impl Renderer for DirtyRects<'_> {
+    fn size(&self) -> (usize, usize) {
+        self.renderer.size()
+    }
+
+    fn clear(&mut self, colour: u32) {
+        for rect in self.rects {
+            self.renderer.fill_rect(rect.x as f32, rect.y as f32, rect.width as f32, rect.height as f32, colour);
+        }
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        if x < 0.0 || y < 0.0 {
+            return;
+        }
+        // The pixels PixelBuffer would fill, then only the parts of them inside each rectangle
+        let (left, top) = (x as usize, y as usize);
+        let (right, bottom) = (left + width as usize, top + height as usize);
+        for rect in self.rects {
+            let (clip_left, clip_top) = (left.max(rect.x), top.max(rect.y));
+            let (clip_right, clip_bottom) = (right.min(rect.x + rect.width), bottom.min(rect.y + rect.height));
+            if clip_left < clip_right && clip_top < clip_bottom {
+                let (clip_width, clip_height) = ((clip_right - clip_left) as f32, (clip_bottom - clip_top) as f32);
+                self.renderer.fill_rect(clip_left as f32, clip_top as f32, clip_width, clip_height, colour);
+            }
+        }
+    }
+
+    fn fill_rect_smooth(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.draw_clipped(x, y, width, height, |renderer| renderer.fill_rect_smooth(x, y, width, height, colour));
+    }
+
+    fn fill_circle(&mut self, x: f32, y: f32, size: f32, colour: u32) {
+        self.draw_clipped(x, y, size, size, |renderer| renderer.fill_circle(x, y, size, colour));
+    }
+
+    fn draw_texture(&mut self, x: f32, y: f32, width: f32, height: f32, texture: &Texture) {
+        self.draw_clipped(x, y, width, height, |renderer| renderer.draw_texture(x, y, width, height, texture));
+    }
+
+    // Text is opaque, so drawing it again over itself outside the rectangles changes nothing
+    fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: usize, colour: u32) {
+        let (width, height) = (font::text_width(text, scale) as f32, (font::GLYPH_HEIGHT * scale) as f32);
+        if self.touches(x as f32, y as f32, width, height) {
+            self.renderer.draw_text(x, y, text, scale, colour);
+        }
+    }
+
+    fn dim(&mut self) {
+        self.renderer.dim();
+    }
+
+    fn present(&mut self) -> Result<(), String> {
+        self.renderer.present()
+    }
+
+    fn pixels(&self) -> Option<&[u32]> {
+        self.renderer.pixels()
+    }
+
+    fn pixels_mut(&mut self) -> Option<&mut [u32]> {
+        self.renderer.pixels_mut()
+    }
+
+    fn set_border_colour(&mut self, colour: u32) {
+        self.renderer.set_border_colour(colour);
+    }
+}
+
+// An 0xAARRGGBB pixel over an opaque one
+fn blend(under: u32, over: u32) -> u32 {
+    let alpha = over >> 24;
+    if alpha == 0xFF {
+        return over & 0xFFFFFF;
+    }
+    let channel = |shift: u32| {
+        let (a, b) = ((under >> shift) & 0xFF, (over >> shift) & 0xFF);
+        ((b * alpha + a * (255 - alpha)) / 255) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
@@ -0,0 +1,159 @@
+// the web backend, draws into a frame the size of the playfield which web/index.html copies onto a canvas.
+// the page hands key and mouse events over as they happen (see web.rs) and they're taken in once a frame,
+// the same as the other backends
+
+use super::{Display, PixelBuffer, Renderer};
+use crate::assets::Texture;
+use crate::input::{InputSource, Key, KeyRepeat};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+// What passes between the game and the page
+#[derive(Default)]
+pub struct Page {
+    pub key_events: Vec<KeyEvent>,
+    pub mouse: Option<(f32, f32)>, // In playfield pixels
+    pub rgba: Vec<u8>,             // The last frame, in the byte order ImageData wants
+}
+
+#[derive(Clone, Copy)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub down: bool,
+    pub repeat: bool,
+}
+
+pub struct CanvasDisplay {
+    page: Rc<RefCell<Page>>,
+    frame: Vec<u32>,
+    field_width: usize,
+    field_height: usize,
+    held: HashSet<Key>,
+    pressed: Vec<Key>,  // First presses since the last frame
+    repeated: Vec<Key>, // Presses since the last frame, repeats included
+    mouse: Option<(f32, f32)>,
+}
+
+impl CanvasDisplay {
+    pub fn new(field_width: usize, field_height: usize, page: Rc<RefCell<Page>>) -> Self {
+        page.borrow_mut().rgba = vec![0; field_width * field_height * 4];
+        CanvasDisplay {
+            page,
+            frame: vec![0; field_width * field_height],
+            field_width,
+            field_height,
+            held: HashSet::new(),
+            pressed: Vec::new(),
+            repeated: Vec::new(),
+            mouse: None,
+        }
+    }
+
+    fn pixel_buffer(&mut self) -> PixelBuffer<'_> {
+        PixelBuffer::new(&mut self.frame, self.field_width)
+    }
+}
+
+impl Renderer for CanvasDisplay {
+    fn size(&self) -> (usize, usize) {
+        (self.field_width, self.field_height)
+    }
+
+    fn clear(&mut self, colour: u32) {
+        self.pixel_buffer().clear(colour);
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.pixel_buffer().fill_rect(x, y, width, height, colour);
+    }
+
+    fn fill_rect_smooth(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.pixel_buffer().fill_rect_smooth(x, y, width, height, colour);
+    }
+
+    fn fill_circle(&mut self, x: f32, y: f32, size: f32, colour: u32) {
+        self.pixel_buffer().fill_circle(x, y, size, colour);
+    }
+
+    fn draw_texture(&mut self, x: f32, y: f32, width: f32, height: f32, texture: &Texture) {
+        self.pixel_buffer().draw_texture(x, y, width, height, texture);
+    }
+
+    fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: usize, colour: u32) {
+        self.pixel_buffer().draw_text(x, y, text, scale, colour);
+    }
+
+    fn dim(&mut self) {
+        self.pixel_buffer().dim();
+    }
+
+    // The page draws the frame after this returns, and the events it got meanwhile count for the next one
+    fn present(&mut self) -> Result<(), String> {
+        let mut page = self.page.borrow_mut();
+        for (pixel, rgba) in self.frame.iter().zip(page.rgba.chunks_exact_mut(4)) {
+            rgba.copy_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, *pixel as u8, 0xFF]);
+        }
+
+        self.pressed.clear();
+        self.repeated.clear();
+        for event in page.key_events.drain(..) {
+            if !event.down {
+                self.held.remove(&event.key);
+                continue;
+            }
+            if !event.repeat {
+                self.pressed.push(event.key);
+            }
+            self.repeated.push(event.key);
+            self.held.insert(event.key);
+        }
+        self.mouse = page.mouse;
+        Ok(())
+    }
+
+    fn pixels(&self) -> Option<&[u32]> {
+        Some(&self.frame)
+    }
+
+    fn pixels_mut(&mut self) -> Option<&mut [u32]> {
+        Some(&mut self.frame)
+    }
+}
+
+impl InputSource for CanvasDisplay {
+    // The page can't be closed from inside, quitting from the menu is what ends the game
+    fn is_open(&self) -> bool {
+        true
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.held.contains(&key)
+    }
+
+    fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        match repeat {
+            KeyRepeat::Yes => self.repeated.contains(&key),
+            KeyRepeat::No => self.pressed.contains(&key),
+        }
+    }
+
+    fn keys_pressed(&self) -> Vec<Key> {
+        self.pressed.clone()
+    }
+
+    fn keys_repeated(&self) -> Vec<Key> {
+        self.repeated.clone()
+    }
+
+    fn keys_down(&self) -> Vec<Key> {
+        self.held.iter().copied().collect()
+    }
+
+    fn mouse_position(&self) -> Option<(f32, f32)> {
+        self.mouse
+    }
+}
+
+// Fullscreen is left to the browser (F11)
+impl Display for CanvasDisplay {}
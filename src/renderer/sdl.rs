@@ -0,0 +1,447 @@
+// an SDL2 window, for platforms where minifb struggles (older X11 setups, some BSDs).
+// SDL2 is loaded when the game starts rather than linked, so building with the feature doesn't need
+// the SDL2 development files, only the library at runtime. the frame is drawn in software as with
+// the minifb window and SDL scales it to the window, letterboxed, on the GPU
+
+use super::{Display, PixelBuffer, Renderer};
+use crate::assets::Texture;
+use crate::input::{InputSource, Key, KeyRepeat};
+use crate::viewport::{Scaling, Screen};
+use libloading::Library;
+use std::collections::HashSet;
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::ptr;
+
+// The library names SDL2 goes by on each platform
+const LIBRARY_NAMES: [&str; 5] = ["libSDL2-2.0.so.0", "libSDL2.so", "libSDL2-2.0.0.dylib", "libSDL2.dylib", "SDL2.dll"];
+
+const SDL_INIT_VIDEO: u32 = 0x20;
+const SDL_WINDOWPOS_CENTERED: c_int = 0x2FFF0000;
+const SDL_WINDOW_FULLSCREEN_DESKTOP: u32 = 0x1001;
+const SDL_WINDOW_RESIZABLE: u32 = 0x20;
+const SDL_WINDOW_ALLOW_HIGHDPI: u32 = 0x2000;
+const SDL_PIXELFORMAT_RGB888: u32 = 0x16161804; // 0x00RRGGBB, the same layout as the frame
+const SDL_TEXTUREACCESS_STREAMING: c_int = 1;
+const SDL_RENDERER_PRESENTVSYNC: u32 = 0x4;
+
+const SDL_QUIT: u32 = 0x100;
+const SDL_KEYDOWN: u32 = 0x300;
+const SDL_KEYUP: u32 = 0x301;
+const SDL_MOUSEMOTION: u32 = 0x400;
+
+// SDL_Event is a 56 byte union, only the fields read here are picked out of it
+#[repr(C, align(8))]
+struct Event([u8; 56]);
+
+impl Event {
+    fn u32_at(&self, offset: usize) -> u32 {
+        u32::from_ne_bytes(self.0[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn i32_at(&self, offset: usize) -> i32 {
+        self.u32_at(offset) as i32
+    }
+
+    fn kind(&self) -> u32 {
+        self.u32_at(0)
+    }
+}
+
+type SdlWindow = c_void;
+type SdlRenderer = c_void;
+type SdlTexture = c_void;
+
+// The handful of SDL2 functions the game uses
+struct Sdl {
+    init: unsafe extern "C" fn(u32) -> c_int,
+    quit: unsafe extern "C" fn(),
+    get_error: unsafe extern "C" fn() -> *const c_char,
+    create_window: unsafe extern "C" fn(*const c_char, c_int, c_int, c_int, c_int, u32) -> *mut SdlWindow,
+    destroy_window: unsafe extern "C" fn(*mut SdlWindow),
+    set_window_fullscreen: unsafe extern "C" fn(*mut SdlWindow, u32) -> c_int,
+    create_renderer: unsafe extern "C" fn(*mut SdlWindow, c_int, u32) -> *mut SdlRenderer,
+    destroy_renderer: unsafe extern "C" fn(*mut SdlRenderer),
+    render_set_logical_size: unsafe extern "C" fn(*mut SdlRenderer, c_int, c_int) -> c_int,
+    render_set_integer_scale: unsafe extern "C" fn(*mut SdlRenderer, c_int) -> c_int,
+    set_hint: unsafe extern "C" fn(*const c_char, *const c_char) -> c_int,
+    create_texture: unsafe extern "C" fn(*mut SdlRenderer, u32, c_int, c_int, c_int) -> *mut SdlTexture,
+    destroy_texture: unsafe extern "C" fn(*mut SdlTexture),
+    update_texture: unsafe extern "C" fn(*mut SdlTexture, *const c_void, *const c_void, c_int) -> c_int,
+    set_render_draw_color: unsafe extern "C" fn(*mut SdlRenderer, u8, u8, u8, u8) -> c_int,
+    render_clear: unsafe extern "C" fn(*mut SdlRenderer) -> c_int,
+    render_copy: unsafe extern "C" fn(*mut SdlRenderer, *mut SdlTexture, *const c_void, *const c_void) -> c_int,
+    render_present: unsafe extern "C" fn(*mut SdlRenderer),
+    poll_event: unsafe extern "C" fn(*mut Event) -> c_int,
+    _library: Library, // Must outlive the function pointers above
+}
+
+impl Sdl {
+    fn load() -> Result<Self, String> {
+        // SAFETY: loading SDL2 runs its initialisers, which are fine to run at any point
+        let library = LIBRARY_NAMES
+            .iter()
+            .find_map(|name| unsafe { Library::new(name) }.ok())
+            .ok_or_else(|| "couldn't find the SDL2 library, is SDL2 installed?".to_string())?;
+
+        // SAFETY: each type below is the function's C signature from the SDL2 headers
+        unsafe fn symbol<T: Copy>(library: &Library, name: &str) -> Result<T, String> {
+            let symbol = library.get::<T>(name.as_bytes()).map_err(|e| format!("{}: {}", name, e))?;
+            Ok(*symbol)
+        }
+        unsafe {
+            Ok(Sdl {
+                init: symbol(&library, "SDL_Init")?,
+                quit: symbol(&library, "SDL_Quit")?,
+                get_error: symbol(&library, "SDL_GetError")?,
+                create_window: symbol(&library, "SDL_CreateWindow")?,
+                destroy_window: symbol(&library, "SDL_DestroyWindow")?,
+                set_window_fullscreen: symbol(&library, "SDL_SetWindowFullscreen")?,
+                create_renderer: symbol(&library, "SDL_CreateRenderer")?,
+                destroy_renderer: symbol(&library, "SDL_DestroyRenderer")?,
+                render_set_logical_size: symbol(&library, "SDL_RenderSetLogicalSize")?,
+                render_set_integer_scale: symbol(&library, "SDL_RenderSetIntegerScale")?,
+                set_hint: symbol(&library, "SDL_SetHint")?,
+                create_texture: symbol(&library, "SDL_CreateTexture")?,
+                destroy_texture: symbol(&library, "SDL_DestroyTexture")?,
+                update_texture: symbol(&library, "SDL_UpdateTexture")?,
+                set_render_draw_color: symbol(&library, "SDL_SetRenderDrawColor")?,
+                render_clear: symbol(&library, "SDL_RenderClear")?,
+                render_copy: symbol(&library, "SDL_RenderCopy")?,
+                render_present: symbol(&library, "SDL_RenderPresent")?,
+                poll_event: symbol(&library, "SDL_PollEvent")?,
+                _library: library,
+            })
+        }
+    }
+
+    fn error(&self) -> String {
+        // SAFETY: SDL_GetError always returns a valid, possibly empty, C string
+        unsafe { CStr::from_ptr((self.get_error)()) }.to_string_lossy().into_owned()
+    }
+}
+
+pub struct SdlDisplay {
+    sdl: Sdl,
+    window: *mut SdlWindow,
+    renderer: *mut SdlRenderer,
+    texture: *mut SdlTexture,
+    frame: Vec<u32>,
+    frame_width: usize,
+    frame_height: usize,
+    bars: u32,
+    held: HashSet<Key>,
+    pressed: Vec<Key>,  // First presses since the last frame
+    repeated: Vec<Key>, // Presses since the last frame, repeats included
+    mouse: Option<(f32, f32)>,
+    open: bool,
+    vsync: bool,
+}
+
+impl SdlDisplay {
+    pub fn new(screen: Screen, fullscreen: bool, vsync: bool) -> Result<Self, String> {
+        let (frame_width, frame_height) = (screen.frame_width, screen.frame_height);
+        let sdl = Sdl::load()?;
+        // SAFETY: the calls follow SDL's documented order, each result is checked before it's used
+        // and Drop destroys whatever was created
+        unsafe {
+            if (sdl.init)(SDL_INIT_VIDEO) != 0 {
+                return Err(sdl.error());
+            }
+            let mut display = SdlDisplay {
+                sdl,
+                window: ptr::null_mut(),
+                renderer: ptr::null_mut(),
+                texture: ptr::null_mut(),
+                frame: vec![0; frame_width * frame_height],
+                frame_width,
+                frame_height,
+                bars: 0,
+                held: HashSet::new(),
+                pressed: Vec::new(),
+                repeated: Vec::new(),
+                mouse: None,
+                open: true,
+                vsync,
+            };
+
+            // On retina screens the window is sized in points and drawn at the screen's full resolution
+            let flags = SDL_WINDOW_RESIZABLE
+                | SDL_WINDOW_ALLOW_HIGHDPI
+                | if fullscreen { SDL_WINDOW_FULLSCREEN_DESKTOP } else { 0 };
+            let (width, height) = (screen.window_width as c_int, screen.window_height as c_int);
+            let title = c"Game Window";
+            display.window =
+                (display.sdl.create_window)(title.as_ptr(), SDL_WINDOWPOS_CENTERED, SDL_WINDOWPOS_CENTERED, width, height, flags);
+            let (width, height) = (frame_width as c_int, frame_height as c_int);
+            if display.window.is_null() {
+                return Err(display.sdl.error());
+            }
+            let flags = if vsync { SDL_RENDERER_PRESENTVSYNC } else { 0 };
+            display.renderer = (display.sdl.create_renderer)(display.window, -1, flags);
+            if display.renderer.is_null() {
+                return Err(display.sdl.error());
+            }
+            // SDL keeps the frame's shape whatever the window size, and gives mouse positions in frame pixels
+            (display.sdl.render_set_logical_size)(display.renderer, width, height);
+            if screen.pixel_scale > 1 {
+                (display.sdl.render_set_integer_scale)(display.renderer, 1);
+            }
+            // Read when the texture is made, for how it's scaled to the window
+            let quality = match screen.scaling {
+                Scaling::Nearest => c"nearest",
+                Scaling::Bilinear => c"linear",
+            };
+            (display.sdl.set_hint)(c"SDL_RENDER_SCALE_QUALITY".as_ptr(), quality.as_ptr());
+            display.texture = (display.sdl.create_texture)(
+                display.renderer,
+                SDL_PIXELFORMAT_RGB888,
+                SDL_TEXTUREACCESS_STREAMING,
+                width,
+                height,
+            );
+            if display.texture.is_null() {
+                return Err(display.sdl.error());
+            }
+            Ok(display)
+        }
+    }
+
+    fn pixel_buffer(&mut self) -> PixelBuffer<'_> {
+        PixelBuffer::new(&mut self.frame, self.frame_width)
+    }
+
+    // Take in the events since the last frame
+    fn poll_events(&mut self) {
+        self.pressed.clear();
+        self.repeated.clear();
+        let mut event = Event([0; 56]);
+        // SAFETY: the event is the size and alignment of SDL_Event
+        while unsafe { (self.sdl.poll_event)(&mut event) } != 0 {
+            match event.kind() {
+                SDL_QUIT => self.open = false,
+                // Keyboard events: repeat flag at byte 13, scancode at 16
+                SDL_KEYDOWN | SDL_KEYUP => {
+                    let Some(key) = key_from_scancode(event.u32_at(16)) else {
+                        continue;
+                    };
+                    if event.kind() == SDL_KEYUP {
+                        self.held.remove(&key);
+                        continue;
+                    }
+                    if event.0[13] == 0 {
+                        self.pressed.push(key);
+                    }
+                    self.repeated.push(key);
+                    self.held.insert(key);
+                }
+                // Mouse motion: x and y at bytes 20 and 24
+                SDL_MOUSEMOTION => self.mouse = Some((event.i32_at(20) as f32, event.i32_at(24) as f32)),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Renderer for SdlDisplay {
+    fn size(&self) -> (usize, usize) {
+        (self.frame_width, self.frame_height)
+    }
+
+    fn clear(&mut self, colour: u32) {
+        self.pixel_buffer().clear(colour);
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.pixel_buffer().fill_rect(x, y, width, height, colour);
+    }
+
+    fn fill_rect_smooth(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.pixel_buffer().fill_rect_smooth(x, y, width, height, colour);
+    }
+
+    fn fill_circle(&mut self, x: f32, y: f32, size: f32, colour: u32) {
+        self.pixel_buffer().fill_circle(x, y, size, colour);
+    }
+
+    fn draw_texture(&mut self, x: f32, y: f32, width: f32, height: f32, texture: &Texture) {
+        self.pixel_buffer().draw_texture(x, y, width, height, texture);
+    }
+
+    fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: usize, colour: u32) {
+        self.pixel_buffer().draw_text(x, y, text, scale, colour);
+    }
+
+    fn dim(&mut self) {
+        self.pixel_buffer().dim();
+    }
+
+    fn present(&mut self) -> Result<(), String> {
+        self.poll_events();
+        let sdl = &self.sdl;
+        let bars = self.bars;
+        // SAFETY: the frame is frame_width * frame_height pixels, the size the texture was made
+        unsafe {
+            (sdl.update_texture)(self.texture, ptr::null(), self.frame.as_ptr().cast(), (self.frame_width * 4) as c_int);
+            // Clearing also fills the letterbox bars
+            (sdl.set_render_draw_color)(self.renderer, (bars >> 16) as u8, (bars >> 8) as u8, bars as u8, 0xFF);
+            (sdl.render_clear)(self.renderer);
+            (sdl.render_copy)(self.renderer, self.texture, ptr::null(), ptr::null());
+            (sdl.render_present)(self.renderer);
+        }
+        Ok(())
+    }
+
+    fn pixels(&self) -> Option<&[u32]> {
+        Some(&self.frame)
+    }
+
+    fn pixels_mut(&mut self) -> Option<&mut [u32]> {
+        Some(&mut self.frame)
+    }
+
+    fn set_border_colour(&mut self, colour: u32) {
+        self.bars = colour;
+    }
+}
+
+impl InputSource for SdlDisplay {
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.held.contains(&key)
+    }
+
+    fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        match repeat {
+            KeyRepeat::Yes => self.repeated.contains(&key),
+            KeyRepeat::No => self.pressed.contains(&key),
+        }
+    }
+
+    fn keys_pressed(&self) -> Vec<Key> {
+        self.pressed.clone()
+    }
+
+    fn keys_repeated(&self) -> Vec<Key> {
+        self.repeated.clone()
+    }
+
+    fn keys_down(&self) -> Vec<Key> {
+        self.held.iter().copied().collect()
+    }
+
+    fn mouse_position(&self) -> Option<(f32, f32)> {
+        self.mouse
+    }
+}
+
+impl Display for SdlDisplay {
+    fn vsync(&self) -> bool {
+        self.vsync
+    }
+
+    // SDL has real fullscreen, at the desktop resolution so switching is quick
+    fn set_fullscreen(&mut self, fullscreen: bool) -> Result<(), String> {
+        let flags = if fullscreen { SDL_WINDOW_FULLSCREEN_DESKTOP } else { 0 };
+        // SAFETY: the window is valid for as long as the display is
+        if unsafe { (self.sdl.set_window_fullscreen)(self.window, flags) } != 0 {
+            return Err(self.sdl.error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SdlDisplay {
+    fn drop(&mut self) {
+        // SAFETY: each pointer is either null or was created by SDL and not destroyed yet
+        unsafe {
+            if !self.texture.is_null() {
+                (self.sdl.destroy_texture)(self.texture);
+            }
+            if !self.renderer.is_null() {
+                (self.sdl.destroy_renderer)(self.renderer);
+            }
+            if !self.window.is_null() {
+                (self.sdl.destroy_window)(self.window);
+            }
+            (self.sdl.quit)();
+        }
+    }
+}
+
+// SDL scancodes are USB keyboard usage ids, the key's position rather than what's printed on it
+fn key_from_scancode(scancode: u32) -> Option<Key> {
+    const LETTERS: [Key; 26] = [
+        Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+        Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    ];
+    const DIGITS: [Key; 10] = [
+        Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9, Key::Key0,
+    ];
+    const FUNCTION_KEYS: [Key; 12] = [
+        Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8, Key::F9, Key::F10, Key::F11, Key::F12,
+    ];
+    const NUMPAD_DIGITS: [Key; 10] = [
+        Key::NumPad1, Key::NumPad2, Key::NumPad3, Key::NumPad4, Key::NumPad5, Key::NumPad6, Key::NumPad7,
+        Key::NumPad8, Key::NumPad9, Key::NumPad0,
+    ];
+
+    let key = match scancode {
+        4..=29 => LETTERS[scancode as usize - 4],
+        30..=39 => DIGITS[scancode as usize - 30],
+        40 => Key::Enter,
+        41 => Key::Escape,
+        42 => Key::Backspace,
+        43 => Key::Tab,
+        44 => Key::Space,
+        45 => Key::Minus,
+        46 => Key::Equal,
+        47 => Key::LeftBracket,
+        48 => Key::RightBracket,
+        49 => Key::Backslash,
+        51 => Key::Semicolon,
+        52 => Key::Apostrophe,
+        53 => Key::Backquote,
+        54 => Key::Comma,
+        55 => Key::Period,
+        56 => Key::Slash,
+        57 => Key::CapsLock,
+        58..=69 => FUNCTION_KEYS[scancode as usize - 58],
+        71 => Key::ScrollLock,
+        72 => Key::Pause,
+        73 => Key::Insert,
+        74 => Key::Home,
+        75 => Key::PageUp,
+        76 => Key::Delete,
+        77 => Key::End,
+        78 => Key::PageDown,
+        79 => Key::Right,
+        80 => Key::Left,
+        81 => Key::Down,
+        82 => Key::Up,
+        83 => Key::NumLock,
+        84 => Key::NumPadSlash,
+        85 => Key::NumPadAsterisk,
+        86 => Key::NumPadMinus,
+        87 => Key::NumPadPlus,
+        88 => Key::NumPadEnter,
+        89..=98 => NUMPAD_DIGITS[scancode as usize - 89],
+        99 => Key::NumPadDot,
+        101 => Key::Menu,
+        104 => Key::F13,
+        105 => Key::F14,
+        106 => Key::F15,
+        224 => Key::LeftCtrl,
+        225 => Key::LeftShift,
+        226 => Key::LeftAlt,
+        227 => Key::LeftSuper,
+        228 => Key::RightCtrl,
+        229 => Key::RightShift,
+        230 => Key::RightAlt,
+        231 => Key::RightSuper,
+        _ => return None,
+    };
+    Some(key)
+}
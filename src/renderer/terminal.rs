@@ -0,0 +1,352 @@
+// plays the game in a terminal, for SSH sessions and machines without a display server.
+// the frame is drawn with half block characters, each character cell showing two pixels one above
+// the other in 24 bit colour, and keys are read with the terminal in raw mode (set up with stty).
+// terminals only send key presses, never releases, so a key counts as held until its repeats stop coming
+
+use super::{Display, PixelBuffer, Renderer};
+use crate::assets::Texture;
+use crate::input::{InputSource, Key, KeyRepeat};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const FIRST_HOLD: Duration = Duration::from_millis(550); // A bit longer than the usual delay before keys repeat
+const REPEAT_HOLD: Duration = Duration::from_millis(100);
+const SIZE_CHECK_INTERVAL: Duration = Duration::from_secs(1); // The terminal may be resized
+const UPPER_HALF_BLOCK: char = '\u{2580}';
+
+enum Event {
+    Key(Key),
+    Quit,
+}
+
+pub struct TerminalRenderer {
+    frame: Vec<u32>,
+    field_width: usize,
+    field_height: usize,
+    columns: usize,
+    rows: usize,
+    last_size_check: Instant,
+    bars: u32,
+    saved_settings: String, // The terminal's stty settings to put back afterwards
+    events: Receiver<Event>,
+    held: HashMap<Key, Instant>, // Keys that count as down, until when
+    pressed: Vec<Key>,           // First presses since the last frame
+    repeated: Vec<Key>,          // Presses since the last frame, repeats included
+    open: bool,
+}
+
+impl TerminalRenderer {
+    pub fn new(field_width: usize, field_height: usize) -> io::Result<Self> {
+        let saved_settings = stty(&["-g"])?.trim().to_string();
+        stty(&["raw", "-echo"])?;
+        let (columns, rows) = terminal_size();
+
+        let (sender, events) = mpsc::channel();
+        thread::spawn(move || read_keys(sender));
+
+        // Switch to the alternate screen and hide the cursor, Drop puts it all back
+        print!("\x1b[?1049h\x1b[?25l\x1b[2J");
+        io::stdout().flush()?;
+
+        Ok(TerminalRenderer {
+            frame: vec![0; field_width * field_height],
+            field_width,
+            field_height,
+            columns,
+            rows,
+            last_size_check: Instant::now(),
+            bars: 0,
+            saved_settings,
+            events,
+            held: HashMap::new(),
+            pressed: Vec::new(),
+            repeated: Vec::new(),
+            open: true,
+        })
+    }
+
+    fn pixel_buffer(&mut self) -> PixelBuffer<'_> {
+        PixelBuffer::new(&mut self.frame, self.field_width)
+    }
+
+    // Take in the keys that arrived since the last frame
+    fn poll_keys(&mut self) {
+        let now = Instant::now();
+        self.pressed.clear();
+        self.repeated.clear();
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                Event::Key(key) => {
+                    self.repeated.push(key);
+                    let hold = if self.held.get(&key).is_some_and(|&until| until > now) {
+                        REPEAT_HOLD
+                    } else {
+                        self.pressed.push(key);
+                        FIRST_HOLD
+                    };
+                    self.held.insert(key, now + hold);
+                }
+                Event::Quit => self.open = false,
+            }
+        }
+        self.held.retain(|_, until| *until > now);
+    }
+
+    // The frame scaled to fit the terminal, two pixels to a character
+    fn draw_frame(&self) -> String {
+        let scale = (self.columns as f32 / self.field_width as f32).min(2.0 * self.rows as f32 / self.field_height as f32);
+        let offset_x = (self.columns as f32 - self.field_width as f32 * scale) / 2.0;
+        let offset_y = (2.0 * self.rows as f32 - self.field_height as f32 * scale) / 2.0;
+        let sample = |column: usize, pixel_row: usize| {
+            let x = ((column as f32 - offset_x) / scale).floor();
+            let y = ((pixel_row as f32 - offset_y) / scale).floor();
+            if x < 0.0 || y < 0.0 || x as usize >= self.field_width || y as usize >= self.field_height {
+                self.bars
+            } else {
+                self.frame[y as usize * self.field_width + x as usize]
+            }
+        };
+
+        let mut out = String::with_capacity(self.columns * self.rows * 4);
+        out.push_str("\x1b[H");
+        let (mut last_top, mut last_bottom) = (None, None);
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                // Only send colours when they change, most of the screen is the background
+                let top = sample(column, row * 2) & 0xFFFFFF;
+                let bottom = sample(column, row * 2 + 1) & 0xFFFFFF;
+                if last_top != Some(top) {
+                    out.push_str(&format!("\x1b[38;2;{};{};{}m", top >> 16, (top >> 8) & 0xFF, top & 0xFF));
+                    last_top = Some(top);
+                }
+                if last_bottom != Some(bottom) {
+                    out.push_str(&format!("\x1b[48;2;{};{};{}m", bottom >> 16, (bottom >> 8) & 0xFF, bottom & 0xFF));
+                    last_bottom = Some(bottom);
+                }
+                out.push(UPPER_HALF_BLOCK);
+            }
+            // No new line after the last row, it would scroll the screen
+            if row + 1 < self.rows {
+                out.push_str("\r\n");
+            }
+        }
+        out.push_str("\x1b[0m");
+        out
+    }
+}
+
+impl Renderer for TerminalRenderer {
+    fn size(&self) -> (usize, usize) {
+        (self.field_width, self.field_height)
+    }
+
+    fn clear(&mut self, colour: u32) {
+        self.pixel_buffer().clear(colour);
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.pixel_buffer().fill_rect(x, y, width, height, colour);
+    }
+
+    fn fill_rect_smooth(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.pixel_buffer().fill_rect_smooth(x, y, width, height, colour);
+    }
+
+    fn fill_circle(&mut self, x: f32, y: f32, size: f32, colour: u32) {
+        self.pixel_buffer().fill_circle(x, y, size, colour);
+    }
+
+    fn draw_texture(&mut self, x: f32, y: f32, width: f32, height: f32, texture: &Texture) {
+        self.pixel_buffer().draw_texture(x, y, width, height, texture);
+    }
+
+    fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: usize, colour: u32) {
+        self.pixel_buffer().draw_text(x, y, text, scale, colour);
+    }
+
+    fn dim(&mut self) {
+        self.pixel_buffer().dim();
+    }
+
+    fn present(&mut self) -> Result<(), String> {
+        self.poll_keys();
+        if self.last_size_check.elapsed() >= SIZE_CHECK_INTERVAL {
+            let size = terminal_size();
+            if size != (self.columns, self.rows) {
+                (self.columns, self.rows) = size;
+                print!("\x1b[2J");
+            }
+            self.last_size_check = Instant::now();
+        }
+
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(self.draw_frame().as_bytes()).and_then(|()| stdout.flush()).map_err(|e| e.to_string())
+    }
+
+    fn pixels(&self) -> Option<&[u32]> {
+        Some(&self.frame)
+    }
+
+    fn pixels_mut(&mut self) -> Option<&mut [u32]> {
+        Some(&mut self.frame)
+    }
+
+    fn set_border_colour(&mut self, colour: u32) {
+        self.bars = colour;
+    }
+}
+
+impl InputSource for TerminalRenderer {
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.held.contains_key(&key)
+    }
+
+    fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        match repeat {
+            KeyRepeat::Yes => self.repeated.contains(&key),
+            KeyRepeat::No => self.pressed.contains(&key),
+        }
+    }
+
+    fn keys_pressed(&self) -> Vec<Key> {
+        self.pressed.clone()
+    }
+
+    fn keys_repeated(&self) -> Vec<Key> {
+        self.repeated.clone()
+    }
+
+    fn keys_down(&self) -> Vec<Key> {
+        self.held.keys().copied().collect()
+    }
+}
+
+impl Display for TerminalRenderer {}
+
+impl Drop for TerminalRenderer {
+    fn drop(&mut self) {
+        print!("\x1b[0m\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        let _ = stty(&[&self.saved_settings]);
+    }
+}
+
+// Run stty on the terminal rather than whatever stdin happens to be
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty").args(args).stdin(File::open("/dev/tty")?).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Columns and rows, 80x24 if the terminal won't say
+fn terminal_size() -> (usize, usize) {
+    let size = stty(&["size"]).ok().and_then(|size| {
+        let mut numbers = size.split_whitespace().map(|n| n.parse::<usize>().ok());
+        let rows = numbers.next()??;
+        let columns = numbers.next()??;
+        Some((columns, rows))
+    });
+    size.filter(|&(columns, rows)| columns > 0 && rows > 0).unwrap_or((80, 24))
+}
+
+// Runs on its own thread as reading stdin blocks
+fn read_keys(sender: Sender<Event>) {
+    let mut stdin = io::stdin();
+    let mut buffer = [0u8; 64];
+    loop {
+        let len = match stdin.read(&mut buffer) {
+            Ok(0) | Err(_) => {
+                let _ = sender.send(Event::Quit);
+                return;
+            }
+            Ok(len) => len,
+        };
+        for event in parse_keys(&buffer[..len]) {
+            if sender.send(event).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+// Turn what the terminal sent into keys, arrow and function keys come as escape sequences
+fn parse_keys(bytes: &[u8]) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        i += 1;
+        let key = match byte {
+            0x03 => {
+                // Ctrl+C, raw mode stops it interrupting the game so treat it as closing the window
+                events.push(Event::Quit);
+                continue;
+            }
+            0x1B if i < bytes.len() && (bytes[i] == b'[' || bytes[i] == b'O') => {
+                // The sequence runs up to a final byte in the range @ to ~
+                let start = i + 1;
+                let end = bytes[start..].iter().position(|b| (0x40..=0x7E).contains(b)).map(|n| start + n);
+                let Some(end) = end else {
+                    break;
+                };
+                i = end + 1;
+                match escape_sequence(&bytes[start..=end]) {
+                    Some(key) => key,
+                    None => continue,
+                }
+            }
+            0x1B => Key::Escape,
+            b'\r' | b'\n' => Key::Enter,
+            0x7F | 0x08 => Key::Backspace,
+            b'\t' => Key::Tab,
+            b' ' => Key::Space,
+            b'+' | b'=' => Key::Equal,
+            b'-' => Key::Minus,
+            b'0'..=b'9' => DIGITS[(byte - b'0') as usize],
+            b'a'..=b'z' => LETTERS[(byte - b'a') as usize],
+            b'A'..=b'Z' => LETTERS[(byte - b'A') as usize],
+            _ => continue,
+        };
+        events.push(Event::Key(key));
+    }
+    events
+}
+
+// The part after ESC [ or ESC O
+fn escape_sequence(sequence: &[u8]) -> Option<Key> {
+    let key = match sequence {
+        b"A" => Key::Up,
+        b"B" => Key::Down,
+        b"C" => Key::Right,
+        b"D" => Key::Left,
+        b"P" | b"11~" => Key::F1,
+        b"Q" | b"12~" => Key::F2,
+        b"R" | b"13~" => Key::F3,
+        b"S" | b"14~" => Key::F4,
+        b"21~" => Key::F10,
+        b"23~" => Key::F11,
+        b"24~" => Key::F12,
+        _ => return None,
+    };
+    Some(key)
+}
+
+const LETTERS: [Key; 26] = [
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+    Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+];
+
+const DIGITS: [Key; 10] = [
+    Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9,
+];
@@ -0,0 +1,252 @@
+// runs the game on a thread of its own, away from the display. the game draws into a ThreadDisplay,
+// which keeps its frame like any other backend and sends a copy of each finished one over a channel.
+// the Presenter on the main thread, where windows have to live, shows the newest frame on the real display
+// and sends back the input read from it. a display that is slow to show frames only misses some of them
+// without holding up the physics, and a slow simulation leaves the window showing the last frame but responding
+
+use super::{Display, PixelBuffer, Renderer};
+use crate::assets::Texture;
+use crate::input::{InputSource, Key, KeyRepeat};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender, TrySendError};
+use std::time::Duration;
+
+// A finished frame on its way to the main thread
+struct Frame {
+    pixels: Vec<u32>,
+    border_colour: u32,
+    fullscreen: Option<bool>, // Set when the game switched fullscreen on or off
+}
+
+// Everything read from the display after showing a frame
+#[derive(Default)]
+struct InputSnapshot {
+    open: bool,
+    held: Vec<Key>,
+    pressed: Vec<Key>,
+    repeated: Vec<Key>,
+    mouse: Option<(f32, f32)>,
+}
+
+impl InputSnapshot {
+    fn read(display: &dyn Display) -> Self {
+        InputSnapshot {
+            open: display.is_open(),
+            held: display.keys_down(),
+            pressed: display.keys_pressed(),
+            repeated: display.keys_repeated(),
+            mouse: display.mouse_position(),
+        }
+    }
+}
+
+// The game's end, drawn into and read from on the game thread
+pub struct ThreadDisplay {
+    frame: Vec<u32>,
+    field_width: usize,
+    border_colour: u32,
+    fullscreen: Option<bool>,
+    input: InputSnapshot,
+    frames: SyncSender<Frame>,
+    spare_frames: Receiver<Vec<u32>>, // Frames the main thread has shown, to send the next ones in
+    unsent: Option<Vec<u32>>,         // A frame the display was too busy to take
+    inputs: Receiver<InputSnapshot>,
+}
+
+// The display's end, on the main thread
+pub struct Presenter {
+    display: Box<dyn Display>,
+    frames: Receiver<Frame>,
+    spare_frames: Sender<Vec<u32>>,
+    inputs: Sender<InputSnapshot>,
+    frame_time: Duration,
+}
+
+// Both ends for a display, frame_time being how often the game sends frames
+pub fn split(display: Box<dyn Display>, frame_time: Duration) -> (ThreadDisplay, Presenter) {
+    let (field_width, field_height) = display.size();
+    // Room for one frame, so the game never runs more than a frame ahead of what's shown
+    let (frame_sender, frames) = mpsc::sync_channel(1);
+    let (spare_sender, spare_frames) = mpsc::channel();
+    let (input_sender, inputs) = mpsc::channel();
+    let thread_display = ThreadDisplay {
+        frame: vec![0; field_width * field_height],
+        field_width,
+        border_colour: 0,
+        fullscreen: None,
+        input: InputSnapshot::read(display.as_ref()),
+        frames: frame_sender,
+        spare_frames,
+        unsent: None,
+        inputs,
+    };
+    let presenter = Presenter {
+        display,
+        frames,
+        spare_frames: spare_sender,
+        inputs: input_sender,
+        frame_time,
+    };
+    (thread_display, presenter)
+}
+
+impl ThreadDisplay {
+    fn pixel_buffer(&mut self) -> PixelBuffer<'_> {
+        PixelBuffer::new(&mut self.frame, self.field_width)
+    }
+
+    // Take in everything read from the display since the last frame. Held keys and the mouse go by the
+    // latest reading, presses from all of them count so none are lost when the display shows frames faster
+    fn read_input(&mut self) {
+        self.input.pressed.clear();
+        self.input.repeated.clear();
+        for input in self.inputs.try_iter() {
+            self.input.open = input.open;
+            self.input.held = input.held;
+            self.input.mouse = input.mouse;
+            self.input.pressed.extend(input.pressed);
+            self.input.repeated.extend(input.repeated);
+        }
+    }
+}
+
+impl Renderer for ThreadDisplay {
+    fn size(&self) -> (usize, usize) {
+        (self.field_width, self.frame.len() / self.field_width)
+    }
+
+    fn clear(&mut self, colour: u32) {
+        self.pixel_buffer().clear(colour);
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.pixel_buffer().fill_rect(x, y, width, height, colour);
+    }
+
+    fn fill_rect_smooth(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.pixel_buffer().fill_rect_smooth(x, y, width, height, colour);
+    }
+
+    fn fill_circle(&mut self, x: f32, y: f32, size: f32, colour: u32) {
+        self.pixel_buffer().fill_circle(x, y, size, colour);
+    }
+
+    fn draw_texture(&mut self, x: f32, y: f32, width: f32, height: f32, texture: &Texture) {
+        self.pixel_buffer().draw_texture(x, y, width, height, texture);
+    }
+
+    fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: usize, colour: u32) {
+        self.pixel_buffer().draw_text(x, y, text, scale, colour);
+    }
+
+    fn dim(&mut self) {
+        self.pixel_buffer().dim();
+    }
+
+    fn present(&mut self) -> Result<(), String> {
+        let spare = self.unsent.take().or_else(|| self.spare_frames.try_recv().ok());
+        let mut pixels = spare.unwrap_or_else(|| vec![0; self.frame.len()]);
+        pixels.copy_from_slice(&self.frame);
+        let frame = Frame {
+            pixels,
+            border_colour: self.border_colour,
+            fullscreen: self.fullscreen.take(),
+        };
+        match self.frames.try_send(frame) {
+            Ok(()) => {}
+            // Still showing the last frame, so this one is skipped
+            Err(TrySendError::Full(frame)) => {
+                self.fullscreen = frame.fullscreen;
+                self.unsent = Some(frame.pixels);
+            }
+            Err(TrySendError::Disconnected(_)) => return Err("the display has closed".to_string()),
+        }
+        self.read_input();
+        Ok(())
+    }
+
+    fn pixels(&self) -> Option<&[u32]> {
+        Some(&self.frame)
+    }
+
+    fn pixels_mut(&mut self) -> Option<&mut [u32]> {
+        Some(&mut self.frame)
+    }
+
+    fn set_border_colour(&mut self, colour: u32) {
+        self.border_colour = colour;
+    }
+}
+
+impl InputSource for ThreadDisplay {
+    fn is_open(&self) -> bool {
+        self.input.open
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.input.held.contains(&key)
+    }
+
+    fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        match repeat {
+            KeyRepeat::Yes => self.input.repeated.contains(&key),
+            KeyRepeat::No => self.input.pressed.contains(&key),
+        }
+    }
+
+    fn keys_pressed(&self) -> Vec<Key> {
+        self.input.pressed.clone()
+    }
+
+    fn keys_repeated(&self) -> Vec<Key> {
+        self.input.repeated.clone()
+    }
+
+    fn keys_down(&self) -> Vec<Key> {
+        self.input.held.clone()
+    }
+
+    fn mouse_position(&self) -> Option<(f32, f32)> {
+        self.input.mouse
+    }
+}
+
+// The real display switches when the frame gets to it
+impl Display for ThreadDisplay {
+    fn set_fullscreen(&mut self, fullscreen: bool) -> Result<(), String> {
+        self.fullscreen = Some(fullscreen);
+        Ok(())
+    }
+}
+
+impl Presenter {
+    // Show frames as they come until the game stops sending them
+    pub fn run(mut self) -> Result<(), String> {
+        loop {
+            match self.frames.recv_timeout(self.frame_time) {
+                Ok(frame) => self.show(frame)?,
+                // Nothing new for a frame, show the last one again so the window keeps responding
+                Err(RecvTimeoutError::Timeout) => self.display.present()?,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+            if self.inputs.send(InputSnapshot::read(self.display.as_ref())).is_err() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn show(&mut self, frame: Frame) -> Result<(), String> {
+        if let Some(fullscreen) = frame.fullscreen {
+            if let Err(e) = self.display.set_fullscreen(fullscreen) {
+                eprintln!("Couldn't switch fullscreen: {}", e);
+            }
+        }
+        if let Some(pixels) = self.display.pixels_mut() {
+            pixels.copy_from_slice(&frame.pixels);
+        }
+        self.display.set_border_colour(frame.border_colour);
+        let shown = self.display.present();
+        // The game may have finished already, leaving nobody to take the frame back
+        let _ = self.spare_frames.send(frame.pixels);
+        shown
+    }
+}
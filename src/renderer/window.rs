@@ -0,0 +1,164 @@
+// the default backend, draws into a frame the size of the playfield, or the lower render resolution, and
+// shows it in a minifb window scaled to fit
+
+use super::{Display, PixelBuffer, Renderer};
+use crate::assets::Texture;
+use crate::input::{InputSource, Key, KeyRepeat};
+use crate::viewport::{Screen, Viewport};
+use minifb::{MouseMode, Window, WindowOptions};
+
+pub struct WindowRenderer {
+    window: Window,
+    frame: Vec<u32>,
+    frame_width: usize,
+    frame_height: usize,
+    screen: Screen, // The sizes asked for, a new window opens at its window size
+    viewport: Viewport,
+    scaled: Vec<u32>, // The scaled frame when the window isn't the frame size
+    bars: u32,        // Colour around the playfield when the window is a different shape
+}
+
+impl WindowRenderer {
+    pub fn new(screen: Screen, fullscreen: bool) -> minifb::Result<Self> {
+        let (frame_width, frame_height) = (screen.frame_width, screen.frame_height);
+        Ok(WindowRenderer {
+            window: create_window(screen.window_width, screen.window_height, fullscreen)?,
+            frame: vec![0; frame_width * frame_height],
+            frame_width,
+            frame_height,
+            screen,
+            viewport: Viewport::fit(frame_width, frame_height, frame_width, frame_height),
+            scaled: Vec::new(),
+            bars: 0,
+        })
+    }
+
+    fn pixel_buffer(&mut self) -> PixelBuffer<'_> {
+        PixelBuffer::new(&mut self.frame, self.frame_width)
+    }
+}
+
+impl Renderer for WindowRenderer {
+    fn size(&self) -> (usize, usize) {
+        (self.frame_width, self.frame_height)
+    }
+
+    fn clear(&mut self, colour: u32) {
+        self.pixel_buffer().clear(colour);
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.pixel_buffer().fill_rect(x, y, width, height, colour);
+    }
+
+    fn fill_rect_smooth(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.pixel_buffer().fill_rect_smooth(x, y, width, height, colour);
+    }
+
+    fn fill_circle(&mut self, x: f32, y: f32, size: f32, colour: u32) {
+        self.pixel_buffer().fill_circle(x, y, size, colour);
+    }
+
+    fn draw_texture(&mut self, x: f32, y: f32, width: f32, height: f32, texture: &Texture) {
+        self.pixel_buffer().draw_texture(x, y, width, height, texture);
+    }
+
+    fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: usize, colour: u32) {
+        self.pixel_buffer().draw_text(x, y, text, scale, colour);
+    }
+
+    fn dim(&mut self) {
+        self.pixel_buffer().dim();
+    }
+
+    fn present(&mut self) -> Result<(), String> {
+        let (window_width, window_height) = self.window.get_size();
+        if window_width == 0 || window_height == 0 {
+            // Minimised, there is nothing to draw into but events still need handling
+            self.window.update();
+            return Ok(());
+        }
+        if (window_width, window_height) != (self.viewport.window_width, self.viewport.window_height) {
+            self.viewport = self.screen.fit(window_width, window_height);
+            self.scaled = vec![0; window_width * window_height];
+        }
+
+        let shown = if self.viewport.is_identity(self.frame_width, self.frame_height) {
+            self.window.update_with_buffer(&self.frame, self.frame_width, self.frame_height)
+        } else {
+            let (frame, scaling) = (&self.frame, self.screen.scaling);
+            self.viewport.blit(frame, self.frame_width, self.frame_height, &mut self.scaled, self.bars, scaling);
+            self.window.update_with_buffer(&self.scaled, window_width, window_height)
+        };
+        shown.map_err(|e| e.to_string())
+    }
+
+    fn pixels(&self) -> Option<&[u32]> {
+        Some(&self.frame)
+    }
+
+    fn pixels_mut(&mut self) -> Option<&mut [u32]> {
+        Some(&mut self.frame)
+    }
+
+    fn set_border_colour(&mut self, colour: u32) {
+        self.bars = colour;
+    }
+}
+
+impl InputSource for WindowRenderer {
+    fn is_open(&self) -> bool {
+        self.window.is_open()
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.window.is_key_down(key)
+    }
+
+    fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        self.window.is_key_pressed(key, repeat)
+    }
+
+    fn keys_pressed(&self) -> Vec<Key> {
+        self.window.get_keys_pressed(KeyRepeat::No)
+    }
+
+    fn keys_repeated(&self) -> Vec<Key> {
+        self.window.get_keys_pressed(KeyRepeat::Yes)
+    }
+
+    fn keys_down(&self) -> Vec<Key> {
+        self.window.get_keys()
+    }
+
+    fn mouse_position(&self) -> Option<(f32, f32)> {
+        let (x, y) = self.window.get_mouse_pos(MouseMode::Pass)?;
+        Some(self.viewport.to_field(x, y))
+    }
+}
+
+impl Display for WindowRenderer {
+    // minifb can't change the window style, so a new window replaces the old one
+    fn set_fullscreen(&mut self, fullscreen: bool) -> Result<(), String> {
+        let (width, height) = (self.screen.window_width, self.screen.window_height);
+        self.window = create_window(width, height, fullscreen).map_err(|e| e.to_string())?;
+        // The new window starts at its first size, present() picks up the difference and any later resize
+        self.viewport = Viewport::fit(self.frame_width, self.frame_height, self.frame_width, self.frame_height);
+        self.scaled = Vec::new();
+        Ok(())
+    }
+}
+
+// minifb has no true fullscreen, so fullscreen is a borderless window kept above everything else
+fn create_window(width: usize, height: usize, fullscreen: bool) -> minifb::Result<Window> {
+    let options = WindowOptions {
+        borderless: fullscreen,
+        topmost: fullscreen,
+        resize: true,
+        ..WindowOptions::default()
+    };
+    let mut window = Window::new("Game Window", width, height, options)?;
+    // The frontend paces the frames, minifb's own limit would only add sleeps of its own
+    window.set_target_fps(0);
+    Ok(window)
+}
@@ -0,0 +1,186 @@
+// recorded games, the seed plus the player's input for every frame, enough to play a game out again exactly.
+// replays are only faithful with the same config as the game was recorded with
+//
+// file layout, all little endian:
+//   "PPRP", version u8, mode u8, seed u64,
+//   starting power-up count u8 and a u8 for each (from version 2), frame count u32
+//   then per frame: flags u8, frame time f32, paddle speed f32,
+//   top paddle speed f32 in 2 player games, mouse x f32 when FLAG_MOUSE is set
+
+use crate::powerups::{self, PowerUpKind};
+use crate::GameMode;
+use std::path::Path;
+
+pub const REPLAY_FILE: &str = "last_replay.rpl";
+const MAGIC: &[u8; 4] = b"PPRP";
+const VERSION: u8 = 2; // Version 1 files are still read, they have no starting power-ups
+const FLAG_SERVE: u8 = 1;
+const FLAG_MOUSE: u8 = 2;
+const FLAG_FIRE: u8 = 4;
+const FIRST_PLUGIN_ID: u8 = 5; // Mode ids from here on are registered modes
+
+// Everything from outside the simulation that one frame of update() depends on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameInput {
+    pub frame_time: f32,
+    pub paddle_vel: f32,
+    pub top_paddle_vel: f32,
+    pub mouse_x: Option<f32>,
+    pub serve: bool, // The serve pause ended on this frame
+    pub fire: bool,  // The serve key was held, which fires the laser
+}
+
+pub struct Replay {
+    pub seed: u64,
+    pub mode: GameMode,
+    pub power_ups: Vec<PowerUpKind>, // Bought in the shop and used at the start of the game
+    pub frames: Vec<FrameInput>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, mode: GameMode) -> Self {
+        Replay {
+            seed,
+            mode,
+            power_ups: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let mut bytes = Vec::with_capacity(18 + self.frames.len() * 9);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(mode_id(self.mode));
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(self.power_ups.len() as u8);
+        bytes.extend(self.power_ups.iter().map(|&kind| power_up_id(kind)));
+        bytes.extend_from_slice(&(self.frames.len() as u32).to_le_bytes());
+        for frame in &self.frames {
+            let mut flags = 0;
+            if frame.serve {
+                flags |= FLAG_SERVE;
+            }
+            if frame.mouse_x.is_some() {
+                flags |= FLAG_MOUSE;
+            }
+            if frame.fire {
+                flags |= FLAG_FIRE;
+            }
+            bytes.push(flags);
+            bytes.extend_from_slice(&frame.frame_time.to_le_bytes());
+            bytes.extend_from_slice(&frame.paddle_vel.to_le_bytes());
+            if self.mode == GameMode::TwoPlayer {
+                bytes.extend_from_slice(&frame.top_paddle_vel.to_le_bytes());
+            }
+            if let Some(mouse_x) = frame.mouse_x {
+                bytes.extend_from_slice(&mouse_x.to_le_bytes());
+            }
+        }
+        std::fs::write(path, bytes)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+        let mut reader = Reader { bytes: &bytes, pos: 0 };
+
+        if reader.take(4)? != MAGIC {
+            return Err("not a replay file".to_string());
+        }
+        let version = reader.u8()?;
+        if version == 0 || version > VERSION {
+            return Err(format!("unsupported replay version {}", version));
+        }
+        let mode = mode_from_id(reader.u8()?).ok_or("unknown game mode")?;
+        let seed = reader.u64()?;
+        let mut power_ups = Vec::new();
+        if version >= 2 {
+            for _ in 0..reader.u8()? {
+                power_ups.push(power_up_from_id(reader.u8()?).ok_or("unknown power-up")?);
+            }
+        }
+        let count = reader.u32()?;
+
+        let mut frames = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let flags = reader.u8()?;
+            let frame_time = reader.f32()?;
+            let paddle_vel = reader.f32()?;
+            let top_paddle_vel = if mode == GameMode::TwoPlayer { reader.f32()? } else { 0.0 };
+            let mouse_x = if flags & FLAG_MOUSE != 0 { Some(reader.f32()?) } else { None };
+            frames.push(FrameInput {
+                frame_time,
+                paddle_vel,
+                top_paddle_vel,
+                mouse_x,
+                serve: flags & FLAG_SERVE != 0,
+                fire: flags & FLAG_FIRE != 0,
+            });
+        }
+        Ok(Replay { seed, mode, power_ups, frames })
+    }
+}
+
+fn mode_id(mode: GameMode) -> u8 {
+    match mode {
+        GameMode::Solo => 0,
+        GameMode::TwoPlayer => 1,
+        GameMode::VsAi => 2,
+        GameMode::Breakout => 3,
+        GameMode::Survival => 4,
+        GameMode::Plugin(index) => FIRST_PLUGIN_ID + index as u8, // In the order the plugins were registered
+    }
+}
+
+fn mode_from_id(id: u8) -> Option<GameMode> {
+    match id {
+        0 => Some(GameMode::Solo),
+        1 => Some(GameMode::TwoPlayer),
+        2 => Some(GameMode::VsAi),
+        3 => Some(GameMode::Breakout),
+        4 => Some(GameMode::Survival),
+        id => Some(GameMode::Plugin((id - FIRST_PLUGIN_ID) as usize)),
+    }
+}
+
+fn power_up_id(kind: PowerUpKind) -> u8 {
+    powerups::KINDS.iter().position(|&known| known == kind).unwrap_or_default() as u8
+}
+
+fn power_up_from_id(id: u8) -> Option<PowerUpKind> {
+    powerups::KINDS.get(id as usize).copied()
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], String> {
+        let end = self.pos + n;
+        let slice = self.bytes.get(self.pos..end).ok_or("replay file is truncated")?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
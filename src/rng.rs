@@ -0,0 +1,47 @@
+// small seedable random number generator (xorshift64*), the same seed always plays out the same game
+
+use crate::clock;
+
+#[derive(Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck on zero, so mix the seed and make sure some bits are set
+        Rng {
+            state: (seed ^ 0x9E3779B97F4A7C15) | 1,
+        }
+    }
+
+    // A seed that differs from run to run, used when none is given on the command line
+    pub fn seed_from_time() -> u64 {
+        clock::unix_time().as_nanos() as u64
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    // Uniform in [0.0, 1.0)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    // Uniform in 0..n
+    pub fn index(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn coin_flip(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+}
@@ -0,0 +1,283 @@
+// lua scripting, built with the lua feature: a script named in the config is told what happens in a game
+// and can change it, so new behaviours don't need a fork of the crate. like SDL2, the Lua library (5.4 or 5.3)
+// is loaded when the game starts rather than linked, so only the library needs to be installed.
+//
+// the script can define any of these, each called with a number:
+//   on_level_start(level), on_paddle_hit(hits this game), on_life_lost(lives left)
+// and call these from them, carried out once the hook returns:
+//   game.spawn_ball(x, y, vel_x, vel_y)
+//   game.spawn_block(x, y, width, height)
+//   game.spawn_pickup(kind, x), kind one of "wide", "slow", "life", "multi",
+//     "heavy", "ghost", "split", "shrink", "invert", "fast", "shield", "laser" or "magnet"
+//   game.set_ball_speed(speed), pixels/second for the balls in play and the next serve
+//   game.set_paddle_speed(speed), pixels/second
+
+use crate::powerups::{self, PowerUpKind};
+use libloading::Library;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::ptr;
+use std::sync::OnceLock;
+
+// The library names Lua goes by on each platform, newest first
+const LIBRARY_NAMES: [&str; 10] = [
+    "liblua5.4.so.0",
+    "liblua5.4.so",
+    "liblua.so.5.4",
+    "liblua.5.4.dylib",
+    "lua54.dll",
+    "liblua5.3.so.0",
+    "liblua5.3.so",
+    "liblua.so.5.3",
+    "liblua.5.3.dylib",
+    "lua53.dll",
+];
+
+const LUA_OK: c_int = 0;
+const LUA_TFUNCTION: c_int = 6;
+
+type LuaState = std::ffi::c_void;
+type CFunction = unsafe extern "C" fn(*mut LuaState) -> c_int;
+type KFunction = unsafe extern "C" fn(*mut LuaState, c_int, isize) -> c_int;
+
+// What happened in the game, each calls the script function of the same name
+#[derive(Clone, Copy, Debug)]
+pub enum Hook {
+    LevelStart { level: u32 },
+    PaddleHit { hits: u32 },
+    LifeLost { lives: i32 },
+}
+
+impl Hook {
+    fn function(self) -> &'static CStr {
+        match self {
+            Hook::LevelStart { .. } => c"on_level_start",
+            Hook::PaddleHit { .. } => c"on_paddle_hit",
+            Hook::LifeLost { .. } => c"on_life_lost",
+        }
+    }
+
+    fn argument(self) -> i64 {
+        match self {
+            Hook::LevelStart { level } => level.into(),
+            Hook::PaddleHit { hits } => hits.into(),
+            Hook::LifeLost { lives } => lives.into(),
+        }
+    }
+}
+
+// A change the script asked for, for the game to make
+#[derive(Clone, Copy, Debug)]
+pub enum Command {
+    SpawnBall { x: f32, y: f32, vel_x: f32, vel_y: f32 },
+    SpawnBlock { x: f32, y: f32, width: f32, height: f32 },
+    SpawnPickup { kind: PowerUpKind, x: f32 },
+    SetBallSpeed(f32),
+    SetPaddleSpeed(f32),
+}
+
+// The handful of Lua functions used here
+struct Lua {
+    new_state: unsafe extern "C" fn() -> *mut LuaState,
+    open_libs: unsafe extern "C" fn(*mut LuaState),
+    close: unsafe extern "C" fn(*mut LuaState),
+    load_file: unsafe extern "C" fn(*mut LuaState, *const c_char, *const c_char) -> c_int,
+    pcall: unsafe extern "C" fn(*mut LuaState, c_int, c_int, c_int, isize, Option<KFunction>) -> c_int,
+    get_global: unsafe extern "C" fn(*mut LuaState, *const c_char) -> c_int,
+    set_global: unsafe extern "C" fn(*mut LuaState, *const c_char),
+    set_top: unsafe extern "C" fn(*mut LuaState, c_int),
+    create_table: unsafe extern "C" fn(*mut LuaState, c_int, c_int),
+    set_field: unsafe extern "C" fn(*mut LuaState, c_int, *const c_char),
+    push_integer: unsafe extern "C" fn(*mut LuaState, i64),
+    push_closure: unsafe extern "C" fn(*mut LuaState, CFunction, c_int),
+    to_number: unsafe extern "C" fn(*mut LuaState, c_int, *mut c_int) -> f64,
+    to_string: unsafe extern "C" fn(*mut LuaState, c_int, *mut usize) -> *const c_char,
+    _library: Library, // Must outlive the function pointers above
+}
+
+// Loaded once, the functions the script calls need it without a way to be handed it
+static LUA: OnceLock<Result<Lua, String>> = OnceLock::new();
+
+thread_local! {
+    // Filled by the game.* functions while a hook runs
+    static COMMANDS: RefCell<Vec<Command>> = const { RefCell::new(Vec::new()) };
+}
+
+impl Lua {
+    fn get() -> Result<&'static Lua, String> {
+        LUA.get_or_init(Lua::load).as_ref().map_err(String::clone)
+    }
+
+    fn load() -> Result<Self, String> {
+        // SAFETY: loading Lua runs no initialisers that matter
+        let library = LIBRARY_NAMES
+            .iter()
+            .find_map(|name| unsafe { Library::new(name) }.ok())
+            .ok_or_else(|| "couldn't find the Lua library, is Lua 5.4 installed?".to_string())?;
+
+        // SAFETY: each type below is the function's C signature from the Lua headers, the same in 5.3 and 5.4
+        unsafe fn symbol<T: Copy>(library: &Library, name: &str) -> Result<T, String> {
+            let symbol = library.get::<T>(name.as_bytes()).map_err(|e| format!("{}: {}", name, e))?;
+            Ok(*symbol)
+        }
+        unsafe {
+            Ok(Lua {
+                new_state: symbol(&library, "luaL_newstate")?,
+                open_libs: symbol(&library, "luaL_openlibs")?,
+                close: symbol(&library, "lua_close")?,
+                load_file: symbol(&library, "luaL_loadfilex")?,
+                pcall: symbol(&library, "lua_pcallk")?,
+                get_global: symbol(&library, "lua_getglobal")?,
+                set_global: symbol(&library, "lua_setglobal")?,
+                set_top: symbol(&library, "lua_settop")?,
+                create_table: symbol(&library, "lua_createtable")?,
+                set_field: symbol(&library, "lua_setfield")?,
+                push_integer: symbol(&library, "lua_pushinteger")?,
+                push_closure: symbol(&library, "lua_pushcclosure")?,
+                to_number: symbol(&library, "lua_tonumberx")?,
+                to_string: symbol(&library, "lua_tolstring")?,
+                _library: library,
+            })
+        }
+    }
+
+    // The number at a stack index, None if it isn't one
+    unsafe fn number(&self, state: *mut LuaState, index: c_int) -> Option<f32> {
+        let mut is_number = 0;
+        let value = (self.to_number)(state, index, &mut is_number);
+        (is_number != 0).then_some(value as f32)
+    }
+
+    // The string at a stack index, None if it isn't one
+    unsafe fn string(&self, state: *mut LuaState, index: c_int) -> Option<String> {
+        let text = (self.to_string)(state, index, ptr::null_mut());
+        (!text.is_null()).then(|| CStr::from_ptr(text).to_string_lossy().into_owned())
+    }
+
+    // Pop the error message a failed call or load left on the stack
+    unsafe fn pop_error(&self, state: *mut LuaState) -> String {
+        let message = self.string(state, -1).unwrap_or_else(|| "unknown error".to_string());
+        (self.set_top)(state, -2);
+        message
+    }
+}
+
+pub struct Script {
+    lua: &'static Lua,
+    state: *mut LuaState,
+}
+
+impl Script {
+    // Run the script file once, which defines its hooks
+    pub fn load(path: &str) -> Result<Self, String> {
+        let lua = Lua::get()?;
+        let file = CString::new(path).map_err(|e| e.to_string())?;
+        // SAFETY: the state is only used from here and run(), and closed once in drop()
+        unsafe {
+            let state = (lua.new_state)();
+            if state.is_null() {
+                return Err("Lua ran out of memory".to_string());
+            }
+            let script = Script { lua, state };
+            (lua.open_libs)(state);
+
+            let functions: [(&CStr, CFunction); 5] = [
+                (c"spawn_ball", spawn_ball),
+                (c"spawn_block", spawn_block),
+                (c"spawn_pickup", spawn_pickup),
+                (c"set_ball_speed", set_ball_speed),
+                (c"set_paddle_speed", set_paddle_speed),
+            ];
+            (lua.create_table)(state, 0, functions.len() as c_int);
+            for (name, function) in functions {
+                (lua.push_closure)(state, function, 0);
+                (lua.set_field)(state, -2, name.as_ptr());
+            }
+            (lua.set_global)(state, c"game".as_ptr());
+
+            if (lua.load_file)(state, file.as_ptr(), ptr::null()) != LUA_OK || (lua.pcall)(state, 0, 0, 0, 0, None) != LUA_OK {
+                return Err(lua.pop_error(state));
+            }
+            Ok(script)
+        }
+    }
+
+    // Call the script's function for the hook if it has one, and return what it asked for.
+    // A script error is printed and the game carries on as if it hadn't asked for anything
+    pub fn run(&mut self, hook: Hook) -> Vec<Command> {
+        let lua = self.lua;
+        // SAFETY: the state is open until drop() and each call leaves the stack as it found it
+        unsafe {
+            if (lua.get_global)(self.state, hook.function().as_ptr()) != LUA_TFUNCTION {
+                (lua.set_top)(self.state, -2);
+                return Vec::new();
+            }
+            (lua.push_integer)(self.state, hook.argument());
+            if (lua.pcall)(self.state, 1, 0, 0, 0, None) != LUA_OK {
+                eprintln!("Script error in {}: {}", hook.function().to_string_lossy(), lua.pop_error(self.state));
+                COMMANDS.with_borrow_mut(Vec::clear);
+                return Vec::new();
+            }
+        }
+        COMMANDS.with_borrow_mut(std::mem::take)
+    }
+}
+
+impl Drop for Script {
+    fn drop(&mut self) {
+        // SAFETY: the state was opened in load() and nothing uses it after this
+        unsafe { (self.lua.close)(self.state) };
+    }
+}
+
+// The numbers passed to a game.* function, None if any is missing or not a number
+unsafe fn numbers<const N: usize>(state: *mut LuaState) -> Option<[f32; N]> {
+    let lua = Lua::get().ok()?;
+    let mut values = [0.0; N];
+    for (index, value) in values.iter_mut().enumerate() {
+        *value = lua.number(state, index as c_int + 1)?;
+    }
+    Some(values)
+}
+
+// Queue a command for the game, or say what was wrong with the call. Lua's own error
+// reporting would jump straight back over the Rust code that called it, so it isn't used
+fn queue(function: &str, command: Option<Command>) -> c_int {
+    match command {
+        Some(command) => COMMANDS.with_borrow_mut(|commands| commands.push(command)),
+        None => eprintln!("Script error: bad arguments to game.{}", function),
+    }
+    0 // Nothing returned to the script
+}
+
+unsafe extern "C" fn spawn_ball(state: *mut LuaState) -> c_int {
+    let command = numbers(state).map(|[x, y, vel_x, vel_y]| Command::SpawnBall { x, y, vel_x, vel_y });
+    queue("spawn_ball", command)
+}
+
+unsafe extern "C" fn spawn_block(state: *mut LuaState) -> c_int {
+    let command = numbers(state)
+        .filter(|[_, _, width, height]| *width > 0.0 && *height > 0.0)
+        .map(|[x, y, width, height]| Command::SpawnBlock { x, y, width, height });
+    queue("spawn_block", command)
+}
+
+unsafe extern "C" fn spawn_pickup(state: *mut LuaState) -> c_int {
+    let kind = Lua::get()
+        .ok()
+        .and_then(|lua| lua.string(state, 1))
+        .and_then(|name| powerups::KINDS.into_iter().find(|kind| kind.key() == name));
+    let x = Lua::get().ok().and_then(|lua| lua.number(state, 2));
+    let command = kind.zip(x).map(|(kind, x)| Command::SpawnPickup { kind, x });
+    queue("spawn_pickup", command)
+}
+
+unsafe extern "C" fn set_ball_speed(state: *mut LuaState) -> c_int {
+    let command = numbers(state).filter(|[speed]| *speed > 0.0).map(|[speed]| Command::SetBallSpeed(speed));
+    queue("set_ball_speed", command)
+}
+
+unsafe extern "C" fn set_paddle_speed(state: *mut LuaState) -> c_int {
+    let command = numbers(state).filter(|[speed]| *speed >= 0.0).map(|[speed]| Command::SetPaddleSpeed(speed));
+    queue("set_paddle_speed", command)
+}
@@ -0,0 +1,91 @@
+// the shop, opened from the title screen: coins caught in games buy paddle skins without scoring for them,
+// and power-ups that are used up at the start of the next game with lives
+
+use crate::locale::Locale;
+use crate::menu;
+use crate::palette::Palette;
+use crate::powerups::PowerUpKind;
+use crate::profile::Profile;
+use crate::renderer::Renderer;
+use crate::skins::SKINS;
+
+const ITEMS_Y: usize = 200;
+const ITEM_SPACING: usize = 32;
+const ITEM_SCALE: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Item {
+    Skin(usize), // Index into SKINS
+    PowerUp(PowerUpKind),
+}
+
+const ITEMS: [Item; 8] = [
+    Item::Skin(1),
+    Item::Skin(2),
+    Item::Skin(3),
+    Item::Skin(4),
+    Item::PowerUp(PowerUpKind::WidePaddle),
+    Item::PowerUp(PowerUpKind::SlowBall),
+    Item::PowerUp(PowerUpKind::MultiBall),
+    Item::PowerUp(PowerUpKind::ExtraLife),
+];
+
+impl Item {
+    pub fn price(self) -> u32 {
+        match self {
+            Item::Skin(skin) => SKINS[skin].price,
+            Item::PowerUp(PowerUpKind::WidePaddle | PowerUpKind::SlowBall) => 15,
+            Item::PowerUp(PowerUpKind::ExtraLife) => 30,
+            Item::PowerUp(_) => 20,
+        }
+    }
+
+    fn label(self, locale: &Locale, profile: &Profile) -> String {
+        match self {
+            Item::Skin(skin) if profile.is_unlocked(skin) => {
+                locale.format("shop.skin_owned", &[&locale.name("skin", SKINS[skin].name)])
+            }
+            Item::Skin(skin) => locale.format("shop.skin", &[&locale.name("skin", SKINS[skin].name), &self.price()]),
+            Item::PowerUp(kind) => {
+                let name = locale.get(kind.name_key());
+                locale.format("shop.power_up", &[&name, &self.price(), &profile.stocked(kind)])
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Shop {
+    selected: usize,
+}
+
+impl Shop {
+    pub fn move_up(&mut self) {
+        self.selected = (self.selected + ITEMS.len() - 1) % ITEMS.len();
+    }
+
+    pub fn move_down(&mut self) {
+        self.selected = (self.selected + 1) % ITEMS.len();
+    }
+
+    pub fn selected_item(&self) -> Item {
+        ITEMS[self.selected]
+    }
+
+    // quit is the name of the key that goes back to the title screen
+    pub fn render(&self, renderer: &mut dyn Renderer, palette: &Palette, locale: &Locale, profile: &Profile, quit: &str) {
+        menu::draw_centred(renderer, 80, locale.get("shop.title"), 6, palette.hud);
+        menu::draw_centred(renderer, 150, &locale.format("shop.coins", &[&profile.coins()]), 2, palette.hud);
+        for (i, item) in ITEMS.iter().enumerate() {
+            let y = ITEMS_Y + i * ITEM_SPACING;
+            let label = item.label(locale, profile);
+            if i == self.selected {
+                menu::draw_centred(renderer, y, &format!("> {} <", label), ITEM_SCALE, palette.hud);
+            } else {
+                menu::draw_centred(renderer, y, &label, ITEM_SCALE, palette.hud_dim);
+            }
+        }
+        let help = locale.format("shop.help", &[&quit]);
+        menu::draw_centred(renderer, ITEMS_Y + ITEMS.len() * ITEM_SPACING + 20, &help, 2, palette.hud_dim);
+    }
+}
@@ -0,0 +1,44 @@
+// paddle skins, a cosmetic look for player 1's paddle. each one is unlocked by scoring enough points in one game
+// or bought in the shop, see profile.rs for what the player has
+
+use crate::assets::Texture;
+use crate::palette;
+
+const STRIPE_WIDTH: usize = 6; // pixels
+
+#[derive(Clone, Copy, Debug)]
+enum Look {
+    Theme,              // The colour theme's paddle colour, or the config's paddle sprite
+    Colour(u32),        // One plain colour
+    Stripes(u32, u32),  // Slanted stripes of two colours
+    Gradient(u32, u32), // From one colour at the left end to the other at the right
+}
+
+pub struct Skin {
+    pub name: &'static str, // In the profile and the language files
+    pub score: i32,         // Points in one game that unlock it
+    pub price: u32,         // Coins to buy it in the shop instead
+    look: Look,
+}
+
+pub const SKINS: [Skin; 5] = [
+    Skin { name: "classic", score: 0, price: 0, look: Look::Theme },
+    Skin { name: "gold", score: 25, price: 30, look: Look::Colour(0xFFE0B040) },
+    Skin { name: "candy", score: 50, price: 60, look: Look::Stripes(0xFFE04050, 0xFFF0F0F0) },
+    Skin { name: "neon", score: 100, price: 120, look: Look::Gradient(0xFF30E0F0, 0xFFE040E0) },
+    Skin { name: "lava", score: 200, price: 240, look: Look::Gradient(0xFFFF5010, 0xFFFFD020) },
+];
+
+impl Skin {
+    // The sprite for a paddle of this size, None for the theme's look
+    pub fn texture(&self, width: usize, height: usize) -> Option<Texture> {
+        let pixel = |x: usize, y: usize| match self.look {
+            Look::Theme => None,
+            Look::Colour(colour) => Some(colour),
+            Look::Stripes(a, b) => Some(if ((x + y) / STRIPE_WIDTH).is_multiple_of(2) { a } else { b }),
+            Look::Gradient(from, to) => Some(palette::mix(from, to, x as f32 / width.saturating_sub(1).max(1) as f32)),
+        };
+        let pixels = (0..height).flat_map(|y| (0..width).map(move |x| pixel(x, y))).collect::<Option<Vec<u32>>>()?;
+        (!pixels.is_empty()).then_some(Texture { width, height, pixels })
+    }
+}
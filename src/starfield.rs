@@ -0,0 +1,76 @@
+// a parallax starfield behind the playfield: layers of stars drifting down the screen, the nearer ones bigger,
+// brighter and faster. the stars are scattered once and where they are is worked out from the time,
+// so there's nothing to update and, like particles, they never touch the game's own random numbers
+
+use crate::palette;
+use crate::renderer::{Rect, Renderer};
+use crate::rng::Rng;
+
+const SEED: u64 = 0x53544152533A2A2E;
+const FIELD_AREA: f32 = 800.0 * 600.0; // The star counts below are for a field this size
+
+struct Layer {
+    stars: usize,
+    speed: f32,      // pixels/second
+    size: f32,       // pixels
+    brightness: f32, // 0 is the background colour, 1 the star colour
+}
+
+// Far to near
+const LAYERS: [Layer; 3] = [
+    Layer { stars: 60, speed: 6.0, size: 1.0, brightness: 0.3 },
+    Layer { stars: 30, speed: 16.0, size: 1.0, brightness: 0.55 },
+    Layer { stars: 12, speed: 40.0, size: 2.0, brightness: 0.85 },
+];
+
+struct Star {
+    x: f32,
+    y: f32, // At time 0
+    layer: usize,
+}
+
+pub struct Starfield {
+    stars: Vec<Star>,
+    width: f32,
+    height: f32,
+}
+
+impl Starfield {
+    pub fn new(field_width: usize, field_height: usize) -> Self {
+        let (width, height) = (field_width as f32, field_height as f32);
+        let mut rng = Rng::new(SEED);
+        let scale = width * height / FIELD_AREA;
+        let mut stars = Vec::new();
+        for (layer, settings) in LAYERS.iter().enumerate() {
+            for _ in 0..(settings.stars as f32 * scale).round() as usize {
+                stars.push(Star {
+                    x: rng.range(0.0, width),
+                    y: rng.range(0.0, height),
+                    layer,
+                });
+            }
+        }
+        Starfield { stars, width, height }
+    }
+
+    // Each star's top left corner at time seconds, on whole pixels so a star looks the same wherever it is
+    fn positions(&self, time: f32) -> impl Iterator<Item = (f32, f32, &Layer)> + '_ {
+        self.stars.iter().map(move |star| {
+            let layer = &LAYERS[star.layer];
+            let y = (star.y + layer.speed * time).rem_euclid(self.height).floor();
+            (star.x.floor(), y, layer)
+        })
+    }
+
+    pub fn draw(&self, renderer: &mut dyn Renderer, time: f32, background: u32, colour: u32) {
+        for (x, y, layer) in self.positions(time) {
+            renderer.fill_rect(x, y, layer.size, layer.size, palette::mix(background, colour, layer.brightness));
+        }
+    }
+
+    // Where the stars are at time seconds, for the dirty rectangles
+    pub fn rects(&self, time: f32) -> impl Iterator<Item = Rect> + '_ {
+        let field = (self.width as usize, self.height as usize);
+        self.positions(time).map(move |(x, y, layer)| Rect::around(x, y, layer.size, layer.size, 0.0, field))
+    }
+}
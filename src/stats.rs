@@ -0,0 +1,109 @@
+// running totals over every game played, saved in the user's data directory when the game closes
+// and shown from the title screen
+
+use crate::config;
+use crate::highscores;
+use crate::locale::Locale;
+use std::path::PathBuf;
+
+const FILE_NAME: &str = "stats.toml";
+
+#[derive(Default)]
+pub struct Stats {
+    pub play_time: f64, // Seconds
+    pub balls_lost: u64,
+    pub paddle_hits: u64,
+    pub longest_rally: u32, // Paddle hits between one serve and the next
+    pub top_speed: f32,     // Fastest any ball has gone, pixels/second
+    changed: bool,          // Anything to save since loading
+    path: Option<PathBuf>,
+}
+
+impl Stats {
+    // Load the totals, an unreadable or missing file starts them all from zero
+    pub fn load() -> Self {
+        let path = highscores::data_dir().map(|dir| dir.join(FILE_NAME));
+        let mut stats = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|text| parse_stats(&text))
+            .unwrap_or_default();
+        stats.path = path;
+        stats
+    }
+
+    pub fn add_play_time(&mut self, seconds: f32) {
+        self.play_time += seconds as f64;
+        self.changed = true;
+    }
+
+    pub fn add_balls_lost(&mut self, count: usize) {
+        self.balls_lost += count as u64;
+        self.changed = true;
+    }
+
+    // A paddle hit that made the current rally this long
+    pub fn add_paddle_hit(&mut self, rally: u32) {
+        self.paddle_hits += 1;
+        self.longest_rally = self.longest_rally.max(rally);
+        self.changed = true;
+    }
+
+    pub fn record_speed(&mut self, speed: f32) {
+        if speed > self.top_speed {
+            self.top_speed = speed;
+            self.changed = true;
+        }
+    }
+
+    // Each total as a line of the stats screen
+    pub fn lines(&self, locale: &Locale) -> Vec<String> {
+        let minutes = (self.play_time / 60.0) as u64;
+        vec![
+            locale.format("stats.time_played", &[&(minutes / 60), &format!("{:02}", minutes % 60)]),
+            locale.format("stats.balls_lost", &[&self.balls_lost]),
+            locale.format("stats.paddle_hits", &[&self.paddle_hits]),
+            locale.format("stats.longest_rally", &[&self.longest_rally]),
+            locale.format("stats.top_speed", &[&self.top_speed.round()]),
+        ]
+    }
+
+    // Does nothing when there is nothing new, so games that were only watched leave the file alone
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = match &self.path {
+            Some(path) if self.changed => path,
+            _ => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let text = format!(
+            "[stats]\nplay_time = {:.1}\nballs_lost = {}\npaddle_hits = {}\nlongest_rally = {}\ntop_speed = {:.1}\n",
+            self.play_time, self.balls_lost, self.paddle_hits, self.longest_rally, self.top_speed
+        );
+        std::fs::write(path, text)
+    }
+}
+
+fn parse_stats(text: &str) -> Stats {
+    let values = match config::parse(text) {
+        Ok(values) => values,
+        Err(e) => {
+            eprintln!("Could not read stats: {}", e);
+            return Stats::default();
+        }
+    };
+
+    let get = |key: &str| values.get(&format!("stats.{}", key));
+    let count = |key: &str| get(key).and_then(|value| value.as_int()).unwrap_or(0).max(0) as u64;
+    let amount = |key: &str| get(key).and_then(|value| value.as_f64()).unwrap_or(0.0).max(0.0);
+    Stats {
+        play_time: amount("play_time"),
+        balls_lost: count("balls_lost"),
+        paddle_hits: count("paddle_hits"),
+        longest_rally: count("longest_rally") as u32,
+        top_speed: amount("top_speed") as f32,
+        ..Stats::default()
+    }
+}
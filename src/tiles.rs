@@ -0,0 +1,75 @@
+// tile backgrounds for levels: a small atlas image cut into square tiles, left to right then top to bottom,
+// and a map of which tile goes where. the map repeats across and down until it covers the field, so a few
+// rows of tiles are enough to theme a level. tiles are drawn over the background colour and under everything else
+
+use crate::assets::{self, Texture};
+use crate::renderer::Renderer;
+use std::path::Path;
+
+pub struct Tilemap {
+    tiles: Vec<Texture>,
+    size: usize,                  // Width and height of a tile, pixels
+    map: Vec<Vec<Option<usize>>>, // Rows of tile numbers, None shows the background colour
+}
+
+impl Tilemap {
+    // Cut the atlas into tiles of size x size pixels for the map, an empty map covers the field in the first tile
+    pub fn load(atlas: &Path, size: usize, mut map: Vec<Vec<Option<usize>>>) -> Result<Self, String> {
+        let atlas = assets::load_png(atlas)?;
+        let tiles = cut(&atlas, size);
+        if tiles.is_empty() {
+            return Err(format!("tiles of {} pixels don't fit in a {}x{} atlas", size, atlas.width, atlas.height));
+        }
+        if map.is_empty() {
+            map.push(vec![Some(0)]);
+        }
+        if let Some(tile) = map.iter().flatten().flatten().find(|&&tile| tile >= tiles.len()) {
+            return Err(format!("no tile {}, the atlas has {}", tile, tiles.len()));
+        }
+        Ok(Tilemap { tiles, size, map })
+    }
+
+    // A row of the map, each character a tile number from 0-9 then a-z, or '.' for none. None if any isn't
+    pub fn parse_row(line: &str) -> Option<Vec<Option<usize>>> {
+        line.chars()
+            .map(|c| match c {
+                '.' => Some(None),
+                _ => c.to_digit(36).map(|tile| Some(tile as usize)),
+            })
+            .collect()
+    }
+
+    pub fn draw(&self, renderer: &mut dyn Renderer) {
+        let (width, height) = renderer.size();
+        let size = self.size as f32;
+        for row in 0..height.div_ceil(self.size) {
+            let tiles = &self.map[row % self.map.len()];
+            for column in 0..width.div_ceil(self.size) {
+                if let Some(tile) = tiles[column % tiles.len()] {
+                    renderer.draw_texture(column as f32 * size, row as f32 * size, size, size, &self.tiles[tile]);
+                }
+            }
+        }
+    }
+}
+
+// The whole tiles in the atlas, in reading order
+fn cut(atlas: &Texture, size: usize) -> Vec<Texture> {
+    if size == 0 {
+        return Vec::new();
+    }
+    let mut tiles = Vec::new();
+    for top in (0..atlas.height / size).map(|row| row * size) {
+        for left in (0..atlas.width / size).map(|column| column * size) {
+            let pixels = (top..top + size)
+                .flat_map(|y| atlas.pixels[y * atlas.width + left..y * atlas.width + left + size].iter().copied())
+                .collect();
+            tiles.push(Texture {
+                width: size,
+                height: size,
+                pixels,
+            });
+        }
+    }
+    tiles
+}
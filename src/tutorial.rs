@@ -0,0 +1,67 @@
+// the tutorial: a 1 player game with a prompt on screen that teaches one thing at a time,
+// moving, serving and then angled returns. each step waits for the player to do what it asks
+
+const ANGLE_GOAL: f32 = 30.0 * std::f32::consts::PI / 180.0; // Returns at least this far off straight up count as angled
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Step {
+    Move,
+    Serve,
+    Angle,
+    Done, // Carries on as a practice game until the player leaves
+}
+
+// What the player did, reported by the game as it happens
+#[derive(Clone, Copy, Debug)]
+pub enum Action {
+    Moved,
+    Served,
+    Returned { angle: f32 }, // Radians off straight up the ball left the paddle at
+}
+
+pub struct Tutorial {
+    step: Step,
+}
+
+impl Default for Tutorial {
+    fn default() -> Self {
+        Tutorial::new()
+    }
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Tutorial { step: Step::Move }
+    }
+
+    pub fn step(&self) -> Step {
+        self.step
+    }
+
+    // Serving is only taught once the player can move
+    pub fn can_serve(&self) -> bool {
+        self.step != Step::Move
+    }
+
+    // Key of the prompt for the current step in the language files
+    pub fn prompt_key(&self) -> &'static str {
+        match self.step {
+            Step::Move => "tutorial.move",
+            Step::Serve => "tutorial.serve",
+            Step::Angle => "tutorial.angle",
+            Step::Done => "tutorial.done",
+        }
+    }
+
+    // Move on if the action is what the current step is waiting for, true if it was
+    pub fn perform(&mut self, action: Action) -> bool {
+        let next = match (self.step, action) {
+            (Step::Move, Action::Moved) => Step::Serve,
+            (Step::Serve, Action::Served) => Step::Angle,
+            (Step::Angle, Action::Returned { angle }) if angle >= ANGLE_GOAL => Step::Done,
+            _ => return false,
+        };
+        self.step = next;
+        true
+    }
+}
@@ -0,0 +1,191 @@
+// fits the frame into the window when it has been resized
+// the game always runs at the playfield size from the config and draws at that size or the lower render
+// resolution set there, the finished frame is scaled up or down to the window keeping its shape, with bars
+// filling the rest. mouse positions go back through the same fit, so they land where the pointer is over the
+// picture whatever the window's shape. on a high-DPI screen the window opens a whole number of times bigger
+// and the frame is scaled to it by whole pixels, so the game isn't a postage stamp on a 4K or retina screen
+
+use crate::config::Config;
+
+// How the frame is scaled to the window
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Scaling {
+    #[default]
+    Nearest, // Sharp square pixels
+    Bilinear, // Each pixel blended from the four nearest in the frame, softer
+}
+
+pub const SCALINGS: [(&str, Scaling); 2] = [("nearest", Scaling::Nearest), ("bilinear", Scaling::Bilinear)];
+
+pub fn find_scaling(name: &str) -> Option<Scaling> {
+    SCALINGS.iter().find(|(scaling, _)| scaling.eq_ignore_ascii_case(name)).map(|&(_, scaling)| scaling)
+}
+
+// What fills the window around the frame when the two are different shapes
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Bars {
+    #[default]
+    Black,
+    Theme, // The theme's background, so the playfield seems to carry on to the window's edges
+}
+
+pub const BARS: [(&str, Bars); 2] = [("black", Bars::Black), ("theme", Bars::Theme)];
+
+pub fn find_bars(name: &str) -> Option<Bars> {
+    BARS.iter().find(|(bars, _)| bars.eq_ignore_ascii_case(name)).map(|&(_, bars)| bars)
+}
+
+pub const MAX_PIXEL_SCALE: usize = 4;
+
+// The desktop's scale factor as a whole number, from the variables GTK and Qt desktops set for it. Fractional
+// factors round to the nearest whole one, 1.5 up to 2, since whole pixels stay sharp
+pub fn detect_scale(var: impl Fn(&str) -> Option<String>) -> usize {
+    ["GDK_SCALE", "QT_SCALE_FACTOR"]
+        .iter()
+        .filter_map(|name| var(name)?.trim().parse::<f32>().ok())
+        .find(|factor| factor.is_finite() && *factor > 0.0)
+        .map_or(1, |factor| (factor.round() as usize).clamp(1, MAX_PIXEL_SCALE))
+}
+
+// What a window backend is asked for: the frame the game draws into, the window it opens at and how the one
+// is scaled to the other
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Screen {
+    pub frame_width: usize,
+    pub frame_height: usize,
+    pub window_width: usize, // In screen pixels, the playfield size times pixel_scale
+    pub window_height: usize,
+    pub scaling: Scaling,
+    pub pixel_scale: usize, // Above 1 on high-DPI screens, where the frame is only ever scaled by whole pixels
+}
+
+impl Screen {
+    pub fn new(config: &Config) -> Self {
+        let (frame_width, frame_height) = config.render_size();
+        let pixel_scale = config.pixel_scale.unwrap_or_else(|| detect_scale(|name| std::env::var(name).ok()));
+        Screen {
+            frame_width,
+            frame_height,
+            window_width: config.window_width * pixel_scale,
+            window_height: config.window_height * pixel_scale,
+            scaling: config.scaling,
+            pixel_scale,
+        }
+    }
+
+    // How the frame fits a window of this size
+    pub fn fit(&self, window_width: usize, window_height: usize) -> Viewport {
+        if self.pixel_scale > 1 {
+            Viewport::fit_whole(self.frame_width, self.frame_height, window_width, window_height)
+        } else {
+            Viewport::fit(self.frame_width, self.frame_height, window_width, window_height)
+        }
+    }
+}
+
+pub struct Viewport {
+    pub window_width: usize,
+    pub window_height: usize,
+    scale: f32,
+    offset_x: usize,
+    offset_y: usize,
+}
+
+impl Viewport {
+    // Largest scale that fits the frame in the window, centred
+    pub fn fit(field_width: usize, field_height: usize, window_width: usize, window_height: usize) -> Self {
+        let scale = (window_width as f32 / field_width as f32).min(window_height as f32 / field_height as f32);
+        let scaled_width = (field_width as f32 * scale) as usize;
+        let scaled_height = (field_height as f32 * scale) as usize;
+        Viewport {
+            window_width,
+            window_height,
+            scale,
+            offset_x: window_width.saturating_sub(scaled_width) / 2,
+            offset_y: window_height.saturating_sub(scaled_height) / 2,
+        }
+    }
+
+    // Like fit but only a whole number of window pixels to each frame pixel, so every pixel is the same size.
+    // A window smaller than the frame can't have that and falls back to fit
+    pub fn fit_whole(field_width: usize, field_height: usize, window_width: usize, window_height: usize) -> Self {
+        let whole = (window_width / field_width).min(window_height / field_height);
+        if whole == 0 {
+            return Viewport::fit(field_width, field_height, window_width, window_height);
+        }
+        Viewport {
+            window_width,
+            window_height,
+            scale: whole as f32,
+            offset_x: (window_width - field_width * whole) / 2,
+            offset_y: (window_height - field_height * whole) / 2,
+        }
+    }
+
+    // True when the window is exactly the frame size and the frame can be shown as it is
+    pub fn is_identity(&self, field_width: usize, field_height: usize) -> bool {
+        self.window_width == field_width && self.window_height == field_height
+    }
+
+    // Scale the frame into the window sized buffer, the final pass before it's shown
+    pub fn blit(
+        &self,
+        frame: &[u32],
+        field_width: usize,
+        field_height: usize,
+        window: &mut [u32],
+        bars: u32,
+        scaling: Scaling,
+    ) {
+        for y in 0..self.window_height {
+            let row = &mut window[y * self.window_width..(y + 1) * self.window_width];
+            let field_y = ((y as f32 - self.offset_y as f32) / self.scale).floor();
+            if field_y < 0.0 || field_y as usize >= field_height {
+                row.fill(bars);
+                continue;
+            }
+            let src = &frame[field_y as usize * field_width..(field_y as usize + 1) * field_width];
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let field_x = ((x as f32 - self.offset_x as f32) / self.scale).floor();
+                *pixel = if field_x < 0.0 || field_x as usize >= field_width {
+                    bars
+                } else if scaling == Scaling::Bilinear {
+                    // Sampled between pixel centres, the frame's edge pixels carry on past the edge
+                    let (sample_x, sample_y) = self.to_field(x as f32 + 0.5, y as f32 + 0.5);
+                    bilinear(frame, field_width, field_height, sample_x - 0.5, sample_y - 0.5)
+                } else {
+                    src[field_x as usize]
+                };
+            }
+        }
+    }
+
+    // Convert a position in the window, such as the mouse, to playfield coordinates. Over the bars it's off the
+    // edge of the playfield
+    pub fn to_field(&self, x: f32, y: f32) -> (f32, f32) {
+        ((x - self.offset_x as f32) / self.scale, (y - self.offset_y as f32) / self.scale)
+    }
+
+    // Where a playfield position is shown in the window, the other way round from to_field
+    pub fn to_window(&self, x: f32, y: f32) -> (f32, f32) {
+        (x * self.scale + self.offset_x as f32, y * self.scale + self.offset_y as f32)
+    }
+}
+
+// The frame's colour at a fractional position, blended from the four pixels around it
+fn bilinear(frame: &[u32], width: usize, height: usize, x: f32, y: f32) -> u32 {
+    let (x, y) = (x.clamp(0.0, (width - 1) as f32), y.clamp(0.0, (height - 1) as f32));
+    let (left, top) = (x as usize, y as usize);
+    let (right, bottom) = ((left + 1).min(width - 1), (top + 1).min(height - 1));
+    let (across, down) = (x - left as f32, y - top as f32);
+    let pixel = |column: usize, row: usize| frame[row * width + column];
+    let (top_left, top_right) = (pixel(left, top), pixel(right, top));
+    let (bottom_left, bottom_right) = (pixel(left, bottom), pixel(right, bottom));
+    let channel = |shift: u32| {
+        let value = |colour: u32| ((colour >> shift) & 0xFF) as f32;
+        let upper = value(top_left) + (value(top_right) - value(top_left)) * across;
+        let lower = value(bottom_left) + (value(bottom_right) - value(bottom_left)) * across;
+        ((upper + (lower - upper) * down).round() as u32) << shift
+    };
+    0xFF000000 | channel(16) | channel(8) | channel(0)
+}
@@ -0,0 +1,143 @@
+// the browser frontend, what main.rs is to the desktop. built for wasm32-unknown-unknown with the web feature,
+// web/index.html loads it, calls web_start() once and then web_frame() from requestAnimationFrame,
+// copying the frame at web_pixels() onto its canvas and passing key and mouse events in as they happen
+
+use crate::config::Config;
+use crate::input::Key;
+use crate::renderer::{CanvasDisplay, KeyEvent, Page};
+use crate::Game;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+thread_local! {
+    static GAME: RefCell<Option<Game>> = const { RefCell::new(None) };
+    static PAGE: Rc<RefCell<Page>> = Rc::new(RefCell::new(Page::default()));
+}
+
+// The page picks the seed, there's no clock to take one from until it's running
+#[no_mangle]
+pub extern "C" fn web_start(seed: f64) {
+    let config = Config::default();
+    let display = CanvasDisplay::new(config.window_width, config.window_height, PAGE.with(Rc::clone));
+    let game = Game::with_display(config, Some(Box::new(display)), seed as u64);
+    GAME.with(|slot| *slot.borrow_mut() = Some(game));
+}
+
+// One frame of the game, false once the player has quit
+#[no_mangle]
+pub extern "C" fn web_frame() -> bool {
+    GAME.with(|slot| match slot.borrow_mut().as_mut() {
+        Some(game) if game.is_running() => {
+            game.step();
+            game.present().is_ok()
+        }
+        _ => false,
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn web_width() -> u32 {
+    Config::default().window_width as u32
+}
+
+#[no_mangle]
+pub extern "C" fn web_height() -> u32 {
+    Config::default().window_height as u32
+}
+
+// The last frame as RGBA bytes, web_width() * web_height() * 4 of them
+#[no_mangle]
+pub extern "C" fn web_pixels() -> *const u8 {
+    PAGE.with(|page| page.borrow().rgba.as_ptr())
+}
+
+// A keydown or keyup event, by its keyCode and location (for telling left and right shift apart)
+#[no_mangle]
+pub extern "C" fn web_key(code: u32, location: u32, down: bool, repeat: bool) -> bool {
+    let Some(key) = key_from_code(code, location) else {
+        return false;
+    };
+    PAGE.with(|page| page.borrow_mut().key_events.push(KeyEvent { key, down, repeat }));
+    true // The game uses the key, so the page shouldn't scroll with it
+}
+
+// The pointer in playfield pixels
+#[no_mangle]
+pub extern "C" fn web_mouse(x: f32, y: f32) {
+    PAGE.with(|page| page.borrow_mut().mouse = Some((x, y)));
+}
+
+// KeyboardEvent.keyCode is old but every browser still sends it, and it's a plain number
+fn key_from_code(code: u32, location: u32) -> Option<Key> {
+    const LETTERS: [Key; 26] = [
+        Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+        Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    ];
+    const DIGITS: [Key; 10] = [
+        Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9,
+    ];
+    const NUMPAD_DIGITS: [Key; 10] = [
+        Key::NumPad0, Key::NumPad1, Key::NumPad2, Key::NumPad3, Key::NumPad4, Key::NumPad5, Key::NumPad6,
+        Key::NumPad7, Key::NumPad8, Key::NumPad9,
+    ];
+    const FUNCTION_KEYS: [Key; 15] = [
+        Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8, Key::F9, Key::F10, Key::F11, Key::F12,
+        Key::F13, Key::F14, Key::F15,
+    ];
+    const RIGHT: u32 = 2; // KeyboardEvent.DOM_KEY_LOCATION_RIGHT
+    const NUMPAD: u32 = 3; // KeyboardEvent.DOM_KEY_LOCATION_NUMPAD
+
+    let key = match code {
+        8 => Key::Backspace,
+        9 => Key::Tab,
+        13 if location == NUMPAD => Key::NumPadEnter,
+        13 => Key::Enter,
+        16 if location == RIGHT => Key::RightShift,
+        16 => Key::LeftShift,
+        17 if location == RIGHT => Key::RightCtrl,
+        17 => Key::LeftCtrl,
+        18 if location == RIGHT => Key::RightAlt,
+        18 => Key::LeftAlt,
+        19 => Key::Pause,
+        20 => Key::CapsLock,
+        27 => Key::Escape,
+        32 => Key::Space,
+        33 => Key::PageUp,
+        34 => Key::PageDown,
+        35 => Key::End,
+        36 => Key::Home,
+        37 => Key::Left,
+        38 => Key::Up,
+        39 => Key::Right,
+        40 => Key::Down,
+        45 => Key::Insert,
+        46 => Key::Delete,
+        48..=57 => DIGITS[(code - 48) as usize],
+        65..=90 => LETTERS[(code - 65) as usize],
+        91 => Key::LeftSuper,
+        92 => Key::RightSuper,
+        93 => Key::Menu,
+        96..=105 => NUMPAD_DIGITS[(code - 96) as usize],
+        106 => Key::NumPadAsterisk,
+        107 => Key::NumPadPlus,
+        109 => Key::NumPadMinus,
+        110 => Key::NumPadDot,
+        111 => Key::NumPadSlash,
+        112..=126 => FUNCTION_KEYS[(code - 112) as usize],
+        144 => Key::NumLock,
+        145 => Key::ScrollLock,
+        59 | 186 => Key::Semicolon,
+        61 | 187 => Key::Equal,
+        188 => Key::Comma,
+        173 | 189 => Key::Minus,
+        190 => Key::Period,
+        191 => Key::Slash,
+        192 => Key::Backquote,
+        219 => Key::LeftBracket,
+        220 => Key::Backslash,
+        221 => Key::RightBracket,
+        222 => Key::Apostrophe,
+        _ => return None,
+    };
+    Some(key)
+}
@@ -0,0 +1,24 @@
+// passes over the whole frame split into bands, which have to cover every row once whether or not the
+// parallel feature puts them on threads of their own
+
+use game_loop::bands;
+use std::sync::Mutex;
+
+#[test]
+fn the_bands_cover_every_row_once() {
+    for (width, height) in [(800, 600), (7, 1), (3, 97), (640, 33)] {
+        let mut frame = vec![0u32; width * height];
+        let firsts = Mutex::new(Vec::new());
+        bands::for_each_band(&mut frame, width, |first_row, band| {
+            assert_eq!(band.len() % width, 0, "a band of part rows");
+            for (i, pixel) in band.iter_mut().enumerate() {
+                *pixel += (first_row + i / width) as u32 + 1;
+            }
+            firsts.lock().unwrap().push(first_row);
+        });
+        for (i, &pixel) in frame.iter().enumerate() {
+            assert_eq!(pixel, (i / width) as u32 + 1, "row {} at {width}x{height}", i / width);
+        }
+        assert!(firsts.into_inner().unwrap().contains(&0));
+    }
+}
@@ -0,0 +1,77 @@
+// the collision layers: which pairs of things run into each other, and the queries that go by them
+
+use game_loop::balls::BallKind;
+use game_loop::collision::{self, Filter, Layers, SpatialHash};
+use game_loop::ecs::{self, Collider, World};
+use game_loop::rng::Rng;
+use game_loop::GameObject;
+
+fn block(x: f32, y: f32) -> GameObject {
+    GameObject { x, y, width: 20.0, height: 20.0, vel_x: 0.0, vel_y: 0.0 }
+}
+
+#[test]
+fn the_pairs_collide_both_ways_round() {
+    let pairs = [
+        (Layers::BALL, Layers::PADDLE, true),
+        (Layers::BALL, Layers::BRICK, true),
+        (Layers::BALL, Layers::OBSTACLE, true),
+        (Layers::BALL, Layers::SHIELD, true),
+        (Layers::PADDLE, Layers::PICKUP, true),
+        (Layers::PROJECTILE, Layers::BRICK, true),
+        (Layers::PROJECTILE, Layers::OBSTACLE, true),
+        (Layers::BALL, Layers::PICKUP, false),
+        (Layers::BALL, Layers::PROJECTILE, false),
+        (Layers::PROJECTILE, Layers::PADDLE, false),
+        (Layers::PICKUP, Layers::OBSTACLE, false),
+    ];
+    for (a, b, collide) in pairs {
+        assert_eq!(Filter::on(a).collides_with(Filter::on(b)), collide, "{a:?} and {b:?}");
+        assert_eq!(Filter::on(b).collides_with(Filter::on(a)), collide, "{b:?} and {a:?}");
+    }
+}
+
+#[test]
+fn a_ghost_ball_goes_through_obstacles_only() {
+    let ghost = BallKind::Ghost.filter();
+    assert!(!ghost.collides_with(Collider::Solid.filter()));
+    for layer in [Layers::PADDLE, Layers::BRICK, Layers::SHIELD] {
+        assert!(ghost.collides_with(Filter::on(layer)), "{layer:?}");
+    }
+    assert!(BallKind::Heavy.filter().collides_with(Collider::Solid.filter()));
+}
+
+#[test]
+fn touching_only_finds_what_the_filter_collides_with() {
+    let mut world = World::new();
+    let obstacle = ecs::spawn_block(&mut world, &block(100.0, 100.0), 0);
+    let coin = game_loop::powerups::spawn_coin(&mut world, 105.0, 105.0);
+    let body = block(110.0, 110.0);
+
+    let paddle: Vec<_> = ecs::touching(&world, &body, Filter::on(Layers::PADDLE)).into_iter().map(|(e, _)| e).collect();
+    assert_eq!(paddle, vec![coin]);
+    let bolt: Vec<_> = ecs::touching(&world, &body, Collider::Bolt.filter()).into_iter().map(|(e, _)| e).collect();
+    assert_eq!(bolt, vec![obstacle]);
+}
+
+#[test]
+fn the_grid_finds_everything_touching_whatever_the_size() {
+    let mut rng = Rng::new(7);
+    let rectangle = |rng: &mut Rng| {
+        let (width, height) = (rng.range(1.0, 150.0), rng.range(1.0, 150.0));
+        GameObject { x: rng.range(-50.0, 800.0), y: rng.range(-50.0, 600.0), width, height, vel_x: 0.0, vel_y: 0.0 }
+    };
+    let bodies: Vec<GameObject> = (0..300).map(|_| rectangle(&mut rng)).collect();
+    let mut grid = SpatialHash::new(64.0);
+    grid.rebuild(bodies.iter().enumerate());
+    for _ in 0..500 {
+        let probe = rectangle(&mut rng);
+        let found = grid.query(&probe);
+        assert!(found.windows(2).all(|pair| pair[0] < pair[1]), "not in order or not each once");
+        for (index, body) in bodies.iter().enumerate() {
+            if collision::touches(&probe, body) {
+                assert!(found.contains(&index), "missed {index}");
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+// drawing only the changed parts over the last frame has to end up with the same picture as drawing all of it.
+// the display here keeps its frame between presents like a real window, so anything the dirty rectangles
+// miss is left showing what was there before
+
+use game_loop::assets::Texture;
+use game_loop::clock::ManualClock;
+use game_loop::config::Config;
+use game_loop::ecs::Spawn;
+use game_loop::input::{InputSource, Key, KeyRepeat, ScriptedInput};
+use game_loop::renderer::{Display, PixelBuffer, Renderer};
+use game_loop::{Game, GameMode, GameObject, GameState};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+const SEED: u64 = 1;
+const SERVE_COUNTDOWN: Duration = Duration::from_secs(3);
+const FRAME: Duration = Duration::from_micros(16_667);
+const BLOCK_COLOUR: u32 = 0xFFFF00FF;
+
+// A frame the test can look at while the game owns the display
+struct KeptFrame {
+    pixels: Rc<RefCell<Vec<u32>>>,
+    width: usize,
+}
+
+impl KeptFrame {
+    fn draw(&mut self, draw: impl FnOnce(&mut PixelBuffer)) {
+        draw(&mut PixelBuffer::new(&mut self.pixels.borrow_mut(), self.width));
+    }
+}
+
+impl Renderer for KeptFrame {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.pixels.borrow().len() / self.width)
+    }
+
+    fn clear(&mut self, colour: u32) {
+        self.draw(|buffer| buffer.clear(colour));
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.draw(|buffer| buffer.fill_rect(x, y, width, height, colour));
+    }
+
+    fn fill_rect_smooth(&mut self, x: f32, y: f32, width: f32, height: f32, colour: u32) {
+        self.draw(|buffer| buffer.fill_rect_smooth(x, y, width, height, colour));
+    }
+
+    fn fill_circle(&mut self, x: f32, y: f32, size: f32, colour: u32) {
+        self.draw(|buffer| buffer.fill_circle(x, y, size, colour));
+    }
+
+    fn draw_texture(&mut self, x: f32, y: f32, width: f32, height: f32, texture: &Texture) {
+        self.draw(|buffer| buffer.draw_texture(x, y, width, height, texture));
+    }
+
+    fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: usize, colour: u32) {
+        self.draw(|buffer| buffer.draw_text(x, y, text, scale, colour));
+    }
+
+    fn dim(&mut self) {
+        self.draw(|buffer| buffer.dim());
+    }
+
+    fn present(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// The keys come from a ScriptedInput set on the game instead
+impl InputSource for KeptFrame {
+    fn is_open(&self) -> bool {
+        true
+    }
+
+    fn is_key_down(&self, _key: Key) -> bool {
+        false
+    }
+
+    fn is_key_pressed(&self, _key: Key, _repeat: KeyRepeat) -> bool {
+        false
+    }
+
+    fn keys_pressed(&self) -> Vec<Key> {
+        Vec::new()
+    }
+
+    fn keys_repeated(&self) -> Vec<Key> {
+        Vec::new()
+    }
+
+    fn keys_down(&self) -> Vec<Key> {
+        Vec::new()
+    }
+}
+
+impl Display for KeptFrame {}
+
+// A 1 player game on a KeptFrame with the ball served, the frame, and the clock its time goes by
+fn game() -> (Game, Rc<RefCell<Vec<u32>>>, ManualClock) {
+    game_with(Config { starfield: false, ..Config::default() })
+}
+
+fn game_with(config: Config) -> (Game, Rc<RefCell<Vec<u32>>>, ManualClock) {
+    std::env::set_var("XDG_DATA_HOME", env!("CARGO_TARGET_TMPDIR"));
+    let pixels = Rc::new(RefCell::new(vec![0; config.window_width * config.window_height]));
+    let display = KeptFrame { pixels: pixels.clone(), width: config.window_width };
+    let mut game = Game::with_display(config, Some(Box::new(display)), SEED);
+    game.set_input(Box::new(ScriptedInput::new()));
+    let clock = ManualClock::new();
+    game.set_clock(Box::new(clock.clone()));
+    game.start_game(GameMode::Solo);
+    assert_eq!(game.state(), GameState::Playing);
+    clock.advance(SERVE_COUNTDOWN);
+    for _ in 0..3 {
+        clock.advance(FRAME);
+        game.step();
+        game.present().unwrap();
+    }
+    (game, pixels, clock)
+}
+
+// Another frame on the display, checked against the whole frame drawn from scratch
+fn assert_shown_as_drawn(game: &mut Game, pixels: &Rc<RefCell<Vec<u32>>>, clock: &ManualClock, after: &str) {
+    clock.advance(FRAME);
+    game.step();
+    game.present().unwrap();
+    let mut drawn = vec![0; pixels.borrow().len()];
+    game.render_into(&mut drawn);
+    let wrong = pixels.borrow().iter().zip(&drawn).filter(|(shown, drawn)| shown != drawn).count();
+    assert_eq!(wrong, 0, "{wrong} pixels shown differently from a full frame after {after}");
+}
+
+fn block() -> Spawn {
+    let body = GameObject { x: 300.0, y: 250.0, width: 60.0, height: 20.0, vel_x: 0.0, vel_y: 0.0 };
+    Spawn::Block { body, colour: BLOCK_COLOUR, path: None }
+}
+
+#[test]
+fn a_block_spawned_and_despawned_mid_rally_shows_and_goes() {
+    let (mut game, pixels, clock) = game();
+    let entity = game.spawn(block());
+    assert_shown_as_drawn(&mut game, &pixels, &clock, "a spawn");
+    game.despawn(entity);
+    assert_shown_as_drawn(&mut game, &pixels, &clock, "a despawn");
+}
+
+#[test]
+fn a_laser_bolt_knocking_out_an_obstacle_takes_it_off_the_display() {
+    let (mut game, pixels, clock) = game();
+    game.spawn(block());
+    assert_shown_as_drawn(&mut game, &pixels, &clock, "a spawn");
+    let shown = |pixels: &Rc<RefCell<Vec<u32>>>| pixels.borrow().contains(&BLOCK_COLOUR);
+    assert!(shown(&pixels));
+    game.spawn(Spawn::Bolt { x: 320.0, y: 280.0 });
+    for frame in 0..10 {
+        assert_shown_as_drawn(&mut game, &pixels, &clock, &format!("{frame} frames of a bolt"));
+    }
+    assert!(!shown(&pixels), "the bolt never hit the block");
+}
+
+// Skipped where the Lua library isn't installed
+#[cfg(feature = "lua")]
+#[test]
+fn blocks_a_script_spawns_mid_rally_show() {
+    let path = format!("{}/dirty_rects.lua", env!("CARGO_TARGET_TMPDIR"));
+    let script = "function on_paddle_hit(hits) game.spawn_block(300, 250, 60, 20) end\n\
+                  function on_life_lost(lives) game.spawn_block(100, 150, 60, 20) end\n";
+    std::fs::write(&path, script).unwrap();
+    if let Err(e) = game_loop::scripting::Script::load(&path) {
+        eprintln!("skipped, no script: {e}");
+        return;
+    }
+    let (mut game, pixels, clock) = game_with(Config { starfield: false, script: Some(path), ..Config::default() });
+    let lives = game.scoreboard().lives;
+    for frame in 0..3600 {
+        assert_shown_as_drawn(&mut game, &pixels, &clock, &format!("{frame} frames of a scripted game"));
+        if game.scoreboard().lives < lives {
+            break;
+        }
+    }
+    assert!(game.scoreboard().lives < lives, "the first ball was never lost");
+    for frame in 0..30 {
+        assert_shown_as_drawn(&mut game, &pixels, &clock, &format!("{frame} frames after a life lost"));
+    }
+}
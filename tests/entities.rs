@@ -0,0 +1,72 @@
+// entity handles in the world stay tied to what they were spawned for, even once the slot is reused
+
+use game_loop::ecs::{self, Collider, World};
+use game_loop::GameObject;
+
+fn block(x: f32) -> GameObject {
+    GameObject { x, y: 100.0, width: 20.0, height: 20.0, vel_x: 0.0, vel_y: 0.0 }
+}
+
+#[test]
+fn a_despawned_handle_never_finds_the_next_entity_in_its_slot() {
+    let mut world = World::new();
+    let old = ecs::spawn_block(&mut world, &block(10.0), 0);
+    world.despawn(old);
+    let new = ecs::spawn_block(&mut world, &block(50.0), 0);
+    assert_ne!(old, new);
+    assert!(!world.is_alive(old));
+    assert!(world.is_alive(new));
+    assert!(world.body(old).is_none());
+    assert_eq!(world.body(new).map(|body| body.x), Some(50.0));
+    // Despawning the old handle again leaves the new entity alone
+    world.despawn(old);
+    assert!(world.is_alive(new));
+}
+
+#[test]
+fn despawn_later_waits_for_cleanup() {
+    let mut world = World::new();
+    let entity = ecs::spawn_block(&mut world, &block(10.0), 0);
+    world.despawn_later(entity);
+    assert!(world.is_alive(entity));
+    world.cleanup();
+    assert!(!world.is_alive(entity));
+    assert_eq!(world.colliders.iter().count(), 0);
+}
+
+#[test]
+fn clearing_the_world_leaves_old_handles_dead() {
+    let mut world = World::new();
+    let old = ecs::spawn_block(&mut world, &block(10.0), 0);
+    world.clear();
+    let new = ecs::spawn_block(&mut world, &block(50.0), 0);
+    assert!(!world.is_alive(old));
+    assert_eq!(world.colliders.get(old), None);
+    assert_eq!(world.colliders.get(new), Some(&Collider::Solid));
+}
+
+#[test]
+fn entities_leaving_the_field_are_cleaned_up() {
+    let mut world = World::new();
+    let falling = game_loop::powerups::spawn_coin(&mut world, 100.0, 590.0);
+    let rising = game_loop::lasers::spawn_bolt(&mut world, 100.0, 5.0);
+    let still = ecs::spawn_block(&mut world, &block(10.0), 0);
+    ecs::movement(&mut world, 0.5);
+    ecs::despawn_outside(&mut world, 600.0);
+    assert!(!world.is_alive(falling));
+    assert!(!world.is_alive(rising));
+    assert!(world.is_alive(still));
+}
+
+#[test]
+fn despawned_slots_are_reused_before_the_world_grows() {
+    let mut world = World::new();
+    for _ in 0..10 {
+        let bolts: Vec<_> = (0..4).map(|i| game_loop::lasers::spawn_bolt(&mut world, i as f32 * 10.0, 300.0)).collect();
+        for bolt in bolts {
+            world.despawn(bolt);
+        }
+    }
+    assert_eq!(world.stats().live, 0);
+    assert_eq!(world.stats().slots, 4);
+}
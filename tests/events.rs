@@ -0,0 +1,20 @@
+// the queue of what happened during a physics step, handed out and back without a new buffer each step
+
+use game_loop::events::{EventQueue, GameEvent};
+
+#[test]
+fn a_recycled_queue_keeps_its_buffer_and_anything_queued_meanwhile() {
+    let mut queue = EventQueue::default();
+    for level in 0..10 {
+        queue.push(GameEvent::LevelCleared { level });
+    }
+    let mut events = queue.drain();
+    let buffer = events.as_ptr();
+    assert_eq!(events.drain(..).count(), 10);
+    queue.push(GameEvent::LifeLost { lives: 2 });
+    queue.recycle(events);
+
+    let events = queue.drain();
+    assert_eq!(events, vec![GameEvent::LifeLost { lives: 2 }]);
+    assert_eq!(events.as_ptr(), buffer, "the buffer was allocated again");
+}
@@ -0,0 +1,159 @@
+// gameplay tests: headless games played out with scripted input for some seconds of game time, checking the
+// score, lives and where things end up. the script is a replay made up on the spot, which the game plays
+// back frame by frame exactly as it would a recorded one. nothing is written outside the target directory,
+// the game's files go in a data directory of their own there
+
+use game_loop::config::Config;
+use game_loop::replay::{FrameInput, Replay};
+use game_loop::{Game, GameMode, GameState, HEADLESS_FRAME_TIME};
+
+const SEED: u64 = 1;
+const WIDTH: usize = 800;
+const HEIGHT: usize = 600;
+
+fn config() -> Config {
+    Config {
+        window_width: WIDTH,
+        window_height: HEIGHT,
+        ..Config::default()
+    }
+}
+
+// The input for every frame of a game, the paddle speeds picked frame by frame. The serve is never held up
+fn script(mode: GameMode, seconds: f32, paddles: impl Fn(u32) -> (f32, f32)) -> Replay {
+    let mut replay = Replay::new(SEED, mode);
+    let frames = (seconds / HEADLESS_FRAME_TIME).round() as u32;
+    for frame in 0..frames {
+        let (paddle_vel, top_paddle_vel) = paddles(frame);
+        replay.frames.push(FrameInput {
+            frame_time: HEADLESS_FRAME_TIME,
+            paddle_vel,
+            top_paddle_vel,
+            mouse_x: None,
+            serve: true,
+            fire: false,
+        });
+    }
+    replay
+}
+
+// Play the script through, stopping early at game over
+fn play(config: Config, replay: Replay) -> Game {
+    // Game::new reads the high scores and the rest from the data directory, and the game over screen saves them
+    std::env::set_var("XDG_DATA_HOME", env!("CARGO_TARGET_TMPDIR"));
+    let frames = replay.frames.len();
+    let mut game = Game::new(config, true, SEED).expect("a headless game needs no window");
+    game.start_replay(replay);
+    for _ in 0..frames {
+        if game.state() != GameState::Playing {
+            break;
+        }
+        game.step();
+    }
+    game
+}
+
+#[test]
+fn paddle_held_left_stops_at_the_wall() {
+    let config = config();
+    let speed = config.paddle_speed;
+    let game = play(config, script(GameMode::Solo, 2.0, |_| (-speed, 0.0)));
+    assert_eq!(game.paddle().x, 0.0);
+}
+
+#[test]
+fn paddle_held_right_stops_at_the_wall() {
+    let config = config();
+    let speed = config.paddle_speed;
+    let game = play(config, script(GameMode::Solo, 2.0, |_| (speed, 0.0)));
+    assert_eq!(game.paddle().x, WIDTH as f32 - game.paddle().width);
+}
+
+#[test]
+fn top_paddle_follows_player_2() {
+    let config = config();
+    let speed = config.paddle_speed;
+    let game = play(config, script(GameMode::TwoPlayer, 2.0, |_| (0.0, -speed)));
+    assert_eq!(game.top_paddle().x, 0.0);
+}
+
+#[test]
+fn idle_paddle_loses_every_life() {
+    let game = play(config(), script(GameMode::Solo, 300.0, |_| (0.0, 0.0)));
+    assert_eq!(game.state(), GameState::GameOver);
+    assert_eq!(game.scoreboard().lives, 0);
+}
+
+// A paddle as wide as the field can't miss. The first pickup doesn't fall until 10 seconds in, so the
+// paddle keeps its width for these
+#[test]
+fn wide_paddle_returns_every_ball() {
+    let config = Config { paddle_width: WIDTH as f32, ..config() };
+    let lives = config.lives;
+    let game = play(config, script(GameMode::Solo, 9.0, |_| (0.0, 0.0)));
+    assert_eq!(game.state(), GameState::Playing);
+    assert_eq!(game.scoreboard().lives, lives);
+    assert!(game.scoreboard().score > 0, "no returns scored");
+    let ball = &game.balls()[0];
+    assert!(ball.x >= 0.0 && ball.x + ball.width <= WIDTH as f32, "ball at {} is off the field", ball.x);
+    assert!(ball.y + ball.height <= game.paddle().y + game.paddle().height, "ball at {} got past the paddle", ball.y);
+}
+
+#[test]
+fn survival_scores_the_seconds_survived() {
+    let config = Config { paddle_width: WIDTH as f32, ..config() };
+    let game = play(config, script(GameMode::Survival, 8.5, |_| (0.0, 0.0)));
+    assert_eq!(game.state(), GameState::Playing);
+    assert_eq!(game.scoreboard().score, 8);
+}
+
+#[test]
+fn breakout_scores_for_bricks() {
+    let config = Config { paddle_width: WIDTH as f32, ..config() };
+    let lives = config.lives;
+    let game = play(config, script(GameMode::Breakout, 9.0, |_| (0.0, 0.0)));
+    assert_eq!(game.scoreboard().lives, lives);
+    assert!(game.scoreboard().score > 0, "no bricks broken");
+}
+
+#[test]
+fn vs_cpu_ends_when_a_player_wins() {
+    let game = play(config(), script(GameMode::VsAi, 600.0, |_| (0.0, 0.0)));
+    assert_eq!(game.state(), GameState::GameOver);
+    let scoreboard = game.scoreboard();
+    assert!(scoreboard.p2_score > scoreboard.p1_score, "an idle player beat the CPU {} to {}", scoreboard.p1_score, scoreboard.p2_score);
+}
+
+#[test]
+fn the_same_script_plays_out_the_same() {
+    let config = config();
+    let speed = config.paddle_speed;
+    // Swing the paddle from side to side every second
+    let swing = move |frame: u32| (if (frame / 60).is_multiple_of(2) { speed } else { -speed }, 0.0);
+    let first = play(config.clone(), script(GameMode::Breakout, 20.0, swing));
+    let second = play(config, script(GameMode::Breakout, 20.0, swing));
+    assert_eq!(first.summary(), second.summary());
+    assert_eq!(first.paddle().x, second.paddle().x);
+    let positions = |game: &Game| game.balls().iter().map(|ball| (ball.x, ball.y)).collect::<Vec<_>>();
+    assert_eq!(positions(&first), positions(&second));
+}
+
+#[test]
+fn a_tiny_window_keeps_a_ball_and_paddle_to_play_with() {
+    std::env::set_var("XDG_DATA_HOME", env!("CARGO_TARGET_TMPDIR"));
+    let mut config = Config { window_width: 6, window_height: 6, ..Config::default() };
+    assert!(!config.validate().is_empty());
+    assert_eq!((config.paddle_height, config.ball_size), (1.0, 1.0));
+    let mut game = Game::new(config, true, SEED).expect("a headless game needs no window");
+    for _ in 0..120 {
+        game.step();
+    }
+}
+
+// Finishing at all is the test, the ball's steps are kept to a minimum length rather than split up forever
+#[test]
+fn a_ball_and_paddle_next_to_no_size_still_step() {
+    let config = Config { ball_size: 1e-6, paddle_height: 1e-6, ..config() };
+    let game = play(config, script(GameMode::Solo, 2.0, |_| (0.0, 0.0)));
+    assert!(game.balls().iter().all(|ball| ball.x.is_finite() && ball.y.is_finite()));
+}
@@ -0,0 +1,55 @@
+// high-DPI screens: the scale factor the desktop asks for, the bigger window and whole pixel scaling
+
+use game_loop::config::Config;
+use game_loop::viewport::{self, Screen, Viewport};
+
+fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+    move |name| vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.to_string())
+}
+
+#[test]
+fn the_scale_comes_from_the_desktop_rounded_to_whole_pixels() {
+    assert_eq!(viewport::detect_scale(env(&[])), 1);
+    assert_eq!(viewport::detect_scale(env(&[("GDK_SCALE", "2")])), 2);
+    assert_eq!(viewport::detect_scale(env(&[("QT_SCALE_FACTOR", "1.5")])), 2);
+    assert_eq!(viewport::detect_scale(env(&[("QT_SCALE_FACTOR", "1.25")])), 1);
+    assert_eq!(viewport::detect_scale(env(&[("GDK_SCALE", "junk"), ("QT_SCALE_FACTOR", "3")])), 3);
+    assert_eq!(viewport::detect_scale(env(&[("GDK_SCALE", "16")])), viewport::MAX_PIXEL_SCALE);
+    assert_eq!(viewport::detect_scale(env(&[("GDK_SCALE", "0")])), 1);
+}
+
+#[test]
+fn a_scaled_screen_opens_a_bigger_window_for_the_same_frame() {
+    let config = Config { pixel_scale: Some(2), ..Config::default() };
+    let screen = Screen::new(&config);
+    assert_eq!((screen.frame_width, screen.frame_height), (800, 600));
+    assert_eq!((screen.window_width, screen.window_height), (1600, 1200));
+}
+
+#[test]
+fn whole_pixel_scaling_never_stretches_a_pixel_unevenly() {
+    // 2.5 times the frame would fit, only 2 whole pixels do
+    let viewport = Viewport::fit_whole(800, 600, 2000, 1500);
+    assert_eq!(viewport.to_window(0.0, 0.0), (200.0, 150.0));
+    assert_eq!(viewport.to_window(800.0, 600.0), (1800.0, 1350.0));
+    // Smaller than the frame there's no whole number that fits
+    let small = Viewport::fit_whole(800, 600, 400, 300);
+    assert_eq!(small.to_window(800.0, 600.0), (400.0, 300.0));
+
+    let screen = Screen::new(&Config { pixel_scale: Some(2), ..Config::default() });
+    assert_eq!(screen.fit(2000, 1500).to_window(800.0, 600.0), (1800.0, 1350.0));
+    let screen = Screen::new(&Config { pixel_scale: Some(1), ..Config::default() });
+    assert_eq!(screen.fit(2000, 1500).to_window(800.0, 600.0), (2000.0, 1500.0));
+}
+
+#[test]
+fn the_scale_setting_is_auto_or_a_whole_number() {
+    let path = format!("{}/hidpi.toml", env!("CARGO_TARGET_TMPDIR"));
+    let load = |text: &str| {
+        std::fs::write(&path, text).unwrap();
+        Config::load(&path).pixel_scale
+    };
+    assert_eq!(load("[window]\nscale = 3\n"), Some(3));
+    assert_eq!(load("[window]\nscale = \"auto\"\n"), None);
+    assert_eq!(load("[window]\nscale = 9\n"), None);
+}
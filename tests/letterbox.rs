@@ -0,0 +1,49 @@
+// a window a different shape from the playfield: the picture keeps its shape between bars, and the mouse goes
+// back through the same fit
+
+use game_loop::config::Config;
+use game_loop::viewport::{self, Bars, Scaling, Viewport};
+
+const WHITE: u32 = 0xFFFFFFFF;
+const BLACK: u32 = 0xFF000000;
+
+#[test]
+fn a_wide_window_has_bars_at_the_sides_and_a_tall_one_above_and_below() {
+    let wide = Viewport::fit(800, 600, 1600, 900);
+    assert_eq!(wide.to_window(0.0, 0.0), (200.0, 0.0));
+    assert_eq!(wide.to_window(800.0, 600.0), (1400.0, 900.0));
+
+    let tall = Viewport::fit(800, 600, 800, 1000);
+    assert_eq!(tall.to_window(0.0, 0.0), (0.0, 200.0));
+    assert_eq!(tall.to_window(800.0, 600.0), (800.0, 800.0));
+}
+
+#[test]
+fn the_mouse_maps_back_to_where_it_is_over_the_picture() {
+    let viewport = Viewport::fit(800, 600, 1600, 900);
+    for (x, y) in [(0.0, 0.0), (400.0, 300.0), (123.0, 456.0), (800.0, 600.0)] {
+        let (window_x, window_y) = viewport.to_window(x, y);
+        let (field_x, field_y) = viewport.to_field(window_x, window_y);
+        assert!((field_x - x).abs() < 1e-3 && (field_y - y).abs() < 1e-3, "({x}, {y}) came back as ({field_x}, {field_y})");
+    }
+    // Over the bars the mouse is off the playfield's edge, not stretched onto it
+    let (left, _) = viewport.to_field(100.0, 450.0);
+    let (right, _) = viewport.to_field(1500.0, 450.0);
+    assert!(left < 0.0 && right > 800.0, "{left} and {right}");
+}
+
+#[test]
+fn the_bars_are_black_unless_asked_for_the_theme() {
+    assert_eq!(Config::default().bars, Bars::Black);
+    assert_eq!(viewport::find_bars("Theme"), Some(Bars::Theme));
+    assert_eq!(viewport::find_bars("grey"), None);
+
+    let frame = [WHITE; 6];
+    let viewport = Viewport::fit(3, 2, 9, 4);
+    let mut window = vec![0; 36];
+    viewport.blit(&frame, 3, 2, &mut window, BLACK, Scaling::Nearest);
+    for (i, &pixel) in window.iter().enumerate() {
+        let inside = (1..7).contains(&(i % 9)) && i / 9 < 4;
+        assert_eq!(pixel, if inside { WHITE } else { BLACK }, "pixel {i}");
+    }
+}
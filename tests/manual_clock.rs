@@ -0,0 +1,93 @@
+// the waits the game times on the clock rather than in frames, checked with a ManualClock so time only
+// moves when the test says. the keys come from a ScriptedInput, a game with nobody playing serves at once
+
+use game_loop::clock::ManualClock;
+use game_loop::config::Config;
+use game_loop::input::{Key, ScriptedInput};
+use game_loop::{Game, GameState};
+use std::time::Duration;
+
+const SEED: u64 = 1;
+const SERVE_COUNTDOWN: Duration = Duration::from_secs(3);
+
+// A 1 player game started from the title screen, with the first ball let through so it's waiting to serve
+fn game() -> (Game, ScriptedInput, ManualClock) {
+    std::env::set_var("XDG_DATA_HOME", env!("CARGO_TARGET_TMPDIR"));
+    let mut game = Game::new(Config::default(), true, SEED).expect("a headless game needs no window");
+    let input = ScriptedInput::new();
+    let clock = ManualClock::new();
+    game.set_input(Box::new(input.clone()));
+    game.set_clock(Box::new(clock.clone()));
+    game.step();
+    input.hold(Key::Enter);
+    game.step();
+    input.release(Key::Enter);
+    game.step();
+    assert_eq!(game.state(), GameState::Playing);
+    let lives = game.scoreboard().lives;
+    for _ in 0..3600 {
+        if game.scoreboard().lives < lives {
+            break;
+        }
+        game.step();
+    }
+    assert_eq!(game.scoreboard().lives, lives - 1, "the first ball was never lost");
+    (game, input, clock)
+}
+
+fn ball_position(game: &Game) -> (f32, f32) {
+    (game.balls()[0].x, game.balls()[0].y)
+}
+
+fn steps(game: &mut Game, frames: u32) {
+    for _ in 0..frames {
+        game.step();
+    }
+}
+
+#[test]
+fn serve_waits_while_the_clock_stands_still() {
+    let (mut game, _, _) = game();
+    let waiting = ball_position(&game);
+    steps(&mut game, 600);
+    assert_eq!(ball_position(&game), waiting);
+}
+
+#[test]
+fn serve_comes_once_the_countdown_has_run() {
+    let (mut game, _, clock) = game();
+    let waiting = ball_position(&game);
+    clock.advance(SERVE_COUNTDOWN - Duration::from_millis(100));
+    steps(&mut game, 10);
+    assert_eq!(ball_position(&game), waiting, "served before the countdown ran out");
+    clock.advance(Duration::from_millis(100));
+    steps(&mut game, 10);
+    assert_ne!(ball_position(&game), waiting);
+}
+
+#[test]
+fn time_paused_doesnt_count_down() {
+    let (mut game, input, clock) = game();
+    let waiting = ball_position(&game);
+    clock.advance(Duration::from_secs(1));
+    game.step();
+    input.hold(Key::P);
+    game.step();
+    input.release(Key::P);
+    game.step();
+    assert_eq!(game.state(), GameState::Paused);
+    clock.advance(Duration::from_secs(10));
+    game.step();
+    input.hold(Key::P);
+    game.step();
+    input.release(Key::P);
+    game.step();
+    assert_eq!(game.state(), GameState::Playing);
+    // A second on the clock before the pause, so two more to go
+    clock.advance(Duration::from_millis(1900));
+    steps(&mut game, 10);
+    assert_eq!(ball_position(&game), waiting, "the time paused counted towards the serve");
+    clock.advance(Duration::from_millis(100));
+    steps(&mut game, 10);
+    assert_ne!(ball_position(&game), waiting);
+}
@@ -0,0 +1,212 @@
+// property tests for the physics helpers: each invariant is checked over many randomly made cases. there's
+// no proptest to hand, so the cases come from the game's own seeded Rng, and a failure names the case's seed
+// so it can be run again on its own with check_seed
+
+use game_loop::bricks::{self, Brick};
+use game_loop::physics;
+use game_loop::rng::Rng;
+use game_loop::GameObject;
+
+const CASES: u64 = 2000;
+const FIELD_WIDTH: f32 = 800.0;
+const FIELD_HEIGHT: f32 = 600.0;
+const MAX_SPEED: f32 = 2000.0;
+const TOLERANCE: f32 = 1e-3; // Relative, for float rounding in the trigonometry
+const SLACK: f32 = 1e-3; // pixels
+
+// Run the property over CASES cases, each from its own seed
+fn check(property: impl Fn(&mut Rng) -> Result<(), String>) {
+    for seed in 0..CASES {
+        check_seed(seed, &property);
+    }
+}
+
+fn check_seed(seed: u64, property: impl Fn(&mut Rng) -> Result<(), String>) {
+    let mut rng = Rng::new(seed);
+    if let Err(message) = property(&mut rng) {
+        panic!("case with seed {} failed: {}", seed, message);
+    }
+}
+
+fn ball(rng: &mut Rng) -> GameObject {
+    let size = rng.range(4.0, 30.0);
+    GameObject {
+        x: rng.range(0.0, FIELD_WIDTH - size),
+        y: rng.range(0.0, FIELD_HEIGHT - size),
+        width: size,
+        height: size,
+        vel_x: rng.range(-MAX_SPEED, MAX_SPEED),
+        vel_y: rng.range(-MAX_SPEED, MAX_SPEED),
+    }
+}
+
+fn paddle(rng: &mut Rng) -> GameObject {
+    let width = rng.range(20.0, FIELD_WIDTH);
+    GameObject {
+        x: rng.range(0.0, FIELD_WIDTH - width),
+        y: FIELD_HEIGHT - 30.0,
+        width,
+        height: rng.range(5.0, 20.0),
+        vel_x: 0.0,
+        vel_y: 0.0,
+    }
+}
+
+fn speed(object: &GameObject) -> f32 {
+    object.vel_x.hypot(object.vel_y)
+}
+
+fn same_speed(before: f32, after: &GameObject) -> Result<(), String> {
+    if (speed(after) - before).abs() <= before * TOLERANCE {
+        Ok(())
+    } else {
+        Err(format!("speed went from {} to {}", before, speed(after)))
+    }
+}
+
+#[test]
+fn ball_never_leaves_the_field_horizontally() {
+    check(|rng| {
+        let mut ball = ball(rng);
+        let max_travel = ball.width / 2.0;
+        for _ in 0..200 {
+            let time = rng.range(0.0, 0.1);
+            let substeps = physics::substeps(&ball, time, max_travel);
+            for _ in 0..substeps {
+                physics::move_ball(&mut ball, time / substeps as f32, FIELD_WIDTH);
+                if ball.x < 0.0 || ball.x + ball.width > FIELD_WIDTH {
+                    return Err(format!("ball at x {} width {} is outside the field", ball.x, ball.width));
+                }
+            }
+        }
+        Ok(())
+    });
+}
+
+#[test]
+fn substeps_are_never_longer_than_the_max_travel() {
+    check(|rng| {
+        let ball = ball(rng);
+        let time = rng.range(0.0, 0.5);
+        let max_travel = ball.width / 2.0;
+        let substeps = physics::substeps(&ball, time, max_travel);
+        let step = ball.vel_x.abs().max(ball.vel_y.abs()) * time / substeps as f32;
+        if substeps >= 1 && step <= max_travel * (1.0 + TOLERANCE) {
+            Ok(())
+        } else {
+            Err(format!("{} substeps of {} for a max travel of {}", substeps, step, max_travel))
+        }
+    });
+}
+
+#[test]
+fn no_max_travel_is_one_substep() {
+    check(|rng| {
+        let ball = ball(rng);
+        match physics::substeps(&ball, rng.range(0.0, 0.5), 0.0) {
+            1 => Ok(()),
+            substeps => Err(format!("{} substeps with no max travel", substeps)),
+        }
+    });
+}
+
+#[test]
+fn paddle_stays_in_bounds_for_any_input() {
+    check(|rng| {
+        let mut paddle = paddle(rng);
+        for _ in 0..200 {
+            // Anything from holding a key to a mouse flick across the whole window in one frame
+            paddle.vel_x = rng.range(-5.0, 5.0) * FIELD_WIDTH;
+            paddle.x += paddle.vel_x * rng.range(0.0, 0.1);
+            physics::clamp_paddle(&mut paddle, FIELD_WIDTH);
+            if paddle.x < 0.0 || paddle.x + paddle.width > FIELD_WIDTH {
+                return Err(format!("paddle at x {} width {} is outside the field", paddle.x, paddle.width));
+            }
+        }
+        Ok(())
+    });
+}
+
+#[test]
+fn wall_bounces_keep_the_speed() {
+    check(|rng| {
+        let mut ball = ball(rng);
+        let before = speed(&ball);
+        for _ in 0..100 {
+            physics::move_ball(&mut ball, rng.range(0.0, 0.05), FIELD_WIDTH);
+        }
+        same_speed(before, &ball)
+    });
+}
+
+#[test]
+fn paddle_bounces_keep_the_speed_and_send_the_ball_away() {
+    check(|rng| {
+        let paddle = paddle(rng);
+        let mut ball = ball(rng);
+        ball.x = rng.range(paddle.x - ball.width, paddle.x + paddle.width);
+        let before = speed(&ball);
+        let direction = if rng.coin_flip() { 1.0 } else { -1.0 };
+        physics::paddle_bounce(&mut ball, &paddle, direction);
+        if ball.vel_y * direction <= 0.0 {
+            return Err(format!("ball heads {} after a bounce sending it {}", ball.vel_y, direction));
+        }
+        same_speed(before, &ball)
+    });
+}
+
+#[test]
+fn turning_keeps_the_speed() {
+    check(|rng| {
+        let ball = ball(rng);
+        let turned = physics::turned(&ball, rng.range(-3.2, 3.2));
+        same_speed(speed(&ball), &turned)
+    });
+}
+
+#[test]
+fn obstacle_bounces_keep_the_speed_and_push_the_ball_out() {
+    check(|rng| {
+        let block = GameObject {
+            x: rng.range(100.0, 600.0),
+            y: rng.range(100.0, 400.0),
+            width: rng.range(10.0, 100.0),
+            height: rng.range(10.0, 100.0),
+            vel_x: 0.0,
+            vel_y: 0.0,
+        };
+        let mut ball = ball(rng);
+        ball.x = rng.range(block.x - ball.width, block.x + block.width);
+        ball.y = rng.range(block.y - ball.height, block.y + block.height);
+        let before = speed(&ball);
+        bricks::bounce_off(&mut ball, &block);
+        // Put back flush against the block, give or take the rounding in x + width
+        let inside = ball.x + ball.width > block.x + SLACK
+            && ball.x < block.x + block.width - SLACK
+            && ball.y + ball.height > block.y + SLACK
+            && ball.y < block.y + block.height - SLACK;
+        if inside {
+            return Err(format!("ball at ({}, {}) is still inside the block", ball.x, ball.y));
+        }
+        same_speed(before, &ball)
+    });
+}
+
+#[test]
+fn brick_bounces_keep_the_speed() {
+    check(|rng| {
+        let grid = vec![vec![1 + rng.index(3) as u32; 10]; 1 + rng.index(8)];
+        let mut wall: Vec<Brick> = bricks::from_grid(&grid, FIELD_WIDTH);
+        let count = wall.len();
+        let target = &wall[rng.index(count)].body;
+        let mut ball = ball(rng);
+        ball.x = rng.range(target.x - ball.width + 0.1, target.x + target.width - 0.1);
+        ball.y = rng.range(target.y - ball.height + 0.1, target.y + target.height - 0.1);
+        let before = speed(&ball);
+        let mut index = bricks::grid(&wall);
+        if bricks::collide(&mut ball, &mut wall, &mut index, rng.coin_flip()).is_none() {
+            return Err("ball overlapping a brick didn't hit it".to_string());
+        }
+        same_speed(before, &ball)
+    });
+}
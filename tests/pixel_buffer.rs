@@ -0,0 +1,46 @@
+// the software renderer's fills, which write whole rows at a time and have to stop at the buffer's edges
+
+use game_loop::renderer::{PixelBuffer, Renderer};
+
+const WIDTH: usize = 8;
+const HEIGHT: usize = 6;
+const RED: u32 = 0xFFFF0000;
+
+#[test]
+fn a_rectangle_fills_exactly_its_pixels() {
+    let mut pixels = vec![0; WIDTH * HEIGHT];
+    PixelBuffer::new(&mut pixels, WIDTH).fill_rect(2.0, 1.0, 3.0, 2.0, RED);
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let inside = (2..5).contains(&x) && (1..3).contains(&y);
+            assert_eq!(pixels[y * WIDTH + x] == RED, inside, "({x}, {y})");
+        }
+    }
+}
+
+#[test]
+fn rectangles_past_the_edges_are_cut_off_not_wrapped() {
+    let mut pixels = vec![0; WIDTH * HEIGHT];
+    let mut buffer = PixelBuffer::new(&mut pixels, WIDTH);
+    buffer.fill_rect(6.0, 4.0, 5.0, 5.0, RED);
+    buffer.fill_rect(20.0, 0.0, 5.0, 5.0, RED);
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let inside = x >= 6 && y >= 4;
+            assert_eq!(pixels[y * WIDTH + x] == RED, inside, "({x}, {y})");
+        }
+    }
+}
+
+#[test]
+fn text_at_the_edge_stays_in_the_buffer() {
+    let mut pixels = vec![0; WIDTH * HEIGHT];
+    PixelBuffer::new(&mut pixels, WIDTH).draw_text(5, 3, "88", 2, RED);
+    assert!(pixels.contains(&RED));
+    // Nothing wrapped round to the left of where the text starts
+    for y in 0..HEIGHT {
+        for x in 0..5 {
+            assert_ne!(pixels[y * WIDTH + x], RED, "({x}, {y})");
+        }
+    }
+}
@@ -0,0 +1,45 @@
+// pools keep finished items' slots and hand them out again, so they only grow to the most there have been at once
+
+use game_loop::particles::Particles;
+use game_loop::pool::Pool;
+
+#[test]
+fn finished_slots_are_reused() {
+    let mut pool = Pool::default();
+    for round in 0..5 {
+        for i in 0..8 {
+            pool.spawn(round * 10 + i);
+        }
+        pool.retain(|_| false);
+    }
+    assert!(pool.is_empty());
+    assert_eq!(pool.stats().slots, 8);
+}
+
+#[test]
+fn retain_keeps_the_ones_asked_for() {
+    let mut pool = Pool::default();
+    for i in 0..10 {
+        pool.spawn(i);
+    }
+    pool.retain(|&mut i| i % 3 == 0);
+    let mut kept: Vec<i32> = pool.iter().copied().collect();
+    kept.sort();
+    assert_eq!(kept, vec![0, 3, 6, 9]);
+    assert_eq!(pool.stats().live, 4);
+    assert_eq!(pool.stats().slots, 10);
+}
+
+#[test]
+fn bursts_of_particles_dont_keep_growing_the_pool() {
+    let mut particles = Particles::new();
+    particles.burst(400.0, 300.0, 100, 300.0, 0xFFFFFFFF);
+    let slots = particles.stats().slots;
+    for _ in 0..20 {
+        while !particles.is_empty() {
+            particles.update(1.0 / 60.0);
+        }
+        particles.burst(400.0, 300.0, 100, 300.0, 0xFFFFFFFF);
+    }
+    assert_eq!(particles.stats().slots, slots);
+}
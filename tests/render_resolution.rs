@@ -0,0 +1,86 @@
+// drawing at a render resolution lower than the playfield, and scaling the frame back up to the window
+
+use game_loop::config::Config;
+use game_loop::renderer::{PixelBuffer, Renderer, Scaled};
+use game_loop::viewport::{Scaling, Viewport};
+
+const WHITE: u32 = 0xFFFFFFFF;
+const BLACK: u32 = 0xFF000000;
+
+#[test]
+fn the_render_size_keeps_the_playfield_shape_when_only_one_side_is_set() {
+    let mut config = Config { window_width: 800, window_height: 600, ..Config::default() };
+    assert_eq!(config.render_size(), (800, 600));
+    config.render_width = Some(400);
+    assert_eq!(config.render_size(), (400, 300));
+    config.render_width = None;
+    config.render_height = Some(150);
+    assert_eq!(config.render_size(), (200, 150));
+    config.render_width = Some(320);
+    assert_eq!(config.render_size(), (320, 150));
+}
+
+#[test]
+fn the_playfield_is_drawn_scaled_down_into_the_frame() {
+    let (width, height) = (400, 300);
+    let mut pixels = vec![BLACK; width * height];
+    let mut buffer = PixelBuffer::new(&mut pixels, width);
+    let mut scaled = Scaled::new(&mut buffer, 800, 600);
+    assert_eq!(scaled.size(), (800, 600));
+    scaled.fill_rect(100.0, 200.0, 60.0, 20.0, WHITE);
+    for y in 0..height {
+        for x in 0..width {
+            let inside = (50..80).contains(&x) && (100..110).contains(&y);
+            assert_eq!(pixels[y * width + x] == WHITE, inside, "({x}, {y})");
+        }
+    }
+}
+
+#[test]
+fn nearest_keeps_the_frame_colours_and_bilinear_blends_them() {
+    // A 2x2 frame shown in a 4x4 window
+    let frame = [BLACK, WHITE, WHITE, BLACK];
+    let viewport = Viewport::fit(2, 2, 4, 4);
+    let mut window = vec![0; 16];
+
+    viewport.blit(&frame, 2, 2, &mut window, 0, Scaling::Nearest);
+    assert!(window.iter().all(|&pixel| pixel == BLACK || pixel == WHITE));
+    assert_eq!(window[0], BLACK);
+    assert_eq!(window[3], WHITE);
+
+    viewport.blit(&frame, 2, 2, &mut window, 0, Scaling::Bilinear);
+    assert_eq!(window[0], BLACK, "the corners are the frame's corners");
+    assert_eq!(window[15], BLACK);
+    let grey = window[5] & 0xFF;
+    assert!(grey > 0 && grey < 0xFF, "nothing blended in the middle: {:08X}", window[5]);
+}
+
+#[test]
+fn bars_fill_the_window_around_a_differently_shaped_frame() {
+    let frame = [WHITE; 4];
+    let viewport = Viewport::fit(2, 2, 6, 4);
+    let mut window = vec![0; 24];
+    for scaling in [Scaling::Nearest, Scaling::Bilinear] {
+        viewport.blit(&frame, 2, 2, &mut window, BLACK, scaling);
+        for (i, &pixel) in window.iter().enumerate() {
+            let inside = (1..5).contains(&(i % 6));
+            assert_eq!(pixel == WHITE, inside, "pixel {i} with {scaling:?}");
+        }
+    }
+}
+
+#[test]
+fn a_whole_game_frame_draws_at_a_quarter_of_the_pixels() {
+    std::env::set_var("XDG_DATA_HOME", env!("CARGO_TARGET_TMPDIR"));
+    let config = Config { render_width: Some(400), ..Config::default() };
+    let (width, height) = config.render_size();
+    let mut game = game_loop::Game::new(config, true, 1).expect("a headless game needs no window");
+    for _ in 0..60 {
+        game.step();
+    }
+    let mut pixels = vec![0; width * height];
+    let mut buffer = PixelBuffer::new(&mut pixels, width);
+    game.render(&mut Scaled::new(&mut buffer, 800, 600));
+    let colours: std::collections::HashSet<u32> = pixels.iter().copied().collect();
+    assert!(colours.len() > 2, "only {} colours drawn", colours.len());
+}
@@ -0,0 +1,127 @@
+// the game's key handling, driven by a ScriptedInput standing in for a player at the keyboard of a headless
+// game. each frame reads the keys held at the time, so a tap is a key held for one step and let go
+
+use game_loop::config::Config;
+use game_loop::input::{Key, ScriptedInput};
+use game_loop::{Game, GameState};
+
+const SEED: u64 = 1;
+const WIDTH: usize = 800;
+
+fn game() -> (Game, ScriptedInput) {
+    // The title screen and games read and save files in the data directory, keep them out of the real one
+    std::env::set_var("XDG_DATA_HOME", env!("CARGO_TARGET_TMPDIR"));
+    let config = Config {
+        window_width: WIDTH,
+        window_height: 600,
+        ..Config::default()
+    };
+    let mut game = Game::new(config, true, SEED).expect("a headless game needs no window");
+    let input = ScriptedInput::new();
+    game.set_input(Box::new(input.clone()));
+    game.step();
+    (game, input)
+}
+
+fn tap(game: &mut Game, input: &ScriptedInput, key: Key) {
+    input.hold(key);
+    game.step();
+    input.release(key);
+    game.step();
+}
+
+fn hold(game: &mut Game, input: &ScriptedInput, key: Key, frames: u32) {
+    input.hold(key);
+    for _ in 0..frames {
+        game.step();
+    }
+    input.release(key);
+}
+
+// Start a 1 player game from the title screen and serve
+fn start_solo(game: &mut Game, input: &ScriptedInput) {
+    tap(game, input, Key::Enter);
+    tap(game, input, Key::Space);
+}
+
+#[test]
+fn starts_on_the_title_screen() {
+    let (game, _) = game();
+    assert_eq!(game.state(), GameState::Menu);
+}
+
+#[test]
+fn enter_starts_the_selected_game() {
+    let (mut game, input) = game();
+    tap(&mut game, &input, Key::Enter);
+    assert_eq!(game.state(), GameState::Playing);
+}
+
+// Long enough to cross from the middle, not so long that the ball is lost and the paddle waits for the serve
+#[test]
+fn right_arrow_moves_the_paddle_right() {
+    let (mut game, input) = game();
+    start_solo(&mut game, &input);
+    hold(&mut game, &input, Key::Right, 75);
+    assert_eq!(game.paddle().x, WIDTH as f32 - game.paddle().width);
+}
+
+#[test]
+fn left_arrow_moves_the_paddle_left() {
+    let (mut game, input) = game();
+    start_solo(&mut game, &input);
+    hold(&mut game, &input, Key::Left, 75);
+    assert_eq!(game.paddle().x, 0.0);
+}
+
+#[test]
+fn paddle_stays_put_with_no_keys_held() {
+    let (mut game, input) = game();
+    start_solo(&mut game, &input);
+    let x = game.paddle().x;
+    for _ in 0..30 {
+        game.step();
+    }
+    assert_eq!(game.paddle().x, x);
+}
+
+#[test]
+fn pause_key_pauses_and_resumes() {
+    let (mut game, input) = game();
+    start_solo(&mut game, &input);
+    tap(&mut game, &input, Key::P);
+    assert_eq!(game.state(), GameState::Paused);
+    // Nothing moves while paused
+    let ball = (game.balls()[0].x, game.balls()[0].y);
+    for _ in 0..30 {
+        game.step();
+    }
+    assert_eq!((game.balls()[0].x, game.balls()[0].y), ball);
+    tap(&mut game, &input, Key::P);
+    assert_eq!(game.state(), GameState::Playing);
+}
+
+#[test]
+fn quitting_from_the_pause_screen_goes_back_to_the_title_screen() {
+    let (mut game, input) = game();
+    start_solo(&mut game, &input);
+    tap(&mut game, &input, Key::Escape);
+    assert_eq!(game.state(), GameState::Paused);
+    tap(&mut game, &input, Key::Escape);
+    assert_eq!(game.state(), GameState::Menu);
+    assert!(game.is_running());
+}
+
+#[test]
+fn quit_on_the_title_screen_stops_the_game() {
+    let (mut game, input) = game();
+    tap(&mut game, &input, Key::Escape);
+    assert!(!game.is_running());
+}
+
+#[test]
+fn closing_the_window_stops_the_game() {
+    let (game, input) = game();
+    input.close();
+    assert!(!game.is_running());
+}